@@ -0,0 +1,280 @@
+use crate::components::command_palette::open_command_palette;
+use crate::components::control_bar::{rerun_failed, start_run};
+use crate::components::shortcuts::open_shortcuts;
+use crate::components::tab_bar_item::close_project;
+use crate::components::toolbar::open_project;
+use crate::state::{State, StateProvider, TestListState};
+use crate::utils::logger::warning;
+use crate::utils::runner::RunScope;
+use gpui::{App, BorrowAppContext, KeyBinding, Keystroke, Window, actions};
+use std::collections::HashMap;
+
+actions!(
+    apollo,
+    [
+        RunTests,
+        RerunFailed,
+        CancelRun,
+        SelectNextTest,
+        SelectPreviousTest,
+        SelectNextFailure,
+        NextTab,
+        PreviousTab,
+        CloseTab,
+        OpenProject,
+        FocusSearch,
+        ShowShortcuts,
+        CommandPalette,
+    ]
+);
+
+/// An action that can be bound to keys in the keymap file
+pub struct Shortcut {
+    /// The name used for the action in the keymap file
+    pub name: &'static str,
+    /// Shown in the shortcuts list
+    pub description: &'static str,
+    /// Used when the keymap file doesn't bind the action, secondary is ctrl on linux and windows and cmd on macos
+    pub default_keys: &'static str,
+    /// What the action does, also used by the command palette
+    pub run: fn(&mut Window, &mut App),
+}
+
+pub const SHORTCUTS: &[Shortcut] = &[
+    Shortcut {
+        name: "run_tests",
+        description: "Run all tests",
+        default_keys: "secondary-r",
+        run: run_tests,
+    },
+    Shortcut {
+        name: "rerun_failed",
+        description: "Re-run failed tests",
+        default_keys: "secondary-shift-r",
+        run: rerun_failed_tests,
+    },
+    Shortcut {
+        name: "cancel_run",
+        description: "Stop running tests",
+        default_keys: "secondary-.",
+        run: cancel_run,
+    },
+    Shortcut {
+        name: "select_next_test",
+        description: "Select the next test",
+        default_keys: "down",
+        run: select_next_test,
+    },
+    Shortcut {
+        name: "select_previous_test",
+        description: "Select the previous test",
+        default_keys: "up",
+        run: select_previous_test,
+    },
+    Shortcut {
+        name: "select_next_failure",
+        description: "Jump to the next failed test",
+        default_keys: "f8",
+        run: select_next_failure,
+    },
+    Shortcut {
+        name: "next_tab",
+        description: "Switch to the next project",
+        default_keys: "ctrl-tab",
+        run: next_tab,
+    },
+    Shortcut {
+        name: "previous_tab",
+        description: "Switch to the previous project",
+        default_keys: "ctrl-shift-tab",
+        run: previous_tab,
+    },
+    Shortcut {
+        name: "close_tab",
+        description: "Close the current project",
+        default_keys: "secondary-w",
+        run: close_active_tab,
+    },
+    Shortcut {
+        name: "open_project",
+        description: "Open a project",
+        default_keys: "secondary-o",
+        run: open_project_prompt,
+    },
+    Shortcut {
+        name: "focus_search",
+        description: "Search the tests",
+        default_keys: "secondary-f",
+        run: focus_search,
+    },
+    Shortcut {
+        name: "show_shortcuts",
+        description: "Show the keyboard shortcuts",
+        default_keys: "secondary-/",
+        run: open_shortcuts,
+    },
+    Shortcut {
+        name: "command_palette",
+        description: "Open the command palette",
+        default_keys: "secondary-shift-p",
+        run: open_command_palette,
+    },
+];
+
+/// Makes the binding for a shortcut, None if the name isn't a shortcut
+fn key_binding(name: &str, keys: &str) -> Option<KeyBinding> {
+    Some(match name {
+        "run_tests" => KeyBinding::new(keys, RunTests, None),
+        "rerun_failed" => KeyBinding::new(keys, RerunFailed, None),
+        "cancel_run" => KeyBinding::new(keys, CancelRun, None),
+        "select_next_test" => KeyBinding::new(keys, SelectNextTest, None),
+        "select_previous_test" => KeyBinding::new(keys, SelectPreviousTest, None),
+        "select_next_failure" => KeyBinding::new(keys, SelectNextFailure, None),
+        "next_tab" => KeyBinding::new(keys, NextTab, None),
+        "previous_tab" => KeyBinding::new(keys, PreviousTab, None),
+        "close_tab" => KeyBinding::new(keys, CloseTab, None),
+        "open_project" => KeyBinding::new(keys, OpenProject, None),
+        "focus_search" => KeyBinding::new(keys, FocusSearch, None),
+        "show_shortcuts" => KeyBinding::new(keys, ShowShortcuts, None),
+        "command_palette" => KeyBinding::new(keys, CommandPalette, None),
+        _ => return None,
+    })
+}
+
+/// Binds the keys in the keymap to their actions, shortcuts with no keys are left unbound
+pub fn bind_shortcuts(keymap: &HashMap<String, String>, cx: &mut App) {
+    let mut bindings = Vec::new();
+
+    for (name, keys) in keymap {
+        if keys.trim().is_empty() {
+            continue;
+        }
+
+        // KeyBinding::new panics on keys it can't parse so they are checked first
+        if let Some(invalid) = keys
+            .split_whitespace()
+            .find(|x| Keystroke::parse(x).is_err())
+        {
+            warning!(
+                "Skipped the {} shortcut, {} is not a valid key",
+                name,
+                invalid
+            );
+            continue;
+        }
+
+        match key_binding(name, keys) {
+            Some(res) => bindings.push(res),
+            None => warning!("Skipped {} in the keymap as it is not a shortcut", name),
+        }
+    }
+
+    cx.bind_keys(bindings)
+}
+
+/// The keys bound to the shortcut formatted for display e.g. Ctrl-R, empty if it is unbound
+pub fn shortcut_keys(name: &str, cx: &App) -> String {
+    let keys = cx.state().keymap.get(name).cloned().unwrap_or_default();
+
+    keys.split_whitespace()
+        .filter_map(|x| Keystroke::parse(x).ok())
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn run_tests(_window: &mut Window, cx: &mut App) {
+    start_run(RunScope::All, cx)
+}
+
+pub fn rerun_failed_tests(_window: &mut Window, cx: &mut App) {
+    rerun_failed(cx)
+}
+
+pub fn cancel_run(window: &mut Window, cx: &mut App) {
+    cx.update_global::<State, ()>(|global, _| global.cancel_run(global.active_project));
+    window.refresh()
+}
+
+/// Moves the selection to the test after or before the selected one in the test list
+fn select_adjacent_test(forward: bool, window: &mut Window, cx: &mut App) {
+    let index = cx.update_global::<TestListState, _>(|global, _cx| {
+        _cx.state()
+            .active_project()
+            .and_then(|x| global.adjacent_test(x, forward))
+    });
+
+    if let Some(res) = index {
+        cx.update_global::<State, ()>(|global, _| global.select_test(res));
+        window.refresh()
+    }
+}
+
+pub fn select_next_test(window: &mut Window, cx: &mut App) {
+    select_adjacent_test(true, window, cx)
+}
+
+pub fn select_previous_test(window: &mut Window, cx: &mut App) {
+    select_adjacent_test(false, window, cx)
+}
+
+pub fn select_next_failure(window: &mut Window, cx: &mut App) {
+    let index = cx.update_global::<TestListState, _>(|global, _cx| {
+        _cx.state()
+            .active_project()
+            .and_then(|x| global.next_failure(x))
+    });
+
+    if let Some(res) = index {
+        cx.update_global::<State, ()>(|global, _| global.select_test(res));
+        window.refresh()
+    }
+}
+
+/// Makes the next or previous project tab active, wrapping around at either end
+fn switch_tab(forward: bool, window: &mut Window, cx: &mut App) {
+    cx.update_global::<State, ()>(|global, _| {
+        let count = global.open_projects.len();
+        if count == 0 {
+            return;
+        }
+
+        let current = global
+            .open_projects
+            .iter()
+            .position(|x| x.id == global.active_project)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+
+        global.set_active_project(global.open_projects[next].id)
+    });
+    window.refresh()
+}
+
+pub fn next_tab(window: &mut Window, cx: &mut App) {
+    switch_tab(true, window, cx)
+}
+
+pub fn previous_tab(window: &mut Window, cx: &mut App) {
+    switch_tab(false, window, cx)
+}
+
+pub fn close_active_tab(window: &mut Window, cx: &mut App) {
+    if cx.state().has_active_project() {
+        close_project(cx.state().active_project, cx);
+        window.refresh()
+    }
+}
+
+pub fn focus_search(window: &mut Window, cx: &mut App) {
+    let handle = cx.update_global::<TestListState, _>(|global, _cx| global.search_focus(_cx));
+    window.focus(&handle)
+}
+
+pub fn open_project_prompt(_window: &mut Window, cx: &mut App) {
+    open_project(cx)
+}
@@ -0,0 +1,239 @@
+use crate::AlertHandler;
+use crate::components::collapsable_stack::CollapsableStack;
+use crate::components::test_info::open_in_editor;
+use crate::state::{SourceViewState, test_id};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::panic::{Frame, FrameKind};
+use crate::utils::source::resolve_file;
+use cargo_ptest::parse::ParsedTest;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AnyElement, App, ElementId, InteractiveElement, IntoElement, MouseButton, ParentElement,
+    RenderOnce, SharedString, Styled, Window, div, px,
+};
+use std::path::{Path, PathBuf};
+
+#[derive(IntoElement)]
+/// A failed test's backtrace, the project's own frames are shown while std and dependency frames are folded away
+pub struct BacktraceView {
+    pub frames: Vec<Frame>,
+    /// The test the backtrace is from, a clicked frame is shown in the source viewer until another test is selected
+    pub test: ParsedTest,
+    /// Used to tell the project's frames apart and find their files
+    pub project_path: PathBuf,
+}
+
+/// Describes a run of folded frames, e.g. "4 std and dependency frames"
+fn folded_label(frames: &[(Frame, FrameKind)]) -> String {
+    let std = frames.iter().any(|x| x.1 == FrameKind::Std);
+    let dependency = frames.iter().any(|x| x.1 == FrameKind::Dependency);
+    let kind = match (std, dependency) {
+        (true, true) => "std and dependency",
+        (false, true) => "dependency",
+        _ => "std",
+    };
+
+    format!(
+        "{} {} frame{}",
+        frames.len(),
+        kind,
+        if frames.len() == 1 { "" } else { "s" }
+    )
+}
+
+fn frame_row(
+    frame: Frame,
+    kind: FrameKind,
+    test: &ParsedTest,
+    project_path: &Path,
+    cx: &App,
+) -> AnyElement {
+    let style = cx.style();
+    let project = kind == FrameKind::Project;
+    let location = frame.location.map(|x| {
+        let text = format!("{}:{}:{}", x.file, x.line, x.column);
+        (text, x.file, x.line, x.column)
+    });
+
+    div()
+        .id(("backtrace-frame", frame.index))
+        .flex()
+        .flex_row()
+        .items_center()
+        .w_full()
+        .px(px(4.0))
+        .py(px(2.0))
+        .rounded(style.rounding.abs())
+        .border_l(px(2.0))
+        .border_color(if project {
+            &style.primary_colour
+        } else {
+            &style.secondary_bg_colour
+        })
+        .hover(|_style| _style.bg(Colour::Rgba(0xffffff11)))
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_grow()
+                .min_w(px(0.0))
+                .overflow_hidden()
+                .text_sm()
+                .child(
+                    div()
+                        .text_color(if project {
+                            &style.text_colour
+                        } else {
+                            &style.muted_text_colour
+                        })
+                        .child(format!("{}: {}", frame.index, frame.function)),
+                )
+                .when_some(location.clone(), |_self, (text, _, _, _)| {
+                    _self.child(
+                        div()
+                            .text_xs()
+                            .text_color(&style.muted_text_colour)
+                            .child(text),
+                    )
+                }),
+        )
+        .when_some(location, |_self, (_, file, line, column)| {
+            let test = test.clone();
+            let project_path = project_path.to_path_buf();
+            let editor_file = file.clone();
+            let editor_project_path = project_path.clone();
+
+            _self
+                .cursor_pointer()
+                .on_mouse_down(
+                    MouseButton::Left,
+                    move |_e, _window, _cx| match resolve_file(&project_path, &file) {
+                        Some(path) => {
+                            _cx.global_mut::<SourceViewState>().show_frame(
+                                &test,
+                                path,
+                                line.saturating_sub(1),
+                            );
+                            _window.refresh()
+                        }
+                        None => _cx.alert_warning(
+                            None::<String>,
+                            format!("Could not find {}", file),
+                            Some(3000),
+                        ),
+                    },
+                )
+                .child(
+                    div()
+                        .id(("backtrace-frame-editor", frame.index))
+                        .flex_none()
+                        .ml(style.margin.abs())
+                        .px(px(6.0))
+                        .rounded(style.rounding.abs())
+                        .text_xs()
+                        .text_color(&style.muted_text_colour)
+                        .hover(|_style| _style.bg(Colour::Rgba(0xffffff22)))
+                        .on_mouse_down(MouseButton::Left, move |_e, _window, _cx| {
+                            // stops the frame also being opened in the source viewer
+                            _cx.stop_propagation();
+                            match resolve_file(&editor_project_path, &editor_file) {
+                                Some(path) => open_in_editor(&path, line, column, _cx),
+                                None => _cx.alert_warning(
+                                    None::<String>,
+                                    format!("Could not find {}", editor_file),
+                                    Some(3000),
+                                ),
+                            }
+                        })
+                        .child("Editor"),
+                )
+        })
+        .into_any_element()
+}
+
+impl RenderOnce for BacktraceView {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let collapsed = window.use_keyed_state("backtrace-collapsed", cx, |_, _| false);
+
+        // the project's frames are shown on their own with the frames between them folded together
+        let mut groups: Vec<Vec<(Frame, FrameKind)>> = Vec::new();
+        for frame in self.frames.iter() {
+            let kind = frame.kind(&self.project_path);
+            match groups.last_mut() {
+                Some(group)
+                    if kind != FrameKind::Project
+                        && group.last().is_some_and(|x| x.1 != FrameKind::Project) =>
+                {
+                    group.push((frame.clone(), kind))
+                }
+                _ => groups.push(vec![(frame.clone(), kind)]),
+            }
+        }
+
+        let mut rows = Vec::new();
+        // each test's folds are kept separately so opening one test's frames doesn't open another's
+        let fold_key = SharedString::from(format!("backtrace-folded {}", test_id(&self.test)));
+
+        for (index, group) in groups.into_iter().enumerate() {
+            if group[0].1 == FrameKind::Project {
+                let (frame, kind) = group.into_iter().next().unwrap();
+                rows.push(frame_row(frame, kind, &self.test, &self.project_path, cx));
+                continue;
+            }
+
+            let folded =
+                window.use_keyed_state(ElementId::from((fold_key.clone(), index)), cx, |_, _| true);
+            let is_folded = *folded.read(cx);
+            let label = folded_label(&group);
+            let frames = if is_folded {
+                Vec::new()
+            } else {
+                group
+                    .into_iter()
+                    .map(|(frame, kind)| frame_row(frame, kind, &self.test, &self.project_path, cx))
+                    .collect()
+            };
+
+            rows.push(
+                CollapsableStack::new(("backtrace-fold", index))
+                    .collapsed(is_folded)
+                    .header(
+                        div()
+                            .text_sm()
+                            .text_color(&cx.style().muted_text_colour)
+                            .child(label),
+                    )
+                    .chevron_colour(&cx.style().muted_text_colour)
+                    .hover_colour(Colour::Rgba(0xffffff11))
+                    .indent(Size::Px(8.0))
+                    .on_toggle(move |_collapsed, _window, _cx| {
+                        folded.update(_cx, |x, _| *x = *_collapsed);
+                        _window.refresh()
+                    })
+                    .children(frames)
+                    .render(window, cx)
+                    .into_any_element(),
+            )
+        }
+
+        let is_collapsed = *collapsed.read(cx);
+        let frame_count = self.frames.len();
+
+        CollapsableStack::new("backtrace")
+            .collapsed(is_collapsed)
+            .header(div().child(format!(
+                "Backtrace ({} frame{})",
+                frame_count,
+                if frame_count == 1 { "" } else { "s" }
+            )))
+            .chevron_colour(&cx.style().muted_text_colour)
+            .hover_colour(Colour::Rgba(0xffffff22))
+            .border_colour(&cx.style().separator_colour)
+            .indent(Size::Px(4.0))
+            .on_toggle(move |_collapsed, _window, _cx| {
+                collapsed.update(_cx, |x, _| *x = *_collapsed);
+                _window.refresh()
+            })
+            .children(rows)
+    }
+}
@@ -1 +1,142 @@
-pub struct CollapsableStack {}
+use crate::style::{Colour, Size};
+use crate::widgets::core::icon::{Icon, Icons};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AnyElement, App, AppContext, ElementId, InteractiveElement, IntoElement, MouseButton,
+    ParentElement, RenderOnce, Styled, Window, div, px,
+};
+use std::rc::Rc;
+
+/// A header that shows or hides the elements stacked below it when clicked
+///
+/// The stack doesn't remember whether it's collapsed, the owner stores that and updates it in on_toggle
+#[derive(IntoElement)]
+pub struct CollapsableStack {
+    id: ElementId,
+    /// Shown to the right of the chevron
+    header: Option<AnyElement>,
+    /// The elements shown below the header while the stack is expanded
+    children: Vec<AnyElement>,
+    /// Whether the children are hidden
+    collapsed: bool,
+    /// How far the children are indented from the left of the header in pixels
+    indent: Size,
+    /// Colour of the chevron
+    chevron_colour: Colour,
+    /// Background colour of the header when hovered
+    hover_colour: Option<Colour>,
+    /// Colour of the line under the header
+    border_colour: Option<Colour>,
+    /// Function ran when the header is clicked, given whether the stack should now be collapsed
+    on_toggle: Option<Rc<dyn Fn(&bool, &mut Window, &mut App) + 'static>>,
+}
+
+impl RenderOnce for CollapsableStack {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let collapsed = self.collapsed;
+        let on_toggle = self.on_toggle.clone();
+
+        div()
+            .id(self.id)
+            .flex()
+            .flex_col()
+            .w_full()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .flex_none()
+                    .w_full()
+                    .h(px(30.0))
+                    .when_some(self.hover_colour, |_self, colour| {
+                        _self.hover(|style| style.bg(colour))
+                    })
+                    .when_some(self.border_colour, |_self, colour| {
+                        _self.border_b(px(1.0)).border_color(colour)
+                    })
+                    .when_some(on_toggle, |_self, on_toggle| {
+                        _self.on_mouse_down(MouseButton::Left, move |_e, _window, _cx| {
+                            on_toggle(&!collapsed, _window, _cx)
+                        })
+                    })
+                    .child(div().flex_none().mx(px(4.0)).child(cx.new(|_| {
+                        Icon::new()
+                            .icon(if collapsed {
+                                Icons::ChevronRight
+                            } else {
+                                Icons::ChevronDown
+                            })
+                            .size(px(16.0))
+                            .colour(&self.chevron_colour)
+                    })))
+                    .children(self.header),
+            )
+            .when(!collapsed, |_self| {
+                _self.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .w_full()
+                        .pl(self.indent.abs())
+                        .children(self.children),
+                )
+            })
+    }
+}
+
+impl ParentElement for CollapsableStack {
+    fn extend(&mut self, elements: impl IntoIterator<Item = AnyElement>) {
+        self.children.extend(elements)
+    }
+}
+
+impl CollapsableStack {
+    pub fn new(id: impl Into<ElementId>) -> Self {
+        Self {
+            id: id.into(),
+            header: None,
+            children: Vec::new(),
+            collapsed: false,
+            indent: Size::Px(16.0),
+            chevron_colour: Colour::Rgb(0xffffff),
+            hover_colour: None,
+            border_colour: None,
+            on_toggle: None,
+        }
+    }
+    /// Sets the content shown to the right of the chevron
+    pub fn header(mut self, header: impl IntoElement) -> Self {
+        self.header = Some(header.into_any_element());
+        self
+    }
+    /// Whether the children are hidden
+    pub fn collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+    /// How far the children are indented in pixels
+    pub fn indent(mut self, indent: Size) -> Self {
+        self.indent = indent;
+        self
+    }
+    pub fn chevron_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.chevron_colour = colour.into();
+        self
+    }
+    /// Background colour of the header when hovered
+    pub fn hover_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.hover_colour = Some(colour.into());
+        self
+    }
+    /// Colour of the line under the header, no line is drawn if this isn't set
+    pub fn border_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.border_colour = Some(colour.into());
+        self
+    }
+    /// Function ran when the header is clicked, given whether the stack should now be collapsed
+    pub fn on_toggle(mut self, handler: impl Fn(&bool, &mut Window, &mut App) + 'static) -> Self {
+        self.on_toggle = Some(Rc::new(handler));
+        self
+    }
+}
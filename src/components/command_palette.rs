@@ -0,0 +1,407 @@
+use crate::ModalHelper;
+use crate::actions::{SHORTCUTS, shortcut_keys};
+use crate::components::control_bar::start_run;
+use crate::components::history::open_history;
+use crate::components::run_profiles::open_profile_menu;
+use crate::components::run_settings::open_run_settings;
+use crate::components::settings::open_settings;
+use crate::components::slowest_tests::open_slowest_tests;
+use crate::components::snapshots::open_snapshots;
+use crate::state::{RunArgs, State, StateProvider};
+use crate::style::{Size, StyleProvider};
+use crate::utils::fuzzy::fuzzy_score;
+use crate::utils::runner::RunScope;
+use crate::widgets::core::text_input::TextInput;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, BorrowAppContext, InteractiveElement, KeyDownEvent, MouseButton,
+    ParentElement, RenderOnce, ScrollHandle, StatefulInteractiveElement, Styled, Window, div, px,
+    rgba,
+};
+use std::cmp::Reverse;
+use std::rc::Rc;
+
+/// The most results shown at once, tests are only searched once something has been typed
+const MAX_RESULTS: usize = 50;
+
+/// A run arg that is on or off, with the name shown for it, whether it is on and a function to flip it
+type RunFlag = (&'static str, fn(&RunArgs) -> bool, fn(&mut RunArgs));
+
+/// The run args that are on or off
+const RUN_FLAGS: &[RunFlag] = &[
+    ("Lib", |x| x.lib, |x| x.lib = !x.lib),
+    ("Bins", |x| x.bins, |x| x.bins = !x.bins),
+    ("Doc", |x| x.doc, |x| x.doc = !x.doc),
+    ("Workspace", |x| x.workspace, |x| x.workspace = !x.workspace),
+    (
+        "No Fail Fast",
+        |x| x.no_fail_fast,
+        |x| x.no_fail_fast = !x.no_fail_fast,
+    ),
+    (
+        "All Features",
+        |x| x.all_features,
+        |x| x.all_features = !x.all_features,
+    ),
+    (
+        "No Default Features",
+        |x| x.no_default_features,
+        |x| x.no_default_features = !x.no_default_features,
+    ),
+    ("Release", |x| x.release, |x| x.release = !x.release),
+    (
+        "Include Ignored",
+        |x| x.include_ignored,
+        |x| x.include_ignored = !x.include_ignored,
+    ),
+    (
+        "No Capture",
+        |x| x.nocapture,
+        |x| x.nocapture = !x.nocapture,
+    ),
+    (
+        "Show Output",
+        |x| x.show_output,
+        |x| x.show_output = !x.show_output,
+    ),
+    (
+        "Report Times",
+        |x| x.report_time,
+        |x| x.report_time = !x.report_time,
+    ),
+    (
+        "Backtraces",
+        |x| x.backtrace,
+        |x| x.backtrace = !x.backtrace,
+    ),
+    (
+        "Full Backtraces",
+        |x| x.full_backtrace,
+        |x| x.full_backtrace = !x.full_backtrace,
+    ),
+];
+
+type CommandHandler = Rc<dyn Fn(&mut Window, &mut App) + 'static>;
+
+/// Something that can be run from the palette
+#[derive(Clone)]
+struct Command {
+    title: String,
+    /// Shown on the right, e.g. the keys bound to a shortcut or what kind of command it is
+    detail: String,
+    run: CommandHandler,
+}
+
+impl Command {
+    fn new(
+        title: impl ToString,
+        detail: impl ToString,
+        run: impl Fn(&mut Window, &mut App) + 'static,
+    ) -> Self {
+        Self {
+            title: title.to_string(),
+            detail: detail.to_string(),
+            run: Rc::new(run),
+        }
+    }
+}
+
+/// Every command other than jumping to a test, in the order they are shown before anything is typed
+fn commands(cx: &App) -> Vec<Command> {
+    let mut commands = Vec::new();
+
+    for shortcut in SHORTCUTS {
+        // the palette is already open
+        if shortcut.name == "command_palette" {
+            continue;
+        }
+        commands.push(Command::new(
+            shortcut.description,
+            shortcut_keys(shortcut.name, cx),
+            shortcut.run,
+        ))
+    }
+
+    commands.push(Command::new("Open settings", "Settings", |_window, _cx| {
+        open_settings(_window, _cx)
+    }));
+
+    let project_id = cx.state().active_project;
+
+    if cx.state().has_active_project() {
+        let mut profiles = vec![None];
+        profiles.extend(
+            cx.state()
+                .get_profiles(project_id)
+                .into_iter()
+                .map(|x| Some(x.name)),
+        );
+
+        for profile in profiles {
+            let title = match &profile {
+                Some(name) => format!("Run with the {} profile", name),
+                None => String::from("Run with the default settings"),
+            };
+            commands.push(Command::new(title, "Profile", move |_window, _cx| {
+                _cx.update_global::<State, ()>(|global, _| {
+                    global.set_active_profile(project_id, profile.clone())
+                });
+                start_run(RunScope::All, _cx)
+            }))
+        }
+
+        // the flags are changed on whichever run settings the project is using
+        let target = cx.state().active_run_settings(project_id);
+        let args = cx.state().get_run_args(&target).unwrap_or_default();
+
+        for (name, enabled, toggle) in RUN_FLAGS {
+            let target = target.clone();
+            commands.push(Command::new(
+                format!("Toggle {}", name),
+                if enabled(&args) { "On" } else { "Off" },
+                move |_window, _cx| {
+                    _cx.update_global::<State, ()>(|global, _| {
+                        global.update_run_args(&target, toggle)
+                    })
+                },
+            ))
+        }
+
+        commands.push(Command::new(
+            "Open run settings",
+            "Settings",
+            move |_window, _cx| {
+                let target = _cx.state().active_run_settings(project_id);
+                open_run_settings(target, _window, _cx)
+            },
+        ));
+        commands.push(Command::new(
+            "Open run profiles",
+            "Settings",
+            move |_window, _cx| open_profile_menu(project_id, _window, _cx),
+        ));
+        commands.push(Command::new(
+            "Open slowest tests",
+            "Settings",
+            move |_window, _cx| open_slowest_tests(project_id, _window, _cx),
+        ));
+        commands.push(Command::new(
+            "Open run history",
+            "View",
+            move |_window, _cx| open_history(project_id, _window, _cx),
+        ));
+        commands.push(Command::new(
+            "Open snapshots",
+            "View",
+            move |_window, _cx| open_snapshots(project_id, _window, _cx),
+        ));
+    }
+
+    for project in cx.state().open_projects.iter() {
+        if project.id == project_id {
+            continue;
+        }
+        let id = project.id;
+        commands.push(Command::new(
+            format!("Switch to {}", project.display_name()),
+            "Project",
+            move |_window, _cx| {
+                _cx.update_global::<State, ()>(|global, _| global.set_active_project(id))
+            },
+        ))
+    }
+
+    commands
+}
+
+/// The commands matching the query best first, with the active project's tests included once something is typed
+fn search(query: &str, cx: &App) -> Vec<Command> {
+    if query.trim().is_empty() {
+        return commands(cx);
+    }
+
+    let mut results: Vec<(i64, Command)> = commands(cx)
+        .into_iter()
+        .filter_map(|x| fuzzy_score(query, &x.title).map(|score| (score, x)))
+        .collect();
+
+    // only the best tests get a command made for them as there can be a lot of them
+    if let Some(project) = cx.state().active_project() {
+        let mut tests: Vec<(i64, usize, &String)> = project
+            .tests
+            .iter()
+            .flatten()
+            .flat_map(|x| x.tests.iter())
+            .enumerate()
+            .filter_map(|(index, test)| {
+                fuzzy_score(query, &test.module_path).map(|score| (score, index, &test.module_path))
+            })
+            .collect();
+
+        tests.sort_by_key(|x| Reverse(x.0));
+        tests.truncate(MAX_RESULTS);
+
+        for (score, index, module_path) in tests {
+            results.push((
+                score,
+                Command::new(module_path, "Test", move |_window, _cx| {
+                    _cx.update_global::<State, ()>(|global, _| global.select_test(index))
+                }),
+            ))
+        }
+    }
+
+    results.sort_by_key(|x| Reverse(x.0));
+    results.truncate(MAX_RESULTS);
+    results.into_iter().map(|x| x.1).collect()
+}
+
+/// Closes the palette before running the command so commands can open their own modals
+fn run_command(command: &Command, window: &mut Window, cx: &mut App) {
+    window.close_modal(cx);
+    (command.run)(window, cx);
+    window.refresh()
+}
+
+/// Opens a search over every action in the app, including jumping to any of the active project's tests
+pub fn open_command_palette(window: &mut Window, cx: &mut App) {
+    let query = cx.new(|_| String::new());
+    let selected = cx.new(|_| 0usize);
+    let scroll_handle = ScrollHandle::new();
+
+    // the search box is focused straight away so typing goes into it
+    let focus_handle = cx.focus_handle();
+    window.focus(&focus_handle);
+
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let typed = query.read(_cx).clone();
+        let results = Rc::new(search(&typed, _cx));
+        let current = (*selected.read(_cx)).min(results.len().saturating_sub(1));
+
+        let mut rows = Vec::new();
+
+        for (index, command) in results.iter().enumerate() {
+            let clicked = command.clone();
+
+            rows.push(
+                div()
+                    .id(("palette-command", index))
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .flex_none()
+                    .h(px(28.0))
+                    .px(style.padding.abs())
+                    .rounded(style.rounding.abs())
+                    .when(index == current, |_self| _self.bg(&style.primary_colour))
+                    .when(index != current, |_self| {
+                        _self.hover(|_style| _style.bg(rgba(0xffffff22)))
+                    })
+                    .on_mouse_down(MouseButton::Left, move |_e, __window, __cx| {
+                        run_command(&clicked, __window, __cx)
+                    })
+                    .child(
+                        div()
+                            .flex_grow()
+                            .overflow_hidden()
+                            .child(command.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .ml(style.margin.abs())
+                            .text_sm()
+                            .text_color(&style.muted_text_colour)
+                            .child(command.detail.clone()),
+                    ),
+            )
+        }
+
+        let change_query = query.clone();
+        let reset_selected = selected.clone();
+        let submitted = results.clone();
+        let moved = selected.clone();
+        let key_results = results.clone();
+        let key_scroll = scroll_handle.clone();
+
+        let body = div()
+            .flex()
+            .flex_col()
+            .size_full()
+            // this runs before the search box sees the keys so the selection can be moved while typing
+            .capture_key_down(move |e: &KeyDownEvent, __window, __cx| {
+                let last = key_results.len().saturating_sub(1);
+                let next = match e.keystroke.key.as_str() {
+                    "down" => (current + 1).min(last),
+                    "up" => current.saturating_sub(1),
+                    "escape" => {
+                        __cx.stop_propagation();
+                        __window.close_modal(__cx);
+                        return;
+                    }
+                    _ => return,
+                };
+
+                __cx.stop_propagation();
+                moved.update(__cx, |x, _| *x = next);
+                key_scroll.scroll_to_item(next);
+                __window.refresh()
+            })
+            .child(
+                div().pb(style.padding.abs()).child(
+                    TextInput::new("command-palette-search")
+                        .focus_handle(focus_handle.clone())
+                        .value(typed)
+                        .placeholder("Search for a command or test")
+                        .h(Size::Px(30.0))
+                        .text_size(Size::Px(14.0))
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, __window, __cx| {
+                            change_query.update(__cx, |x, _| *x = value.clone());
+                            reset_selected.update(__cx, |x, _| *x = 0);
+                            __window.refresh()
+                        })
+                        .on_submit(move |_value, __window, __cx| {
+                            if let Some(command) = submitted.get(current) {
+                                run_command(command, __window, __cx)
+                            }
+                        })
+                        .render(_window, _cx),
+                ),
+            )
+            .child(
+                div()
+                    .id("palette-results")
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .h(px(360.0))
+                    .overflow_y_scroll()
+                    .track_scroll(&scroll_handle)
+                    .when(rows.is_empty(), |_self| {
+                        _self
+                            .text_color(&style.muted_text_colour)
+                            .child("Nothing matches the search")
+                    })
+                    .children(rows),
+            );
+
+        modal
+            .title("Command Palette")
+            .body(body)
+            .w(px(600.0))
+            .h(px(480.0))
+            .top_offset(Some(px(80.0)))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(None)
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
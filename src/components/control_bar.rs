@@ -1,59 +1,460 @@
-use crate::state::{Project, RunArgs, State, StateProvider};
+use crate::components::history::open_history;
+use crate::components::run_profiles::open_profile_menu;
+use crate::components::run_settings::open_run_settings;
+use crate::components::slowest_tests::open_slowest_tests;
+use crate::components::snapshots::open_snapshots;
+use crate::state::{ProjectWatch, State, StateProvider, Watchers};
 use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::file::{add_history_entry, load_history};
+use crate::utils::flaky::flakiness;
+use crate::utils::history::{GitState, HistoryEntry, status_changes};
 use crate::utils::logger::warning;
+use crate::utils::runner::{
+    RunEvent, RunHandle, RunOutput, RunScope, add_test_args, run_streaming,
+};
+use crate::utils::watcher::watch_project;
 use crate::widgets::core::button::button::{Button, ContentPosition};
 use crate::widgets::core::button::icon_button::IconButton;
 use crate::widgets::core::divider::Divider;
 use crate::widgets::core::icon::Icons;
-use crate::widgets::core::modal::ModalButtonOptions;
 use crate::widgets::styling::Direction;
-use crate::{AlertHandler, AsyncAlertHandler, ModalHelper};
-use cargo_ptest::config::Config;
-use cargo_ptest::parse::ParsedTestGroup;
-use cargo_ptest::run::{RunError, run};
+use crate::{AlertHandler, AsyncAlertHandler};
+use cargo_ptest::run::RunError;
+use futures::StreamExt;
+use futures::channel::mpsc::{UnboundedSender, unbounded};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    App, AppContext, AsyncApp, BorrowAppContext, Context, InteractiveElement, IntoElement,
-    MouseButton, ParentElement, Render, RenderOnce, Styled, Task, Window, div, px,
+    App, AsyncApp, BorrowAppContext, Context, InteractiveElement, IntoElement, MouseButton,
+    ParentElement, Render, RenderOnce, Styled, Task, Window, div, px,
 };
-use std::env::set_current_dir;
 use std::path::PathBuf;
-use crate::widgets::core::checkbox::CheckBox;
-use crate::widgets::core::icon::Icons::Check;
+use std::time::Duration;
+
+/// How long the project's files have to be unchanged for before watch mode runs the tests
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
 
+/// Runs cargo test once for each set of args, the results are combined as if they were from one run
 fn run_tests(
     dir: PathBuf,
-    args: Vec<String>,
+    runs: Vec<Vec<String>>,
+    env: Vec<(String, String)>,
+    events: UnboundedSender<RunEvent>,
+    handle: RunHandle,
     cx: &AsyncApp,
-) -> Task<Result<Vec<ParsedTestGroup>, RunError>> {
+) -> Task<Result<RunOutput, RunError>> {
     cx.background_executor().spawn(async move {
-        let _ = set_current_dir(dir);
-
-        match run(
-            Some(Config {
-                debug: false,
-                ..Default::default()
-            }),
-            Some(args),
-        ) {
-            Ok(res) => Ok(res),
-            Err(err) => {
-                warning!("An error occurred when running tests");
-                Err(err)
+        let mut output = RunOutput::default();
+
+        for args in runs {
+            match run_streaming(
+                dir.clone(),
+                args,
+                env.clone(),
+                events.clone(),
+                handle.clone(),
+            ) {
+                Ok(res) => output.extend(res),
+                Err(err) => {
+                    warning!("An error occurred when running tests");
+                    return Err(err);
+                }
             }
         }
+
+        Ok(output)
     })
 }
 
+/// Starts a test run for the active project, if the scope is partial the results are merged into the existing tests
+pub fn start_run(scope: RunScope, cx: &mut App) {
+    match cx.state().active_project().map(|x| (x.id, x.running)) {
+        Some((id, running)) => {
+            if !running {
+                cx.update_global::<State, ()>(|global, _| global.clear_repeat(id));
+            }
+            start_project_run(id, scope, cx)
+        }
+        None => {}
+    }
+}
+
+/// Runs the tests in the scope the given number of times in a row for the active project, keeping count of how often each test passes
+pub fn start_repeated_run(scope: RunScope, times: u32, cx: &mut App) {
+    let (id, running) = match cx.state().active_project() {
+        Some(res) => (res.id, res.running),
+        None => return,
+    };
+
+    if running {
+        cx.alert_warning(
+            None::<String>,
+            "Tests are already running for this project",
+            Some(3000),
+        );
+        return;
+    }
+
+    let repeat_id = cx.update_global::<State, u32>(|global, _| global.start_repeat(id, times));
+    run_project(id, scope, Some(repeat_id), cx);
+
+    // the run didn't start, e.g. the run settings were invalid, so there won't be any results
+    if !cx.state().is_running(id) {
+        cx.update_global::<State, ()>(|global, _| global.clear_repeat(id));
+    }
+}
+
+/// Starts the next run of a repeated run, does nothing once every run is done or the run was cancelled
+fn start_next_repeat(project_id: u32, scope: RunScope, cx: &mut App) {
+    let next = cx
+        .state()
+        .get_project(project_id)
+        .filter(|x| !x.running && !x.cancelled)
+        .and_then(|x| x.repeat)
+        .filter(|x| !x.is_finished());
+
+    if let Some(repeat) = next {
+        run_project(project_id, scope, Some(repeat.id), cx)
+    }
+}
+
+/// Reloads a project's history in the background and works out how flaky each of its tests has been and when their statuses last changed
+pub fn refresh_flakiness(project_id: u32, cx: &mut App) {
+    let path = match cx.state().get_project(project_id) {
+        Some(project) => project.path,
+        None => return,
+    };
+
+    cx.spawn(async move |_cx| {
+        let (scores, changes) = _cx
+            .background_executor()
+            .spawn(async move {
+                let history = load_history(&path);
+                (flakiness(&history), status_changes(&history))
+            })
+            .await;
+
+        let _ = _cx.update_global::<State, ()>(|global, _| {
+            global.set_flakiness(project_id, scores);
+            global.set_status_changes(project_id, changes)
+        });
+        let _ = _cx.refresh();
+    })
+    .detach()
+}
+
+/// Starts a test run for the given project, this doesn't need the project to be active so watch mode can use it
+pub fn start_project_run(project_id: u32, scope: RunScope, cx: &mut App) {
+    run_project(project_id, scope, None, cx)
+}
+
+/// Starts a test run for the given project, its results are only counted towards a repeated run when it has the repeat's id
+fn run_project(project_id: u32, scope: RunScope, repeat_id: Option<u32>, cx: &mut App) {
+    if cx.state().is_running(project_id) {
+        cx.alert_warning(
+            None::<String>,
+            "Tests are already running for this project",
+            Some(3000),
+        );
+        return;
+    }
+
+    let project = match cx.state().get_project(project_id) {
+        Some(res) => res,
+        None => return,
+    };
+
+    // the active profile replaces the project's own run args and adds to its environment
+    let (run_args, filter, environment) = match cx.state().get_active_profile(project_id) {
+        Some(profile) => (
+            profile.run_args,
+            profile.filter,
+            project.environment.with_overrides(&profile.environment),
+        ),
+        None => (project.run_args, String::new(), project.environment),
+    };
+
+    if let Err(err) = run_args.validate().and(environment.validate()) {
+        cx.alert_error(Some("Invalid run settings"), err, Some(5000));
+        return;
+    }
+
+    let dir = environment.resolve_working_dir(&project.path);
+
+    if !dir.is_dir() {
+        cx.alert_error(
+            Some("Invalid run settings"),
+            format!("The working directory {:?} does not exist", dir),
+            Some(5000),
+        );
+        return;
+    }
+
+    let mut env = environment.vars();
+    // --report-time is unstable, this lets libtest accept it on a stable toolchain
+    if run_args.report_time && !env.iter().any(|x| x.0 == "RUSTC_BOOTSTRAP") {
+        env.push((String::from("RUSTC_BOOTSTRAP"), String::from("1")))
+    }
+    // a RUST_BACKTRACE set in the environment is left as it is
+    if (run_args.backtrace || run_args.full_backtrace)
+        && !env.iter().any(|x| x.0 == "RUST_BACKTRACE")
+    {
+        let value = if run_args.full_backtrace { "full" } else { "1" };
+        env.push((String::from("RUST_BACKTRACE"), String::from(value)))
+    }
+    // a target run picks its own target, the run args' targets would run other targets alongside it
+    let run_args = match scope {
+        RunScope::Target(_, _) => run_args.without_targets(),
+        _ => run_args,
+    };
+    let history_args = run_args.clone();
+    let profile_name = cx.state().get_active_profile(project_id).map(|x| x.name);
+    let project_path = project.path.clone();
+    let mut args = scope.apply(run_args.into());
+
+    // a partial run already picks the tests it needs, adding the filter would run extra tests
+    if !scope.is_partial() && !filter.trim().is_empty() {
+        args = args
+            .into_iter()
+            .map(|x| add_test_args(x, vec![filter.trim().to_string()]))
+            .collect();
+    }
+    let handle = RunHandle::new();
+    let merge = scope.is_partial();
+
+    cx.update_global::<State, ()>(|global, _cx| {
+        global.start_run(project_id, handle.clone());
+        global.set_last_run(project_id, scope.clone());
+        if !merge {
+            global.start_streamed_run(project_id);
+        }
+    });
+    cx.refresh_windows();
+
+    cx.spawn(async move |_cx| {
+        // the code is recorded before the tests start as it can be edited while they run, e.g. in watch mode
+        let git_dir = project_path.clone();
+        let git_state = _cx
+            .background_executor()
+            .spawn(async move { GitState::read(&git_dir) })
+            .await;
+
+        let (sender, mut receiver) = unbounded::<RunEvent>();
+        let run = run_tests(dir, args, env, sender, handle.clone(), _cx);
+
+        // show each test as soon as it finishes, batching any that arrived together into one refresh
+        while let Some(event) = receiver.next().await {
+            let mut events = vec![event];
+            while let Ok(Some(event)) = receiver.try_next() {
+                events.push(event)
+            }
+
+            let _ = _cx.update_global::<State, ()>(|global, __cx| {
+                for event in events {
+                    global.add_run_event(project_id, &handle, event, merge)
+                }
+            });
+            let _ = _cx.refresh();
+        }
+
+        let mut succeeded = false;
+
+        let result = run.await;
+        let current = _cx
+            .update_global::<State, bool>(|global, __cx| global.is_current_run(project_id, &handle))
+            .unwrap_or(false);
+
+        match result {
+            // a run that was cancelled just as it finished mustn't replace the results of the run that followed it
+            Ok(_) if !current => {}
+            Ok(output) => {
+                succeeded = true;
+                let res = output.groups;
+                let history_groups = res.clone();
+                let _ = _cx.update_global::<State, ()>(|global, __cx| {
+                    if let Some(repeat_id) = repeat_id {
+                        global.record_repeat(project_id, repeat_id, &res);
+                    }
+                    global.set_outputs(project_id, output.outputs, &res, merge);
+                    if merge {
+                        global.merge_tests(project_id, res);
+                    } else {
+                        global.set_tests(project_id, res);
+                    }
+                    global.finish_run(project_id, &handle);
+                });
+                let _ = _cx.refresh();
+
+                // writing the file and reloading the history can be slow so it is done after the results are shown
+                let description = scope.description();
+                let history_path = project_path.clone();
+                let (scores, changes) = _cx
+                    .background_executor()
+                    .spawn(async move {
+                        let entry = HistoryEntry::new(
+                            git_state,
+                            history_args,
+                            profile_name,
+                            description,
+                            merge,
+                            history_groups,
+                            output.durations,
+                        );
+                        add_history_entry(&history_path, entry);
+
+                        let history = load_history(&history_path);
+                        (flakiness(&history), status_changes(&history))
+                    })
+                    .await;
+                let _ = _cx.update_global::<State, ()>(|global, __cx| {
+                    global.set_flakiness(project_id, scores);
+                    global.set_status_changes(project_id, changes);
+                });
+            }
+            // the streamed tests are left in place when a run is cancelled
+            Err(_) if handle.is_cancelled() => {}
+            Err(err) => {
+                let _ = _cx.update_global::<State, ()>(|global, __cx| {
+                    global.finish_run(project_id, &handle);
+                });
+                _cx.alert_error(
+                    Some("cargo_ptest: RunError"),
+                    format!("Could not run tests: {}", err.error),
+                    Some(5000),
+                );
+            }
+        };
+        let _ = _cx.update(|__cx| {
+            if succeeded {
+                start_next_repeat(project_id, scope, __cx)
+            }
+            start_queued_watch_run(project_id, __cx)
+        });
+        _cx.refresh()
+    })
+    .detach();
+}
+
+/// Re-runs every failed test in the active project and merges the results back in
+pub fn rerun_failed(cx: &mut App) {
+    let failed = cx
+        .state()
+        .active_project()
+        .map(|x| x.failed_tests())
+        .unwrap_or(Vec::new());
+
+    if failed.is_empty() {
+        cx.alert_info(
+            None::<String>,
+            "There are no failed tests to re-run",
+            Some(3000),
+        );
+        return;
+    }
+
+    start_run(RunScope::Tests(failed), cx)
+}
+
+/// Starts the follow up run for a project if its files changed while its tests were running
+fn start_queued_watch_run(project_id: u32, cx: &mut App) {
+    let queued = cx
+        .state()
+        .get_project(project_id)
+        .is_some_and(|x| x.watching && x.watch_queued && !x.running);
+
+    if queued {
+        cx.update_global::<State, ()>(|global, _| global.set_watch_queued(project_id, false));
+        start_project_run(project_id, RunScope::All, cx)
+    }
+}
+
+/// Called once a project's files have stopped changing, runs the tests or queues a run if tests are already running
+fn watch_triggered(project_id: u32, cx: &mut App) {
+    if cx.state().is_running(project_id) {
+        // however many changes happen during a run only one follow up run is needed
+        cx.update_global::<State, ()>(|global, _| global.set_watch_queued(project_id, true));
+        cx.refresh_windows();
+    } else {
+        start_project_run(project_id, RunScope::All, cx)
+    }
+}
+
+/// Stops watching a project's files, does nothing if the project isn't being watched
+pub fn stop_watching(project_id: u32, cx: &mut App) {
+    cx.update_global::<Watchers, ()>(|watchers, _| {
+        watchers.watchers.remove(&project_id);
+    });
+    cx.update_global::<State, ()>(|global, _| global.set_watching(project_id, false));
+}
+
+/// Turns watch mode on or off for a project, while it is on the tests are run every time a file in the project changes
+pub fn toggle_watch(project_id: u32, cx: &mut App) {
+    if cx.global::<Watchers>().watchers.contains_key(&project_id) {
+        stop_watching(project_id, cx);
+        cx.refresh_windows();
+        return;
+    }
+
+    let dir = match cx.state().get_project(project_id) {
+        Some(project) => project.path,
+        None => return,
+    };
+
+    let (sender, mut receiver) = unbounded::<()>();
+    let watcher = match watch_project(dir, sender) {
+        Ok(res) => res,
+        Err(err) => {
+            cx.alert_error(Some("Watch mode"), err, Some(5000));
+            return;
+        }
+    };
+
+    let task = cx.spawn(async move |_cx| {
+        while receiver.next().await.is_some() {
+            // saving a file usually produces several events, so wait until nothing has changed for a while
+            loop {
+                _cx.background_executor().timer(WATCH_DEBOUNCE).await;
+
+                let mut changed = false;
+                while let Ok(Some(_)) = receiver.try_next() {
+                    changed = true
+                }
+                if !changed {
+                    break;
+                }
+            }
+
+            let _ = _cx.update(|__cx| watch_triggered(project_id, __cx));
+        }
+    });
+
+    cx.update_global::<Watchers, ()>(|watchers, _| {
+        watchers.watchers.insert(
+            project_id,
+            ProjectWatch {
+                _watcher: watcher,
+                _task: task,
+            },
+        );
+    });
+    cx.update_global::<State, ()>(|global, _| global.set_watching(project_id, true));
+    cx.refresh_windows();
+}
+
 pub struct ControlBar {}
 
 impl Render for ControlBar {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let path = cx
             .state()
-            .get_active_project()
-            .unwrap_or(Project::default())
-            .path_string();
+            .active_project()
+            .map_or(String::new(), |x| x.path_string());
+        let watching = cx.state().active_project().map_or(false, |x| x.watching);
+        let running = cx.state().active_project().map_or(false, |x| x.running);
+        let profile_name = cx
+            .state()
+            .active_project()
+            .and_then(|x| cx.state().get_active_profile(x.id))
+            .map_or(String::from("Default"), |x| x.name);
 
         let split_path = path
             .split(&['/', '\\'][..])
@@ -88,9 +489,8 @@ impl Render for ControlBar {
                                             move |_e, _window, _cx| {
                                                 let sp = _cx
                                                     .state()
-                                                    .get_active_project()
-                                                    .unwrap_or(Project::default())
-                                                    .path_string()
+                                                    .active_project()
+                                                    .map_or(String::new(), |x| x.path_string())
                                                     .split(&['/', '\\'][..])
                                                     .map(|x| x.to_string())
                                                     .filter(|x| x.len() > 0)
@@ -151,49 +551,34 @@ impl Render for ControlBar {
                                     .text_size(Size::Px(15.0))
                                     .text_colour(&cx.style().text_colour)
                                     .tooltip("Run the projects tests")
+                                    .on_click(|_e, _window, _cx| start_run(RunScope::All, _cx))
+                                    .render(window, cx),
+                            )
+                            .child(
+                                Divider::new()
+                                    .thickness(1.0)
+                                    .colour(&cx.style().separator_colour)
+                                    .direction(Direction::Vertical)
+                                    .render(window, cx),
+                            )
+                            .child(
+                                Button::new("run-profile-button")
+                                    .text(profile_name)
+                                    .justify_content(ContentPosition::Centre)
+                                    .align_text(ContentPosition::Centre)
+                                    .rounding_all(0.0.into())
+                                    .w(Size::Px(110.0))
+                                    .h(cx.style().controlbar.button_height)
+                                    .colour(&cx.style().primary_colour)
+                                    .hover_colour(&cx.style().hover_primary_colour)
+                                    .text_size(Size::Px(13.0))
+                                    .text_colour(&cx.style().text_colour)
+                                    .tooltip("Choose the run profile")
                                     .on_click(|_e, _window, _cx| {
-                                        _cx.update_global::<State, ()>(|global, _cx| {
-                                            global.status.running_tests = true;
-                                        });
-                                        _window.refresh();
-
-                                        let dir =
-                                            _cx.read_global::<State, PathBuf>(|global, ___cx| {
-                                                global.get_active_project().unwrap().path
-                                            });
-                                        _cx.spawn(async |__cx| {
-                                            let args: Vec<String> = __cx
-                                                .read_global::<State, Vec<String>>(|global, _| {
-                                                    global.run_args.clone().into()
-                                                })
-                                                .unwrap_or(RunArgs::default_vec());
-
-                                            match run_tests(dir, args, __cx).await {
-                                                Ok(res) => {
-                                                    let _ = __cx.update_global::<State, ()>(
-                                                        |global, ___cx| {
-                                                            global.set_tests(
-                                                                global.active_project,
-                                                                res,
-                                                            );
-                                                            global.status.running_tests = false;
-                                                        },
-                                                    );
-                                                }
-                                                Err(err) => {
-                                                    __cx.alert_error(
-                                                        Some("cargo_ptest: RunError"),
-                                                        format!(
-                                                            "Could not run tests: {}",
-                                                            err.error
-                                                        ),
-                                                        Some(5000),
-                                                    );
-                                                }
-                                            };
-                                            __cx.refresh()
-                                        })
-                                        .detach();
+                                        if let Some(id) = _cx.state().active_project().map(|x| x.id)
+                                        {
+                                            open_profile_menu(id, _window, _cx)
+                                        }
                                     })
                                     .render(window, cx),
                             )
@@ -222,174 +607,149 @@ impl Render for ControlBar {
                                     .hover_colour(&cx.style().hover_primary_colour)
                                     .icon_size(Size::Px(15.0))
                                     .icon_colour(&cx.style().text_colour)
-                                    .tooltip("Edit run settings")
+                                    .tooltip("Edit the run settings of the selected profile")
                                     .on_click(|_e, _window, _cx| {
-                                        _window.open_modal(_cx, |modal, __window, __cx| {
-                                            
-                                            let modal_body = div()
-                                                .size_full()
-                                                .child(
-                                                    div()
-                                                        .flex()
-                                                        .flex_row()
-                                                        .justify_between()
-                                                        .pb(__cx.style().padding.abs())
-                                                        .child(div().flex().flex_col().child("Lib").child(div().text_color(&__cx.style().muted_text_colour).text_xs().child("Run the library tests")))
-                                                        .child(
-                                                            __cx.new(|___cx| CheckBox::new()
-                                                                .checked(___cx.state().run_args.lib)
-                                                                .checked_colour(&___cx.style().primary_colour)
-                                                                .unchecked_colour(Colour::Rgba(0x00000000))
-                                                                .symbol_colour(Colour::Rgba(0xffffffaa))
-                                                                .border_colour(&___cx.style().muted_text_colour)
-                                                                .border(Size::Px(2.0))
-                                                                .size(Size::Px(20.0))
-                                                                .on_toggle(|e, ____window, ____cx| {
-                                                                    ____cx.update_global::<State, ()>(|global, _| {
-                                                                        global.run_args.lib = !global.run_args.lib;
-                                                                    });
-                                                                    ____window.refresh()
-                                                                })
-                                                            )
-                                                        ),
-                                                )
-                                                .child(
-                                                    div()
-                                                        .flex()
-                                                        .flex_row()
-                                                        .justify_between()
-                                                        .pb(__cx.style().padding.abs())
-                                                        .child(div().flex().flex_col().child("Bin").child(div().text_color(&__cx.style().muted_text_colour).text_xs().child("Run the binary tests")))
-                                                        .child(
-                                                            __cx.new(|___cx| CheckBox::new()
-                                                                .checked(___cx.state().run_args.bin)
-                                                                .checked_colour(&___cx.style().primary_colour)
-                                                                .unchecked_colour(Colour::Rgba(0x00000000))
-                                                                .symbol_colour(Colour::Rgba(0xffffffaa))
-                                                                .border_colour(&___cx.style().muted_text_colour)
-                                                                .border(Size::Px(2.0))
-                                                                .size(Size::Px(20.0))
-                                                                .on_toggle(|e, ____window, ____cx| {
-                                                                    ____cx.update_global::<State, ()>(|global, _| {
-                                                                        global.run_args.bin = !global.run_args.bin;
-                                                                    });
-                                                                    ____window.refresh()
-                                                                })
-                                                            )
-                                                        ),
-                                                )
-                                                .child(
-                                                    div()
-                                                        .flex()
-                                                        .flex_row()
-                                                        .justify_between()
-                                                        .pb(__cx.style().padding.abs())
-                                                        .child(div().flex().flex_col().child("Docs").child(div().text_color(&__cx.style().muted_text_colour).text_xs().child("Run the doc tests")))
-                                                        .child(
-                                                            __cx.new(|___cx| CheckBox::new()
-                                                                .checked(___cx.state().run_args.docs)
-                                                                .checked_colour(&___cx.style().primary_colour)
-                                                                .unchecked_colour(Colour::Rgba(0x00000000))
-                                                                .symbol_colour(Colour::Rgba(0xffffffaa))
-                                                                .border_colour(&___cx.style().muted_text_colour)
-                                                                .border(Size::Px(2.0))
-                                                                .size(Size::Px(20.0))
-                                                                .on_toggle(|e, ____window, ____cx| {
-                                                                    ____cx.update_global::<State, ()>(|global, _| {
-                                                                        global.run_args.docs = !global.run_args.docs;
-                                                                    });
-                                                                    ____window.refresh()
-
-                                                                })
-                                                            )
-                                                        ),
-                                                )
-                                                .child(
-                                                    div()
-                                                        .flex()
-                                                        .flex_row()
-                                                        .justify_between()
-                                                        .pb(__cx.style().padding.abs())
-                                                        .child(div().flex().flex_col().child("Workspace").child(div().text_color(&__cx.style().muted_text_colour).text_xs().child("Run the workspace tests")))
-                                                        .child(
-                                                            __cx.new(|___cx| CheckBox::new()
-                                                                .checked(___cx.state().run_args.workspace)
-                                                                .checked_colour(&___cx.style().primary_colour)
-                                                                .unchecked_colour(Colour::Rgba(0x00000000))
-                                                                .symbol_colour(Colour::Rgba(0xffffffaa))
-                                                                .border_colour(&___cx.style().muted_text_colour)
-                                                                .border(Size::Px(2.0))
-                                                                .size(Size::Px(20.0))
-                                                                .on_toggle(|e, ____window, ____cx| {
-                                                                    ____cx.update_global::<State, ()>(|global, _| {
-                                                                        global.run_args.workspace = !global.run_args.workspace;
-                                                                    });
-                                                                    ____window.refresh()
-                                                                })
-                                                            )
-                                                        ),
-                                                )
-                                                .child(
-                                                    div()
-                                                        .flex()
-                                                        .flex_row()
-                                                        .justify_between()
-                                                        .pb(__cx.style().padding.abs())
-                                                        .child(div().flex().flex_col().child("No Fail Fast").child(div().text_color(&__cx.style().muted_text_colour).text_xs().child("Continue tests after a failure")))
-                                                        .child(
-                                                            __cx.new(|___cx| CheckBox::new()
-                                                                .checked(___cx.state().run_args.no_fail_fast)
-                                                                .checked_colour(&___cx.style().primary_colour)
-                                                                .unchecked_colour(Colour::Rgba(0x00000000))
-                                                                .symbol_colour(Colour::Rgba(0xffffffaa))
-                                                                .border_colour(&___cx.style().muted_text_colour)
-                                                                .border(Size::Px(2.0))
-                                                                .size(Size::Px(20.0))
-                                                                .on_toggle(|e, ____window, ____cx| {
-                                                                    ____cx.update_global::<State, ()>(|global, _| {
-                                                                        global.run_args.no_fail_fast = !global.run_args.no_fail_fast;
-                                                                    });
-                                                                    ____window.refresh()
-                                                                })
-                                                            )
-                                                        ),
-                                                );
-                                            modal
-                                                .title("Run Configuration")
-                                                .body(modal_body)
-                                                .h(px(500.0))
-                                                .w(px(500.0))
-                                                .rounding(__cx.style().rounding)
-                                                .bg_colour(&__cx.style().bg_colour)
-                                                .p(Size::Px(10.0))
-                                                .accept_button_options(None)
-                                                .cancel_button_options(Some(
-                                                    ModalButtonOptions {
-                                                        show: true,
-                                                        text: "Close".to_string(),
-                                                        colour: __cx.style().bg_colour.clone(),
-                                                        hover_colour: Some(Colour::Rgba(
-                                                            0xffffff22,
-                                                        )),
-                                                        border_width: Size::Px(1.0),
-                                                        border_colour: Some(
-                                                            __cx.style().separator_colour.clone(),
-                                                        ),
-                                                        padding: Size::Px(50.0),
-                                                        rounding: __cx.style().rounding,
-                                                        on_click: None,
-                                                    }
-                                                    .on_click(|e, ___window, ___cx| {
-                                                        ___window.close_modal(___cx)
-                                                    }),
-                                                ))
-                                                .on_close(|e, ___window, ___cx| {
-                                                    ___window.close_modal(___cx)
-                                                })
-                                        })
+                                        if let Some(id) = _cx.state().active_project().map(|x| x.id)
+                                        {
+                                            let target = _cx.state().active_run_settings(id);
+                                            open_run_settings(target, _window, _cx)
+                                        }
                                     })
                                     .render(window, cx),
-                            ),
+                            )
+                            .when(running, |_self| {
+                                _self.child(
+                                    IconButton::new("stop-tests-button")
+                                        .icon(Icons::Ban)
+                                        .justify_content(ContentPosition::Centre)
+                                        .align_text(ContentPosition::Centre)
+                                        .rounding_all(cx.style().rounding)
+                                        .h(cx.style().controlbar.button_height)
+                                        .w(cx.style().controlbar.button_height)
+                                        .mr(cx.style().margin)
+                                        .colour(&cx.style().failed_colour)
+                                        .hover_colour(Colour::Rgba(0xffffff22))
+                                        .icon_size(Size::Px(15.0))
+                                        .icon_colour(&cx.style().text_colour)
+                                        .tooltip("Stop running tests")
+                                        .on_click(|_e, _window, _cx| {
+                                            _cx.update_global::<State, ()>(|global, _| {
+                                                global.cancel_run(global.active_project)
+                                            });
+                                            _window.refresh()
+                                        })
+                                        .render(window, cx),
+                                )
+                            }),
+                    )
+                    .child(
+                        Button::new("rerun-failed-button")
+                            .text("Re-run Failed")
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .rounding_all(cx.style().rounding)
+                            .w(Size::Px(100.0))
+                            .h(cx.style().controlbar.button_height)
+                            .mr(cx.style().margin)
+                            .colour(&cx.style().bg_colour)
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .border_all(Size::Px(1.0))
+                            .border_colour(&cx.style().failed_colour)
+                            .text_size(Size::Px(15.0))
+                            .text_colour(&cx.style().text_colour)
+                            .tooltip("Re-run only the tests that failed")
+                            .on_click(|_e, _window, _cx| rerun_failed(_cx))
+                            .render(window, cx),
+                    )
+                    .child(
+                        Button::new("watch-button")
+                            .text("Watch")
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .rounding_all(cx.style().rounding)
+                            .w(Size::Px(70.0))
+                            .h(cx.style().controlbar.button_height)
+                            .mr(cx.style().margin)
+                            .colour(if watching {
+                                &cx.style().primary_colour
+                            } else {
+                                &cx.style().bg_colour
+                            })
+                            .hover_colour(&cx.style().hover_primary_colour)
+                            .border_all(Size::Px(1.0))
+                            .border_colour(&cx.style().primary_colour)
+                            .text_size(Size::Px(15.0))
+                            .text_colour(&cx.style().text_colour)
+                            .tooltip("Re-run the tests whenever a file in the project changes")
+                            .on_click(|_e, _window, _cx| {
+                                if let Some(id) = _cx.state().active_project().map(|x| x.id) {
+                                    toggle_watch(id, _cx)
+                                }
+                            })
+                            .render(window, cx),
+                    )
+                    .child(
+                        IconButton::new("history-button")
+                            .icon(Icons::History)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .rounding_all(cx.style().rounding)
+                            .ml(cx.style().margin)
+                            .h(cx.style().controlbar.button_height)
+                            .w(cx.style().controlbar.button_height)
+                            .colour(&cx.style().bg_colour)
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .icon_size(cx.style().controlbar.button_height * 0.75)
+                            .icon_colour(&cx.style().text_colour)
+                            .tooltip("Run history")
+                            .on_click(|_, _window, _cx| {
+                                if let Some(id) = _cx.state().active_project().map(|x| x.id) {
+                                    open_history(id, _window, _cx)
+                                }
+                            })
+                            .render(window, cx),
+                    )
+                    .child(
+                        IconButton::new("slowest-tests-button")
+                            .icon(Icons::Timer)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .rounding_all(cx.style().rounding)
+                            .ml(cx.style().margin)
+                            .h(cx.style().controlbar.button_height)
+                            .w(cx.style().controlbar.button_height)
+                            .colour(&cx.style().bg_colour)
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .icon_size(cx.style().controlbar.button_height * 0.75)
+                            .icon_colour(&cx.style().text_colour)
+                            .tooltip("Slowest tests")
+                            .on_click(|_, _window, _cx| {
+                                if let Some(id) = _cx.state().active_project().map(|x| x.id) {
+                                    open_slowest_tests(id, _window, _cx)
+                                }
+                            })
+                            .render(window, cx),
+                    )
+                    .child(
+                        IconButton::new("snapshots-button")
+                            .icon(Icons::Compare)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .rounding_all(cx.style().rounding)
+                            .ml(cx.style().margin)
+                            .h(cx.style().controlbar.button_height)
+                            .w(cx.style().controlbar.button_height)
+                            .colour(&cx.style().bg_colour)
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .icon_size(cx.style().controlbar.button_height * 0.75)
+                            .icon_colour(&cx.style().text_colour)
+                            .tooltip("Snapshots and diffs")
+                            .on_click(|_, _window, _cx| {
+                                if let Some(id) = _cx.state().active_project().map(|x| x.id) {
+                                    open_snapshots(id, _window, _cx)
+                                }
+                            })
+                            .render(window, cx),
                     )
                     .child(
                         IconButton::new("clear-tests-button")
@@ -437,9 +797,8 @@ impl Render for ControlBar {
                             .tooltip("Open folder location")
                             .on_click(|_, _window, _cx| {
                                 if _cx.state().has_active_project() {
-                                    _cx.open_with_system(
-                                        _cx.state().get_active_project().unwrap().path.as_path(),
-                                    )
+                                    let path = _cx.state().active_project().unwrap().path.clone();
+                                    _cx.open_with_system(path.as_path())
                                 }
                             })
                             .render(window, cx),
@@ -0,0 +1,63 @@
+use crate::components::test_list_item::TestListItem;
+use crate::state::{StateProvider, test_id};
+use crate::style::StyleProvider;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, InteractiveElement, IntoElement, ParentElement, RenderOnce,
+    StatefulInteractiveElement, Styled, Window, div,
+};
+
+/// The tests that changed between the two sides of the active project's diff
+pub struct DiffList {}
+
+impl RenderOnce for DiffList {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        // only the diff's tests are copied out rather than the whole project
+        let tests = cx
+            .state()
+            .active_project()
+            .and_then(|project| {
+                let diff = project.diff.as_ref()?;
+                Some(
+                    diff.tests
+                        .iter()
+                        .map(|x| {
+                            let flakiness = project.flakiness.get(&test_id(&x.test)).copied();
+                            (x.clone(), flakiness)
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .unwrap_or_default();
+
+        let mut elements = Vec::new();
+        for (index, (test, flakiness)) in tests.into_iter().enumerate() {
+            elements.push(cx.new(|_| TestListItem {
+                index,
+                test_data: test.test.clone(),
+                label: None,
+                flakiness,
+                pass_rate: None,
+                duration: test.durations.1.or(test.durations.0),
+                diff: Some(test),
+            }))
+        }
+
+        div()
+            .id("diff-list")
+            .flex()
+            .flex_col()
+            .size_full()
+            .overflow_y_scroll()
+            .when_else(
+                elements.is_empty(),
+                |_self| {
+                    _self
+                        .p(cx.style().padding.abs())
+                        .text_color(&cx.style().muted_text_colour)
+                        .child("No tests changed between these runs")
+                },
+                |_self| _self.children(elements),
+            )
+    }
+}
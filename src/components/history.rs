@@ -0,0 +1,102 @@
+use crate::ModalHelper;
+use crate::state::{State, StateProvider};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::file::load_history;
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::modal::ModalButtonOptions;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, BorrowAppContext, InteractiveElement, IntoElement, ParentElement, RenderOnce,
+    StatefulInteractiveElement, Styled, Window, div, px,
+};
+
+/// Opens the list of a project's past runs, selecting one shows its results in the test list
+pub fn open_history(project_id: u32, window: &mut Window, cx: &mut App) {
+    let project = match cx.state().get_project(project_id) {
+        Some(res) => res,
+        None => return,
+    };
+    let mut history = load_history(&project.path);
+    history.reverse();
+
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let mut rows = Vec::new();
+
+        for (index, entry) in history.iter().enumerate() {
+            let entry = entry.clone();
+
+            rows.push(
+                Button::new(("history-entry", index))
+                    .text(entry.description())
+                    .justify_content(ContentPosition::Start)
+                    .align_text(ContentPosition::Centre)
+                    .h(Size::Px(30.0))
+                    .pa(style.padding)
+                    .mb(style.margin)
+                    .colour(&style.secondary_bg_colour)
+                    .hover_colour(&style.hover_primary_colour)
+                    .text_colour(if entry.summary.failed > 0 {
+                        &style.failed_colour
+                    } else {
+                        &style.text_colour
+                    })
+                    .text_size(Size::Px(13.0))
+                    .rounding_all(style.rounding)
+                    .tooltip(match &entry.profile {
+                        Some(profile) => format!("Run with the {} profile", profile),
+                        None => String::from("Run with the project's run settings"),
+                    })
+                    .on_click(move |_e, __window, __cx| {
+                        __cx.update_global::<State, ()>(|global, _| {
+                            global.open_history_entry(project_id, &entry)
+                        });
+                        __window.close_modal(__cx)
+                    })
+                    .render(_window, _cx)
+                    .into_any_element(),
+            )
+        }
+
+        let body = div().size_full().child(
+            div()
+                .id("history-entries")
+                .flex()
+                .flex_col()
+                .w_full()
+                .h(px(440.0))
+                .overflow_y_scroll()
+                .when(rows.is_empty(), |_self| {
+                    _self
+                        .text_color(&style.muted_text_colour)
+                        .child("No runs have been recorded for this project yet")
+                })
+                .children(rows),
+        );
+
+        modal
+            .title(format!("Run History - {}", project.display_name()))
+            .body(body)
+            .w(px(600.0))
+            .h(px(560.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
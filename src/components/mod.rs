@@ -1,9 +1,23 @@
 pub(crate) mod alert;
+pub(crate) mod backtrace_view;
 pub(crate) mod collapsable_stack;
+pub(crate) mod command_palette;
 pub(crate) mod control_bar;
+pub(crate) mod diff_list;
+pub(crate) mod history;
+pub(crate) mod output_view;
+pub(crate) mod panic_view;
+pub(crate) mod run_profiles;
+pub(crate) mod run_settings;
+pub(crate) mod settings;
+pub(crate) mod shortcuts;
+pub(crate) mod slowest_tests;
+pub(crate) mod snapshots;
+pub(crate) mod source_view;
 pub(crate) mod status_bar;
 pub(crate) mod tab_bar;
 pub(crate) mod tab_bar_item;
+pub(crate) mod test_filter_bar;
 pub(crate) mod test_info;
 pub(crate) mod test_list;
 pub(crate) mod test_list_item;
@@ -0,0 +1,272 @@
+use crate::AlertHandler;
+use crate::style::{CodeStyle, Colour, Size, StyleProvider};
+use crate::utils::ansi::{AnsiLine, parse_ansi};
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::scrollbar::Scrollbar;
+use crate::widgets::core::text_input::TextInput;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AnyElement, App, ClipboardItem, FontStyle, FontWeight, HighlightStyle, IntoElement,
+    ParentElement, RenderOnce, ScrollStrategy, Styled, StyledText, UnderlineStyle,
+    UniformListScrollHandle, Window, div, px, uniform_list,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(IntoElement)]
+/// What a test printed, with its terminal colours and a search that jumps between matches
+pub struct OutputView {
+    pub output: String,
+}
+
+/// Where the query appears in each line as (line, byte range), ignoring case
+fn find_matches(lines: &[AnsiLine], query: &str) -> Vec<(usize, Range<usize>)> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // only ascii is lowercased so the byte ranges still line up with the original text
+    let query = query.to_ascii_lowercase();
+    let mut matches = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let text = line.text.to_ascii_lowercase();
+        let mut start = 0;
+        while let Some(offset) = text[start..].find(&query) {
+            let range = start + offset..start + offset + query.len();
+            start = range.end;
+            matches.push((index, range))
+        }
+    }
+
+    matches
+}
+
+/// Splits the line's ansi spans around the search matches so both can be shown, the matches go over the colours
+fn line_highlights(
+    line: &AnsiLine,
+    matches: &[(Range<usize>, bool)],
+    style: &CodeStyle,
+) -> Vec<(Range<usize>, HighlightStyle)> {
+    // every point where the styling could change
+    let mut bounds = vec![0, line.text.len()];
+    for (range, _) in line.spans.iter() {
+        bounds.extend([range.start, range.end])
+    }
+    for (range, _) in matches.iter() {
+        bounds.extend([range.start, range.end])
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut highlights = Vec::new();
+    for pair in bounds.windows(2) {
+        let range = pair[0]..pair[1];
+        let span = line
+            .spans
+            .iter()
+            .find(|x| x.0.start <= range.start && range.end <= x.0.end)
+            .map(|x| x.1);
+        let search = matches
+            .iter()
+            .find(|x| x.0.start <= range.start && range.end <= x.0.end)
+            .map(|x| x.1);
+
+        if span.is_none() && search.is_none() {
+            continue;
+        }
+
+        let mut highlight = HighlightStyle::default();
+        if let Some(span) = span {
+            highlight.color = span.foreground.map(|x| Colour::Rgb(x).into());
+            highlight.background_color = span.background.map(|x| Colour::Rgb(x).into());
+            if span.bold {
+                highlight.font_weight = Some(FontWeight::BOLD)
+            }
+            if span.italic {
+                highlight.font_style = Some(FontStyle::Italic)
+            }
+            if span.underline {
+                highlight.underline = Some(UnderlineStyle {
+                    thickness: px(1.0),
+                    ..Default::default()
+                })
+            }
+        }
+        if let Some(current) = search {
+            highlight.background_color = Some(if current {
+                (&style.current_match_colour).into()
+            } else {
+                (&style.search_match_colour).into()
+            })
+        }
+
+        highlights.push((range, highlight))
+    }
+
+    highlights
+}
+
+fn render_line(line: &AnsiLine, matches: &[(Range<usize>, bool)], style: &CodeStyle) -> AnyElement {
+    div()
+        .w_full()
+        .h(style.line_height.abs())
+        .px(px(6.0))
+        .whitespace_nowrap()
+        .overflow_hidden()
+        .child(
+            StyledText::new(line.text.clone())
+                .with_highlights(line_highlights(line, matches, style)),
+        )
+        .into_any_element()
+}
+
+impl RenderOnce for OutputView {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let style = cx.style().clone();
+
+        // the output is only parsed again when it changes
+        let mut hasher = DefaultHasher::new();
+        self.output.hash(&mut hasher);
+        let output = self.output.clone();
+        let lines = window
+            .use_keyed_state(("output-lines", hasher.finish() as usize), cx, |_, _| {
+                Rc::new(parse_ansi(&output))
+            })
+            .read(cx)
+            .clone();
+
+        let query = window.use_keyed_state("output-query", cx, |_, _| String::new());
+        let current = window.use_keyed_state("output-current-match", cx, |_, _| 0usize);
+        let scroll_handle = window
+            .use_keyed_state("output-scroll", cx, |_, _| UniformListScrollHandle::new())
+            .read(cx)
+            .clone();
+
+        let typed = query.read(cx).clone();
+        let matches = Rc::new(find_matches(&lines, &typed));
+        // the match jumped to is kept in range when the output or the query changes
+        let current_match = if matches.is_empty() {
+            0
+        } else {
+            *current.read(cx) % matches.len()
+        };
+
+        let change_query = query.clone();
+        let change_current = current.clone();
+        let submit_matches = matches.clone();
+        let submit_scroll = scroll_handle.clone();
+        let copied = self.output.clone();
+        let line_matches = matches.clone();
+        let line_style = style.code.clone();
+
+        div()
+            .flex()
+            .flex_col()
+            .w_full()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .pb(style.padding.abs())
+                    .child(div().flex_grow().child("Output"))
+                    .when(!typed.is_empty(), |_self| {
+                        _self.child(
+                            div()
+                                .mr(style.margin.abs())
+                                .text_sm()
+                                .text_color(&style.muted_text_colour)
+                                .child(if matches.is_empty() {
+                                    String::from("No matches")
+                                } else {
+                                    format!("{} of {}", current_match + 1, matches.len())
+                                }),
+                        )
+                    })
+                    .child(
+                        div().mr(style.margin.abs()).child(
+                            TextInput::new("output-search")
+                                .value(typed)
+                                .placeholder("Search output")
+                                .w(Size::Px(160.0))
+                                .h(Size::Px(24.0))
+                                .text_size(Size::Px(13.0))
+                                .text_colour(&style.text_colour)
+                                .placeholder_colour(&style.muted_text_colour)
+                                .border_colour(&style.separator_colour)
+                                .focus_border_colour(&style.primary_colour)
+                                .rounding_all(style.rounding)
+                                .on_change(move |value, _window, _cx| {
+                                    change_query.update(_cx, |x, _| *x = value.clone());
+                                    change_current.update(_cx, |x, _| *x = 0);
+                                    _window.refresh()
+                                })
+                                // enter jumps to the next match, going back to the first after the last
+                                .on_submit(move |_value, _window, _cx| {
+                                    if submit_matches.is_empty() {
+                                        return;
+                                    }
+                                    let next = (current_match + 1) % submit_matches.len();
+                                    current.update(_cx, |x, _| *x = next);
+                                    submit_scroll.scroll_to_item_strict(
+                                        submit_matches[next].0,
+                                        ScrollStrategy::Center,
+                                    );
+                                    _window.refresh()
+                                })
+                                .render(window, cx),
+                        ),
+                    )
+                    .child(
+                        Button::new("copy-output")
+                            .text("Copy")
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(60.0))
+                            .h(Size::Px(24.0))
+                            .colour(&style.bg_colour)
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(&style.text_colour)
+                            .text_size(Size::Px(13.0))
+                            .rounding_all(style.rounding)
+                            .on_click(move |_e, _window, _cx| {
+                                // the copy is the output as it was printed, escape codes included
+                                _cx.write_to_clipboard(ClipboardItem::new_string(copied.clone()));
+                                _cx.alert_info(None::<String>, "Copied the output", Some(2000))
+                            })
+                            .render(window, cx),
+                    ),
+            )
+            .child(
+                uniform_list("output-view", lines.len(), move |range, _window, _cx| {
+                    range
+                        .map(|index| {
+                            let line_matches = line_matches
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, x)| x.0 == index)
+                                .map(|(match_index, x)| (x.1.clone(), match_index == current_match))
+                                .collect::<Vec<_>>();
+                            render_line(&lines[index], &line_matches, &line_style)
+                        })
+                        .collect::<Vec<AnyElement>>()
+                })
+                .track_scroll(scroll_handle.clone())
+                .with_decoration(
+                    Scrollbar::new(scroll_handle).thumb_colour(&style.separator_colour),
+                )
+                .w_full()
+                .h(px(240.0))
+                .flex_none()
+                .py(px(4.0))
+                .bg(&style.code.bg_colour)
+                .rounded(style.rounding.abs())
+                .text_color(&style.text_colour)
+                .font_family("monospace")
+                .text_size(style.code.text_size.abs()),
+            )
+    }
+}
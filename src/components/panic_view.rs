@@ -0,0 +1,186 @@
+use crate::components::test_info::open_in_editor;
+use crate::state::SourceViewState;
+use crate::style::{Colour, StyleProvider};
+use crate::utils::panic::{AssertionKind, Panic, diff_chars};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, BorrowAppContext, HighlightStyle, InteractiveElement, IntoElement, MouseButton,
+    ParentElement, RenderOnce, Styled, StyledText, Window, div, px,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(IntoElement)]
+/// A failed test's panic split into its message, where it happened and the values of a failed assertion
+pub struct PanicView {
+    pub panic: Panic,
+    /// Used to find the file the panic happened in so it can be opened
+    pub project_path: PathBuf,
+}
+
+/// One side of a failed assertion with the characters that differ from the other side highlighted
+fn assertion_value(
+    title: &str,
+    value: String,
+    changed: Vec<Range<usize>>,
+    colour: &Colour,
+    cx: &App,
+) -> impl IntoElement {
+    let highlights = changed
+        .into_iter()
+        .map(|x| {
+            (
+                x,
+                HighlightStyle {
+                    background_color: Some(colour.into()),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_1()
+        .min_w(px(0.0))
+        .child(
+            div()
+                .pb(px(2.0))
+                .text_sm()
+                .text_color(&cx.style().muted_text_colour)
+                .child(title.to_string()),
+        )
+        .child(
+            div()
+                .p(cx.style().test_info.block_padding.def())
+                .rounded(cx.style().rounding.abs())
+                .bg(&cx.style().code.bg_colour)
+                .text_color(&cx.style().text_colour)
+                .font_family("monospace")
+                .text_size(cx.style().code.text_size.abs())
+                .child(StyledText::new(value).with_highlights(highlights)),
+        )
+}
+
+impl RenderOnce for PanicView {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let style = cx.style().clone();
+
+        // the diff can be slow for big values so it is only worked out again when the values change
+        let diff = self.panic.assertion.clone().map(|assertion| {
+            let mut hasher = DefaultHasher::new();
+            (&assertion.left, &assertion.right).hash(&mut hasher);
+            let key = ("panic-diff", hasher.finish() as usize);
+            let changed = window
+                .use_keyed_state(key, cx, |_, _| match assertion.kind {
+                    AssertionKind::Equal => diff_chars(&assertion.left, &assertion.right),
+                    // the values are the same so there is nothing to highlight
+                    AssertionKind::NotEqual => (Vec::new(), Vec::new()),
+                })
+                .read(cx)
+                .clone();
+            (assertion, changed)
+        });
+
+        // the file is only searched for once for each test, not every frame
+        let location = self.panic.location.map(|x| {
+            let path = cx.update_global::<SourceViewState, Option<PathBuf>>(|global, _| {
+                global.resolve_file(&self.project_path, &x.file)
+            });
+            (
+                format!("{}:{}:{}", x.file, x.line, x.column),
+                path,
+                x.line,
+                x.column,
+            )
+        });
+
+        div()
+            .flex()
+            .flex_col()
+            .w_full()
+            .child(
+                div()
+                    .bg(&style.test_info.error_background)
+                    .text_color(&style.test_info.error_foreground)
+                    .rounded(style.rounding.abs())
+                    .p(style.test_info.block_padding.def())
+                    .child(if self.panic.message.is_empty() {
+                        String::from("Panicked without a message")
+                    } else {
+                        self.panic.message
+                    }),
+            )
+            .when_some(location, |_self, (text, path, line, column)| {
+                _self.child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .pt(px(4.0))
+                        .text_sm()
+                        .child(
+                            div()
+                                .text_color(&style.muted_text_colour)
+                                .pr(px(4.0))
+                                .child(match &self.panic.thread {
+                                    Some(thread) => format!("Thread '{}' panicked at", thread),
+                                    None => String::from("Panicked at"),
+                                }),
+                        )
+                        .child(
+                            div()
+                                .id("panic-location")
+                                .when_some(path, |__self, path| {
+                                    // the location opens in the editor when it is a file in the project
+                                    __self
+                                        .cursor_pointer()
+                                        .text_color(&style.primary_colour)
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            move |_e, _window, _cx| {
+                                                open_in_editor(&path, line, column, _cx)
+                                            },
+                                        )
+                                })
+                                .child(text),
+                        ),
+                )
+            })
+            .when_some(diff, |_self, (assertion, (left_changed, right_changed))| {
+                _self.child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .w_full()
+                        .gap(style.padding.abs())
+                        .pt(style.padding.abs())
+                        .child(assertion_value(
+                            "Left",
+                            assertion.left,
+                            left_changed,
+                            &style.test_info.diff_left_background,
+                            cx,
+                        ))
+                        .child(assertion_value(
+                            "Right",
+                            assertion.right,
+                            right_changed,
+                            &style.test_info.diff_right_background,
+                            cx,
+                        )),
+                )
+            })
+            .when(!self.panic.notes.is_empty(), |_self| {
+                _self.child(
+                    div()
+                        .pt(px(4.0))
+                        .text_sm()
+                        .text_color(&style.muted_text_colour)
+                        .child(self.panic.notes.join("\n")),
+                )
+            })
+    }
+}
@@ -0,0 +1,217 @@
+use crate::components::run_settings::open_run_settings;
+use crate::state::{RunSettingsTarget, State, StateProvider};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::button::icon_button::IconButton;
+use crate::widgets::core::icon::Icons;
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
+use crate::{AlertHandler, ModalHelper};
+use gpui::prelude::FluentBuilder;
+use gpui::{App, BorrowAppContext, Entity, ParentElement, RenderOnce, Styled, Window, div, px};
+
+/// Adds a profile with the typed name and makes it the active one
+fn create_profile(project_id: u32, name: Entity<String>, window: &mut Window, cx: &mut App) {
+    let new_name = name.read(cx).trim().to_string();
+    let result = cx.update_global::<State, Result<(), String>>(|global, _| {
+        global.add_profile(project_id, new_name.clone())
+    });
+
+    match result {
+        Ok(_) => {
+            cx.update_global::<State, ()>(|global, _| {
+                global.set_active_profile(project_id, Some(new_name))
+            });
+            name.update(cx, |x, _| x.clear());
+        }
+        Err(err) => cx.alert_warning(None::<String>, err, Some(3000)),
+    }
+
+    window.refresh()
+}
+
+/// Opens the list of a project's run profiles, where they can be selected, edited, added and removed
+pub fn open_profile_menu(project_id: u32, window: &mut Window, cx: &mut App) {
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let active = _cx
+            .state()
+            .get_project(project_id)
+            .and_then(|x| x.active_profile);
+        let new_name = _window.use_keyed_state("new-profile-name", _cx, |_, _| String::new());
+
+        // None is the project's own run settings
+        let mut options: Vec<Option<String>> = vec![None];
+        options.extend(
+            _cx.state()
+                .get_profiles(project_id)
+                .into_iter()
+                .map(|x| Some(x.name)),
+        );
+
+        let mut rows = Vec::new();
+
+        for (index, option) in options.into_iter().enumerate() {
+            let selected = option == active;
+            let select_option = option.clone();
+            let edit_target = match option.clone() {
+                Some(name) => RunSettingsTarget::Profile(project_id, name),
+                None => RunSettingsTarget::Project(project_id),
+            };
+
+            rows.push(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .mb(style.margin.abs())
+                    .child(
+                        Button::new(("profile-option", index))
+                            .text(option.clone().unwrap_or(String::from("Default")))
+                            .justify_content(ContentPosition::Start)
+                            .align_text(ContentPosition::Centre)
+                            .h(Size::Px(30.0))
+                            .pa(style.padding)
+                            .colour(if selected {
+                                &style.primary_colour
+                            } else {
+                                &style.secondary_bg_colour
+                            })
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(&style.text_colour)
+                            .rounding_all(style.rounding)
+                            .tooltip(if option.is_none() {
+                                "Use the project's own run settings"
+                            } else {
+                                "Use this profile"
+                            })
+                            .on_click(move |_e, __window, __cx| {
+                                __cx.update_global::<State, ()>(|global, _| {
+                                    global.set_active_profile(project_id, select_option.clone())
+                                });
+                                __window.close_modal(__cx)
+                            })
+                            .render(_window, _cx),
+                    )
+                    .child(
+                        IconButton::new(("profile-edit", index))
+                            .icon(Icons::Settings)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(30.0))
+                            .h(Size::Px(30.0))
+                            .ml(style.margin)
+                            .icon_size(Size::Px(15.0))
+                            .colour(Colour::Rgba(0x00000000))
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .icon_colour(&style.text_colour)
+                            .rounding_all(style.rounding)
+                            .tooltip("Edit run settings")
+                            .on_click(move |_e, __window, __cx| {
+                                __window.close_modal(__cx);
+                                open_run_settings(edit_target.clone(), __window, __cx)
+                            })
+                            .render(_window, _cx),
+                    )
+                    .when_some(option, |_self, name| {
+                        _self.child(
+                            IconButton::new(("profile-delete", index))
+                                .icon(Icons::Trash)
+                                .justify_content(ContentPosition::Centre)
+                                .align_text(ContentPosition::Centre)
+                                .w(Size::Px(30.0))
+                                .h(Size::Px(30.0))
+                                .ml(style.margin)
+                                .icon_size(Size::Px(15.0))
+                                .colour(Colour::Rgba(0x00000000))
+                                .hover_colour(Colour::Rgba(0xffffff22))
+                                .icon_colour(&style.failed_colour)
+                                .rounding_all(style.rounding)
+                                .tooltip("Delete profile")
+                                .on_click(move |_e, __window, __cx| {
+                                    __cx.update_global::<State, ()>(|global, _| {
+                                        global.remove_profile(project_id, &name)
+                                    });
+                                    __window.refresh()
+                                })
+                                .render(_window, _cx),
+                        )
+                    }),
+            )
+        }
+
+        let typed_name = new_name.read(_cx).clone();
+        let change_name = new_name.clone();
+        let submit_name = new_name.clone();
+        let click_name = new_name.clone();
+
+        let body = div().flex().flex_col().w_full().children(rows).child(
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .pt(style.padding.abs())
+                .child(
+                    TextInput::new("new-profile-input")
+                        .value(typed_name)
+                        .placeholder("New profile name")
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, __window, __cx| {
+                            change_name.update(__cx, |x, _| *x = value.clone());
+                            __window.refresh()
+                        })
+                        .on_submit(move |_value, __window, __cx| {
+                            create_profile(project_id, submit_name.clone(), __window, __cx)
+                        })
+                        .render(_window, _cx),
+                )
+                .child(
+                    Button::new("add-profile-button")
+                        .text("Add")
+                        .justify_content(ContentPosition::Centre)
+                        .align_text(ContentPosition::Centre)
+                        .w(Size::Px(60.0))
+                        .h(Size::Px(26.0))
+                        .ml(style.margin)
+                        .colour(&style.primary_colour)
+                        .hover_colour(&style.hover_primary_colour)
+                        .text_colour(&style.text_colour)
+                        .rounding_all(style.rounding)
+                        .tooltip("Add a profile using the project's current run settings")
+                        .on_click(move |_e, __window, __cx| {
+                            create_profile(project_id, click_name.clone(), __window, __cx)
+                        })
+                        .render(_window, _cx),
+                ),
+        );
+
+        modal
+            .title("Run Profiles")
+            .body(body)
+            .w(px(400.0))
+            .h(px(400.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
@@ -0,0 +1,676 @@
+use crate::ModalHelper;
+use crate::state::{EnvVar, RunArgs, RunEnvironment, RunSettingsTarget, State, StateProvider};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::button::icon_button::IconButton;
+use crate::widgets::core::checkbox::CheckBox;
+use crate::widgets::core::icon::Icons;
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, BorrowAppContext, Div, InteractiveElement, IntoElement, ParentElement,
+    RenderOnce, StatefulInteractiveElement, Styled, Window, div, px,
+};
+
+/// A heading separating groups of options
+fn section_title(title: &str, cx: &App) -> Div {
+    div()
+        .w_full()
+        .pt(px(8.0))
+        .pb(cx.style().padding.abs())
+        .text_color(&cx.style().muted_text_colour)
+        .text_sm()
+        .child(title.to_string())
+}
+
+/// The title and description shown on the left of every option
+fn option_label(title: &str, description: &str, cx: &App) -> Div {
+    div().flex().flex_col().child(title.to_string()).child(
+        div()
+            .text_color(&cx.style().muted_text_colour)
+            .text_xs()
+            .child(description.to_string()),
+    )
+}
+
+/// An option that is turned on or off
+fn checkbox_option(
+    target: &RunSettingsTarget,
+    title: &str,
+    description: &str,
+    checked: bool,
+    toggle: fn(&mut RunArgs),
+    cx: &mut App,
+) -> Div {
+    let target = target.clone();
+
+    div()
+        .flex()
+        .flex_row()
+        .justify_between()
+        .items_center()
+        .pb(cx.style().padding.abs())
+        .child(option_label(title, description, cx))
+        .child(cx.new(|_cx| {
+            CheckBox::new()
+                .checked(checked)
+                .checked_colour(&_cx.style().primary_colour)
+                .unchecked_colour(Colour::Rgba(0x00000000))
+                .symbol_colour(Colour::Rgba(0xffffffaa))
+                .border_colour(&_cx.style().muted_text_colour)
+                .border(Size::Px(2.0))
+                .size(Size::Px(20.0))
+                .on_toggle(move |_e, _window, __cx| {
+                    __cx.update_global::<State, ()>(|global, _| {
+                        global.update_run_args(&target, toggle)
+                    });
+                    _window.refresh()
+                })
+        }))
+}
+
+/// What an option with a typed value is and which run arg it edits
+struct InputOption {
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
+    placeholder: &'static str,
+    edit: fn(&mut RunArgs, String),
+}
+
+/// An option with a typed value, e.g. a list of package names
+fn input_option(
+    target: &RunSettingsTarget,
+    option: InputOption,
+    value: &str,
+    window: &mut Window,
+    cx: &mut App,
+) -> Div {
+    let target = target.clone();
+    let edit = option.edit;
+
+    div()
+        .flex()
+        .flex_row()
+        .justify_between()
+        .items_center()
+        .pb(cx.style().padding.abs())
+        .child(option_label(option.title, option.description, cx))
+        .child(
+            TextInput::new(option.id)
+                .value(value)
+                .placeholder(option.placeholder)
+                .w(Size::Px(180.0))
+                .text_colour(&cx.style().text_colour)
+                .placeholder_colour(&cx.style().muted_text_colour)
+                .border_colour(&cx.style().separator_colour)
+                .focus_border_colour(&cx.style().primary_colour)
+                .rounding_all(cx.style().rounding)
+                .on_change(move |value, _window, _cx| {
+                    _cx.update_global::<State, ()>(|global, _| {
+                        global.update_run_args(&target, |args| edit(args, value.clone()))
+                    });
+                    _window.refresh()
+                })
+                .render(window, cx),
+        )
+}
+
+/// The filter of a profile, this isn't part of RunArgs so it is edited separately
+fn filter_option(
+    project_id: u32,
+    profile: &str,
+    value: &str,
+    window: &mut Window,
+    cx: &mut App,
+) -> Div {
+    let profile = profile.to_string();
+
+    div()
+        .flex()
+        .flex_row()
+        .justify_between()
+        .items_center()
+        .pb(cx.style().padding.abs())
+        .child(option_label(
+            "Filter",
+            "Only run tests with names containing this",
+            cx,
+        ))
+        .child(
+            TextInput::new("run-settings-filter")
+                .value(value)
+                .placeholder("all tests")
+                .w(Size::Px(180.0))
+                .text_colour(&cx.style().text_colour)
+                .placeholder_colour(&cx.style().muted_text_colour)
+                .border_colour(&cx.style().separator_colour)
+                .focus_border_colour(&cx.style().primary_colour)
+                .rounding_all(cx.style().rounding)
+                .on_change(move |value, _window, _cx| {
+                    _cx.update_global::<State, ()>(|global, _| {
+                        global.update_profile(project_id, &profile, |x| x.filter = value.clone())
+                    });
+                    _window.refresh()
+                })
+                .render(window, cx),
+        )
+}
+
+/// The working directory and environment variables, each variable is a row with a name and value
+fn environment_options(
+    target: &RunSettingsTarget,
+    environment: &RunEnvironment,
+    window: &mut Window,
+    cx: &mut App,
+) -> Div {
+    let style = cx.style().clone();
+    let working_dir_target = target.clone();
+    let mut rows = Vec::new();
+
+    for (index, var) in environment.env.iter().enumerate() {
+        let key_target = target.clone();
+        let value_target = target.clone();
+        let remove_target = target.clone();
+
+        rows.push(
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .pb(style.padding.abs())
+                .child(
+                    TextInput::new(("run-settings-env-key", index))
+                        .value(&var.key)
+                        .placeholder("NAME")
+                        .w(Size::Px(150.0))
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, _window, _cx| {
+                            _cx.update_global::<State, ()>(|global, _| {
+                                global.update_environment(&key_target, |x| {
+                                    if let Some(var) = x.env.get_mut(index) {
+                                        var.key = value.clone()
+                                    }
+                                })
+                            });
+                            _window.refresh()
+                        })
+                        .render(window, cx),
+                )
+                .child(div().px(px(6.0)).child("="))
+                .child(
+                    TextInput::new(("run-settings-env-value", index))
+                        .value(&var.value)
+                        .placeholder("value")
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, _window, _cx| {
+                            _cx.update_global::<State, ()>(|global, _| {
+                                global.update_environment(&value_target, |x| {
+                                    if let Some(var) = x.env.get_mut(index) {
+                                        var.value = value.clone()
+                                    }
+                                })
+                            });
+                            _window.refresh()
+                        })
+                        .render(window, cx),
+                )
+                .child(
+                    IconButton::new(("run-settings-env-remove", index))
+                        .icon(Icons::Trash)
+                        .justify_content(ContentPosition::Centre)
+                        .align_text(ContentPosition::Centre)
+                        .w(Size::Px(26.0))
+                        .h(Size::Px(26.0))
+                        .ml(style.margin)
+                        .icon_size(Size::Px(14.0))
+                        .colour(Colour::Rgba(0x00000000))
+                        .hover_colour(Colour::Rgba(0xffffff22))
+                        .icon_colour(&style.failed_colour)
+                        .rounding_all(style.rounding)
+                        .tooltip("Remove variable")
+                        .on_click(move |_e, _window, _cx| {
+                            _cx.update_global::<State, ()>(|global, _| {
+                                global.update_environment(&remove_target, |x| {
+                                    if index < x.env.len() {
+                                        x.env.remove(index);
+                                    }
+                                })
+                            });
+                            _window.refresh()
+                        })
+                        .render(window, cx),
+                ),
+        )
+    }
+
+    // the common variables get their own buttons so they don't have to be typed out
+    let mut add_buttons = Vec::new();
+
+    for (index, (text, key, value)) in [
+        ("Add Variable", "", ""),
+        ("RUST_BACKTRACE", "RUST_BACKTRACE", "1"),
+        ("RUST_LOG", "RUST_LOG", "debug"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let add_target = target.clone();
+
+        add_buttons.push(
+            Button::new(("run-settings-env-add", index))
+                .text(text)
+                .justify_content(ContentPosition::Centre)
+                .align_text(ContentPosition::Centre)
+                .w(Size::Px(120.0))
+                .h(Size::Px(26.0))
+                .mr(style.margin)
+                .colour(&style.bg_colour)
+                .hover_colour(Colour::Rgba(0xffffff22))
+                .border_all(Size::Px(1.0))
+                .border_colour(&style.separator_colour)
+                .text_size(Size::Px(13.0))
+                .text_colour(&style.text_colour)
+                .rounding_all(style.rounding)
+                .on_click(move |_e, _window, _cx| {
+                    _cx.update_global::<State, ()>(|global, _| {
+                        global.update_environment(&add_target, |x| {
+                            x.env.push(EnvVar {
+                                key: key.to_string(),
+                                value: value.to_string(),
+                            })
+                        })
+                    });
+                    _window.refresh()
+                })
+                .render(window, cx)
+                .into_any_element(),
+        )
+    }
+
+    div()
+        .flex()
+        .flex_col()
+        .child(
+            div()
+                .flex()
+                .flex_row()
+                .justify_between()
+                .items_center()
+                .pb(style.padding.abs())
+                .child(option_label(
+                    "Working Directory",
+                    "Relative to the project folder, or absolute",
+                    cx,
+                ))
+                .child(
+                    TextInput::new("run-settings-working-dir")
+                        .value(&environment.working_dir)
+                        .placeholder("project folder")
+                        .w(Size::Px(180.0))
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, _window, _cx| {
+                            _cx.update_global::<State, ()>(|global, _| {
+                                global.update_environment(&working_dir_target, |x| {
+                                    x.working_dir = value.clone()
+                                })
+                            });
+                            _window.refresh()
+                        })
+                        .render(window, cx),
+                ),
+        )
+        .children(rows)
+        .child(
+            div()
+                .flex()
+                .flex_row()
+                .pb(style.padding.abs())
+                .children(add_buttons),
+        )
+}
+
+/// Opens the modal for editing the cargo test options of a project or one of its profiles
+pub fn open_run_settings(target: RunSettingsTarget, window: &mut Window, cx: &mut App) {
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let args = _cx.state().get_run_args(&target).unwrap_or_default();
+        let environment = _cx.state().get_environment(&target).unwrap_or_default();
+        let (title, profile) = match &target {
+            RunSettingsTarget::Project(id) => (
+                format!(
+                    "Run Configuration - {}",
+                    _cx.state()
+                        .get_project(*id)
+                        .unwrap_or_default()
+                        .display_name()
+                ),
+                None,
+            ),
+            RunSettingsTarget::Profile(id, name) => (
+                format!("Run Profile - {}", name),
+                _cx.state()
+                    .get_profiles(*id)
+                    .into_iter()
+                    .find(|x| &x.name == name)
+                    .map(|x| (*id, x)),
+            ),
+        };
+
+        let options = div()
+            .id("run-settings-options")
+            .flex()
+            .flex_col()
+            .w_full()
+            .h(px(420.0))
+            .pr(_cx.style().padding.abs())
+            .overflow_y_scroll()
+            .when_some(profile, |_self, (project_id, profile)| {
+                _self
+                    .child(section_title("Profile", _cx))
+                    .child(filter_option(
+                        project_id,
+                        &profile.name,
+                        &profile.filter,
+                        _window,
+                        _cx,
+                    ))
+            })
+            .child(section_title("Targets", _cx))
+            .child(checkbox_option(
+                &target,
+                "Lib",
+                "Run the library tests",
+                args.lib,
+                |x| x.lib = !x.lib,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Bins",
+                "Run the tests of every binary",
+                args.bins,
+                |x| x.bins = !x.bins,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Doc",
+                "Run the doc tests",
+                args.doc,
+                |x| x.doc = !x.doc,
+                _cx,
+            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-bin",
+                    title: "Bin",
+                    description: "Run the tests of the named binaries",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.bin_targets = value,
+                },
+                &args.bin_targets,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-test",
+                    title: "Test",
+                    description: "Run the named integration tests",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.test_targets = value,
+                },
+                &args.test_targets,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-bench",
+                    title: "Bench",
+                    description: "Run the tests of the named benchmarks",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.bench_targets = value,
+                },
+                &args.bench_targets,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-example",
+                    title: "Example",
+                    description: "Run the tests of the named examples",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.example_targets = value,
+                },
+                &args.example_targets,
+                _window,
+                _cx,            ))
+            .child(section_title("Packages", _cx))
+            .child(checkbox_option(
+                &target,
+                "Workspace",
+                "Run the workspace tests",
+                args.workspace,
+                |x| x.workspace = !x.workspace,
+                _cx,
+            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-package",
+                    title: "Package",
+                    description: "Only run the tests of these packages",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.package = value,
+                },
+                &args.package,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-exclude",
+                    title: "Exclude",
+                    description: "Skip these packages, needs workspace",
+                    placeholder: "name, ...",
+                    edit: |x, value| x.exclude = value,
+                },
+                &args.exclude,
+                _window,
+                _cx,            ))
+            .child(section_title("Features", _cx))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-features",
+                    title: "Features",
+                    description: "Features to enable",
+                    placeholder: "feature, ...",
+                    edit: |x, value| x.features = value,
+                },
+                &args.features,
+                _window,
+                _cx,            ))
+            .child(checkbox_option(
+                &target,
+                "All Features",
+                "Enable every feature",
+                args.all_features,
+                |x| x.all_features = !x.all_features,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "No Default Features",
+                "Don't enable the default features",
+                args.no_default_features,
+                |x| x.no_default_features = !x.no_default_features,
+                _cx,
+            ))
+            .child(section_title("Build", _cx))
+            .child(checkbox_option(
+                &target,
+                "Release",
+                "Build with optimisations",
+                args.release,
+                |x| x.release = !x.release,
+                _cx,
+            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-profile",
+                    title: "Profile",
+                    description: "Build with the named profile",
+                    placeholder: "profile",
+                    edit: |x, value| x.profile = value,
+                },
+                &args.profile,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-target",
+                    title: "Target",
+                    description: "Build for the target triple",
+                    placeholder: "triple",
+                    edit: |x, value| x.target = value,
+                },
+                &args.target,
+                _window,
+                _cx,            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-jobs",
+                    title: "Jobs",
+                    description: "Number of parallel build jobs",
+                    placeholder: "default",
+                    edit: |x, value| x.jobs = value,
+                },
+                &args.jobs,
+                _window,
+                _cx,            ))
+            .child(section_title("Running", _cx))
+            .child(checkbox_option(
+                &target,
+                "No Fail Fast",
+                "Continue tests after a failure",
+                args.no_fail_fast,
+                |x| x.no_fail_fast = !x.no_fail_fast,
+                _cx,
+            ))
+            .child(input_option(
+                &target,
+                InputOption {
+                    id: "run-settings-test-threads",
+                    title: "Test Threads",
+                    description: "Number of threads used to run tests",
+                    placeholder: "default",
+                    edit: |x, value| x.test_threads = value,
+                },
+                &args.test_threads,
+                _window,
+                _cx,            ))
+            .child(checkbox_option(
+                &target,
+                "Include Ignored",
+                "Run ignored tests as well",
+                args.include_ignored,
+                |x| x.include_ignored = !x.include_ignored,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "No Capture",
+                "Print test output as it happens, it is only matched to its test when tests run on one thread",
+                args.nocapture,
+                |x| x.nocapture = !x.nocapture,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Show Output",
+                "Keep the output of passing tests as well as failing ones",
+                args.show_output,
+                |x| x.show_output = !x.show_output,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Report Times",
+                "Record how long each test takes, this uses an unstable libtest option so RUSTC_BOOTSTRAP=1 is set",
+                args.report_time,
+                |x| x.report_time = !x.report_time,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Backtraces",
+                "Show where failing tests panicked from, this sets RUST_BACKTRACE=1",
+                args.backtrace,
+                |x| x.backtrace = !x.backtrace,
+                _cx,
+            ))
+            .child(checkbox_option(
+                &target,
+                "Full Backtraces",
+                "Include every frame in backtraces, this sets RUST_BACKTRACE=full",
+                args.full_backtrace,
+                |x| x.full_backtrace = !x.full_backtrace,
+                _cx,
+            ))
+            .child(section_title("Environment", _cx))
+            .child(environment_options(&target, &environment, _window, _cx));
+
+        let body = div().flex().flex_col().size_full().child(options).child(
+            div()
+                .pt(_cx.style().padding.abs())
+                .text_sm()
+                .text_color(&_cx.style().failed_colour)
+                .child(args.validate().err().unwrap_or_default()),
+        );
+
+        modal
+            .title(title.clone())
+            .body(body)
+            .h(px(560.0))
+            .w(px(500.0))
+            .rounding(_cx.style().rounding)
+            .bg_colour(&_cx.style().bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: _cx.style().bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(_cx.style().separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: _cx.style().rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
@@ -0,0 +1,154 @@
+use crate::ModalHelper;
+use crate::state::{State, StateProvider};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::editor::EDITOR_PRESETS;
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
+use gpui::{
+    App, AppContext, BorrowAppContext, IntoElement, ParentElement, RenderOnce, Styled, Window, div,
+    px,
+};
+
+/// Opens the app wide settings, they are saved to settings.json in the config folder when Apollo closes
+pub fn open_settings(window: &mut Window, cx: &mut App) {
+    // kept outside of the settings so half typed values can be shown
+    let editor_command = cx.new(|_cx| _cx.state().settings.editor_command.clone());
+    let threshold = cx.new(|_cx| _cx.state().settings.slow_test_threshold.to_string());
+
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let typed_command = editor_command.read(_cx).clone();
+        let typed_threshold = threshold.read(_cx).clone();
+
+        let mut presets = Vec::new();
+
+        for (index, (name, command)) in EDITOR_PRESETS.iter().enumerate() {
+            let preset_command = editor_command.clone();
+
+            presets.push(
+                Button::new(("editor-preset", index))
+                    .text(*name)
+                    .justify_content(ContentPosition::Centre)
+                    .align_text(ContentPosition::Centre)
+                    .w(Size::Px(80.0))
+                    .h(Size::Px(26.0))
+                    .mr(style.margin)
+                    .colour(if typed_command == *command {
+                        &style.primary_colour
+                    } else {
+                        &style.secondary_bg_colour
+                    })
+                    .hover_colour(&style.hover_primary_colour)
+                    .text_colour(&style.text_colour)
+                    .text_size(Size::Px(13.0))
+                    .rounding_all(style.rounding)
+                    .tooltip(command)
+                    .on_click(move |_e, __window, __cx| {
+                        preset_command.update(__cx, |x, _| *x = command.to_string());
+                        __cx.update_global::<State, ()>(|global, _| {
+                            global.update_settings(|x| x.editor_command = command.to_string())
+                        });
+                        __window.refresh()
+                    })
+                    .render(_window, _cx)
+                    .into_any_element(),
+            )
+        }
+
+        let change_command = editor_command.clone();
+        let change_threshold = threshold.clone();
+
+        let body = div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .child(div().pb(px(4.0)).child("Editor command"))
+            .child(
+                TextInput::new("editor-command-input")
+                    .value(typed_command)
+                    .placeholder(EDITOR_PRESETS[0].1)
+                    .text_colour(&style.text_colour)
+                    .placeholder_colour(&style.muted_text_colour)
+                    .border_colour(&style.separator_colour)
+                    .focus_border_colour(&style.primary_colour)
+                    .rounding_all(style.rounding)
+                    .on_change(move |value, __window, __cx| {
+                        change_command.update(__cx, |x, _| *x = value.clone());
+                        __cx.update_global::<State, ()>(|global, _| {
+                            global.update_settings(|x| x.editor_command = value.clone())
+                        });
+                        __window.refresh()
+                    })
+                    .render(_window, _cx),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .py(style.padding.abs())
+                    .children(presets),
+            )
+            .child(
+                div()
+                    .pb(style.padding.abs())
+                    .text_sm()
+                    .text_color(&style.muted_text_colour)
+                    .child(
+                        "{file}, {line} and {column} are replaced with where the test is, environment variables such as $EDITOR can also be used",
+                    ),
+            )
+            .child(div().pb(px(4.0)).child("Slow test threshold (seconds)"))
+            .child(
+                TextInput::new("settings-slow-threshold-input")
+                    .value(typed_threshold)
+                    .placeholder("1.0")
+                    .w(Size::Px(100.0))
+                    .text_colour(&style.text_colour)
+                    .placeholder_colour(&style.muted_text_colour)
+                    .border_colour(&style.separator_colour)
+                    .focus_border_colour(&style.primary_colour)
+                    .rounding_all(style.rounding)
+                    .on_change(move |value, __window, __cx| {
+                        change_threshold.update(__cx, |x, _| *x = value.clone());
+
+                        // the threshold is only saved once the text is a valid number
+                        if let Ok(seconds) = value.trim().parse::<f64>()
+                            && seconds >= 0.0
+                        {
+                            __cx.update_global::<State, ()>(|global, _| {
+                                global.update_settings(|x| x.slow_test_threshold = seconds)
+                            });
+                        }
+                        __window.refresh()
+                    })
+                    .render(_window, _cx),
+            );
+
+        modal
+            .title("Settings")
+            .body(body)
+            .w(px(500.0))
+            .h(px(340.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
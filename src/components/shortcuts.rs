@@ -0,0 +1,103 @@
+use crate::ModalHelper;
+use crate::actions::{SHORTCUTS, shortcut_keys};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::file::keymap_file;
+use crate::widgets::core::modal::ModalButtonOptions;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, InteractiveElement, ParentElement, StatefulInteractiveElement, Styled, Window, div, px,
+};
+
+/// Opens a list of every keyboard shortcut and the keys bound to it
+pub fn open_shortcuts(window: &mut Window, cx: &mut App) {
+    window.open_modal(cx, |modal, _window, _cx| {
+        let style = _cx.style().clone();
+
+        let mut rows = Vec::new();
+
+        for shortcut in SHORTCUTS {
+            let keys = shortcut_keys(shortcut.name, _cx);
+
+            rows.push(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .flex_none()
+                    .h(px(28.0))
+                    .px(style.padding.abs())
+                    .border_b(px(1.0))
+                    .border_color(&style.separator_colour)
+                    .child(div().flex_grow().child(shortcut.description))
+                    .child(
+                        div()
+                            .flex_none()
+                            .px(px(6.0))
+                            .rounded(style.rounding.abs())
+                            .bg(&style.secondary_bg_colour)
+                            .text_sm()
+                            .when_else(
+                                keys.is_empty(),
+                                |_self| _self.text_color(&style.muted_text_colour).child("Unbound"),
+                                |_self| _self.child(keys),
+                            ),
+                    ),
+            )
+        }
+
+        let keymap_path = match keymap_file() {
+            Ok(res) => res.display().to_string(),
+            Err(_) => "keymap.json in the config folder".to_string(),
+        };
+
+        let body = div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .child(
+                div()
+                    .id("shortcuts")
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .h(px(380.0))
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+            .child(
+                div()
+                    .pt(style.padding.abs())
+                    .text_sm()
+                    .text_color(&style.muted_text_colour)
+                    .child(format!(
+                        "Change these in {}, they are loaded when Apollo starts",
+                        keymap_path
+                    )),
+            );
+
+        modal
+            .title("Keyboard Shortcuts")
+            .body(body)
+            .w(px(500.0))
+            .h(px(540.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
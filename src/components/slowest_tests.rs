@@ -0,0 +1,237 @@
+use crate::ModalHelper;
+use crate::state::{State, StateProvider, test_id};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::utils::format_duration;
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, BorrowAppContext, InteractiveElement, MouseButton, ParentElement, RenderOnce,
+    StatefulInteractiveElement, Styled, Window, div, px, rgba,
+};
+
+/// Opens a list of the active project's tests ordered by how long they took, selecting one shows it in the test info
+pub fn open_slowest_tests(project_id: u32, window: &mut Window, cx: &mut App) {
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let project = _cx.state().get_project(project_id).unwrap_or_default();
+        let settings = _cx.state().settings.clone();
+        let target = _cx.state().active_run_settings(project_id);
+        let report_time = _cx
+            .state()
+            .get_run_args(&target)
+            .is_some_and(|x| x.report_time);
+
+        let slowest_first = _window.use_keyed_state("slowest-first", _cx, |_, _| true);
+        let threshold = _window.use_keyed_state("slow-threshold", _cx, |_, __cx| {
+            __cx.state().settings.slow_test_threshold.to_string()
+        });
+        let descending = *slowest_first.read(_cx);
+        let typed_threshold = threshold.read(_cx).clone();
+
+        // the index is kept as it is what selects the test in the test list
+        let mut tests: Vec<(usize, String, f64)> = project
+            .tests_linear()
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, test)| {
+                project
+                    .durations
+                    .get(&test_id(&test))
+                    .map(|duration| (index, test.module_path, *duration))
+            })
+            .collect();
+
+        tests.sort_by(|a, b| a.2.total_cmp(&b.2));
+        if descending {
+            tests.reverse()
+        }
+
+        let mut rows = Vec::new();
+
+        for (index, module_path, duration) in tests {
+            let slow = settings.is_slow(duration);
+
+            rows.push(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .flex_none()
+                    .h(px(26.0))
+                    .px(style.padding.abs())
+                    .rounded(style.rounding.abs())
+                    .hover(|_style| _style.bg(rgba(0xffffff22)))
+                    .on_mouse_down(MouseButton::Left, move |_e, __window, __cx| {
+                        __cx.update_global::<State, ()>(|global, _| global.select_test(index));
+                        __window.close_modal(__cx)
+                    })
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(80.0))
+                            .when_else(
+                                slow,
+                                |_self| _self.text_color(&style.ignore_colour),
+                                |_self| _self.text_color(&style.muted_text_colour),
+                            )
+                            .child(format_duration(duration)),
+                    )
+                    .child(div().flex_grow().overflow_hidden().child(module_path)),
+            )
+        }
+
+        let toggle_order = slowest_first.clone();
+        let change_threshold = threshold.clone();
+
+        let body = div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .pb(style.padding.abs())
+                    .child(
+                        Button::new("slowest-order-button")
+                            .text(if descending {
+                                "Slowest first"
+                            } else {
+                                "Fastest first"
+                            })
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(110.0))
+                            .h(Size::Px(26.0))
+                            .colour(&style.secondary_bg_colour)
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(&style.text_colour)
+                            .text_size(Size::Px(13.0))
+                            .rounding_all(style.rounding)
+                            .tooltip("Reverse the order")
+                            .on_click(move |_e, __window, __cx| {
+                                toggle_order.update(__cx, |x, _| *x = !*x);
+                                __window.refresh()
+                            })
+                            .render(_window, _cx),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .ml_auto()
+                            .mr(style.margin.abs())
+                            .text_sm()
+                            .text_color(&style.muted_text_colour)
+                            .child("Slow after (seconds)"),
+                    )
+                    .child(
+                        TextInput::new("slow-threshold-input")
+                            .value(typed_threshold)
+                            .placeholder("1.0")
+                            .w(Size::Px(70.0))
+                            .text_colour(&style.text_colour)
+                            .placeholder_colour(&style.muted_text_colour)
+                            .border_colour(&style.separator_colour)
+                            .focus_border_colour(&style.primary_colour)
+                            .rounding_all(style.rounding)
+                            .on_change(move |value, __window, __cx| {
+                                change_threshold.update(__cx, |x, _| *x = value.clone());
+
+                                // the threshold is only saved once the text is a valid number
+                                if let Ok(seconds) = value.trim().parse::<f64>()
+                                    && seconds >= 0.0
+                                {
+                                    __cx.update_global::<State, ()>(|global, _| {
+                                        global.update_settings(|x| x.slow_test_threshold = seconds)
+                                    });
+                                }
+                                __window.refresh()
+                            })
+                            .render(_window, _cx),
+                    ),
+            )
+            // times are only recorded with --report-time, which needs RUSTC_BOOTSTRAP so it isn't on by default
+            .when(!report_time, |_self| {
+                _self.child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .mb(style.padding.abs())
+                        .p(style.padding.abs())
+                        .rounded(style.rounding.abs())
+                        .bg(&style.test_info.ignore_background)
+                        .text_color(&style.test_info.ignore_foreground)
+                        .text_sm()
+                        .child(div().flex_grow().child(
+                            "Test times are only recorded when Report Times is on. It uses an unstable libtest option, so runs set RUSTC_BOOTSTRAP=1 while it is on.",
+                        ))
+                        .child(
+                            Button::new("turn-on-report-time")
+                                .text("Turn on")
+                                .justify_content(ContentPosition::Centre)
+                                .align_text(ContentPosition::Centre)
+                                .w(Size::Px(70.0))
+                                .h(Size::Px(26.0))
+                                .ml(style.margin)
+                                .colour(&style.bg_colour)
+                                .hover_colour(&style.hover_primary_colour)
+                                .text_colour(&style.text_colour)
+                                .text_size(Size::Px(13.0))
+                                .rounding_all(style.rounding)
+                                .on_click(move |_e, __window, __cx| {
+                                    __cx.update_global::<State, ()>(|global, _| {
+                                        global.update_run_args(&target, |x| x.report_time = true)
+                                    });
+                                    __window.refresh()
+                                })
+                                .render(_window, _cx),
+                        ),
+                )
+            })
+            .child(
+                div()
+                    .id("slowest-tests")
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .h(px(380.0))
+                    .overflow_y_scroll()
+                    .when(rows.is_empty(), |_self| {
+                        _self.text_color(&style.muted_text_colour).child(
+                            "No test times have been recorded, run the tests with Report Times on to see them here",
+                        )
+                    })
+                    .children(rows),
+            );
+
+        modal
+            .title("Slowest Tests")
+            .body(body)
+            .w(px(600.0))
+            .h(px(540.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
@@ -0,0 +1,412 @@
+use crate::state::{Project, State, StateProvider};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::file::{delete_snapshot, load_snapshots, save_snapshot};
+use crate::utils::history::{GitState, HistoryEntry};
+use crate::utils::snapshot::{DiffSide, RunDiff, Snapshot};
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::button::icon_button::IconButton;
+use crate::widgets::core::icon::Icons;
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
+use crate::{AlertHandler, AsyncAlertHandler, ModalHelper};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, BorrowAppContext, Entity, InteractiveElement, ParentElement, RenderOnce,
+    StatefulInteractiveElement, Styled, Window, div, px,
+};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq)]
+/// Something that can be picked as one side of a diff
+enum DiffChoice {
+    /// The project's current tests
+    Current,
+    /// The snapshot at the given index of the loaded snapshots
+    Snapshot(usize),
+}
+
+/// The chosen older and newer sides of the diff
+type DiffChoices = (Option<DiffChoice>, Option<DiffChoice>);
+
+fn current_side(project: &Project) -> Option<DiffSide> {
+    Some(DiffSide {
+        label: String::from("Current results"),
+        groups: project.tests.clone()?,
+        durations: project.durations.clone(),
+    })
+}
+
+/// Saves the project's current tests as a snapshot and reloads the list of snapshots once it has been written
+fn take_snapshot(
+    project_id: u32,
+    name: Entity<String>,
+    snapshots: Entity<Vec<(PathBuf, Snapshot)>>,
+    cx: &mut App,
+) {
+    let project = match cx.state().get_project(project_id) {
+        Some(res) => res,
+        None => return,
+    };
+    let groups = match project.tests.clone() {
+        Some(res) => res,
+        None => {
+            cx.alert_warning(
+                None::<String>,
+                "Run the tests before taking a snapshot",
+                Some(3000),
+            );
+            return;
+        }
+    };
+
+    let typed_name = name.read(cx).trim().to_string();
+    let snapshot_name = if typed_name.is_empty() {
+        format!("Snapshot of {}", project.display_name())
+    } else {
+        typed_name
+    };
+    name.update(cx, |x, _| x.clear());
+
+    let run_args = project.run_args.clone();
+    let profile = project.active_profile.clone();
+    let scope = project
+        .last_run
+        .as_ref()
+        .map_or(String::from("all tests"), |x| x.description());
+    let partial = project.is_partial_run();
+    let path = project.path.clone();
+    let durations = project.durations.clone();
+
+    cx.spawn(async move |_cx| {
+        // the snapshot records the git commit, which can be slow to get, so it is built off the main thread
+        let result = _cx
+            .background_executor()
+            .spawn(async move {
+                let run = HistoryEntry::new(
+                    GitState::read(&path),
+                    run_args,
+                    profile,
+                    scope,
+                    partial,
+                    groups,
+                    durations,
+                );
+                save_snapshot(
+                    &path,
+                    Snapshot {
+                        name: snapshot_name,
+                        run,
+                    },
+                )
+                .map(|_| load_snapshots(&path))
+            })
+            .await;
+
+        match result {
+            Ok(res) => {
+                let _ = snapshots.update(_cx, |x, _| *x = res);
+            }
+            Err(err) => _cx.alert_error(Some("Snapshot"), err, Some(5000)),
+        }
+        let _ = _cx.refresh();
+    })
+    .detach()
+}
+
+/// Opens the diff between the two chosen sides in place of the project's test list
+fn compare(
+    project_id: u32,
+    choices: DiffChoices,
+    snapshots: &[(PathBuf, Snapshot)],
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let project = match cx.state().get_project(project_id) {
+        Some(res) => res,
+        None => return,
+    };
+
+    let side = |choice: Option<DiffChoice>| match choice? {
+        DiffChoice::Current => current_side(&project),
+        DiffChoice::Snapshot(index) => snapshots.get(index).map(|x| x.1.clone().into()),
+    };
+
+    match (side(choices.0), side(choices.1)) {
+        (Some(before), Some(after)) if choices.0 != choices.1 => {
+            cx.update_global::<State, ()>(|global, _| {
+                global.open_diff(project_id, RunDiff::new(before, after))
+            });
+            window.close_modal(cx)
+        }
+        _ => cx.alert_warning(
+            None::<String>,
+            "Pick two different results to compare",
+            Some(3000),
+        ),
+    }
+}
+
+/// Opens the list of a project's snapshots, where snapshots can be taken, deleted and compared
+pub fn open_snapshots(project_id: u32, window: &mut Window, cx: &mut App) {
+    let project = match cx.state().get_project(project_id) {
+        Some(res) => res,
+        None => return,
+    };
+
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let style = _cx.style().clone();
+        let has_tests = _cx
+            .state()
+            .get_project(project_id)
+            .is_some_and(|x| x.tests.is_some());
+
+        let path = project.path.clone();
+        let snapshots = _window.use_keyed_state("snapshot-list", _cx, |_, _| load_snapshots(&path));
+        let choices =
+            _window.use_keyed_state("snapshot-choices", _cx, |_, _| DiffChoices::default());
+        let new_name = _window.use_keyed_state("snapshot-name", _cx, |_, _| String::new());
+
+        let chosen = *choices.read(_cx);
+        let loaded = snapshots.read(_cx).clone();
+
+        let mut options: Vec<(DiffChoice, String, String)> = Vec::new();
+        if has_tests {
+            options.push((
+                DiffChoice::Current,
+                String::from("Current results"),
+                String::from("The tests currently shown in the test list"),
+            ))
+        }
+        for (index, (_, snapshot)) in loaded.iter().enumerate() {
+            options.push((
+                DiffChoice::Snapshot(index),
+                snapshot.name.clone(),
+                snapshot.run.description(),
+            ))
+        }
+
+        let mut rows = Vec::new();
+
+        for (index, (choice, name, description)) in options.into_iter().enumerate() {
+            let mut row = div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .w_full()
+                .mb(style.margin.abs())
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .flex_grow()
+                        .overflow_hidden()
+                        .child(name)
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(&style.muted_text_colour)
+                                .child(description),
+                        ),
+                );
+
+            for (side, text, tooltip) in [
+                (0, "Before", "Compare from these results"),
+                (1, "After", "Compare to these results"),
+            ] {
+                let selected = if side == 0 {
+                    chosen.0 == Some(choice)
+                } else {
+                    chosen.1 == Some(choice)
+                };
+                let choose = choices.clone();
+
+                row = row.child(
+                    Button::new(("snapshot-choice", index * 2 + side))
+                        .text(text)
+                        .justify_content(ContentPosition::Centre)
+                        .align_text(ContentPosition::Centre)
+                        .w(Size::Px(60.0))
+                        .h(Size::Px(26.0))
+                        .ml(style.margin)
+                        .colour(if selected {
+                            &style.primary_colour
+                        } else {
+                            &style.secondary_bg_colour
+                        })
+                        .hover_colour(&style.hover_primary_colour)
+                        .text_colour(&style.text_colour)
+                        .text_size(Size::Px(13.0))
+                        .rounding_all(style.rounding)
+                        .tooltip(tooltip)
+                        .on_click(move |_e, __window, __cx| {
+                            choose.update(__cx, |x, _| {
+                                if side == 0 {
+                                    x.0 = Some(choice)
+                                } else {
+                                    x.1 = Some(choice)
+                                }
+                            });
+                            __window.refresh()
+                        })
+                        .render(_window, _cx),
+                )
+            }
+
+            if let DiffChoice::Snapshot(snapshot_index) = choice {
+                let file = loaded[snapshot_index].0.clone();
+                let list = snapshots.clone();
+                let reset = choices.clone();
+                let snapshot_path = project.path.clone();
+
+                row = row.child(
+                    IconButton::new(("snapshot-delete", index))
+                        .icon(Icons::Trash)
+                        .justify_content(ContentPosition::Centre)
+                        .align_text(ContentPosition::Centre)
+                        .w(Size::Px(26.0))
+                        .h(Size::Px(26.0))
+                        .ml(style.margin)
+                        .icon_size(Size::Px(15.0))
+                        .colour(Colour::Rgba(0x00000000))
+                        .hover_colour(Colour::Rgba(0xffffff22))
+                        .icon_colour(&style.failed_colour)
+                        .rounding_all(style.rounding)
+                        .tooltip("Delete snapshot")
+                        .on_click(move |_e, __window, __cx| {
+                            delete_snapshot(&file);
+                            list.update(__cx, |x, _| *x = load_snapshots(&snapshot_path));
+                            // the indexes of the remaining snapshots have changed
+                            reset.update(__cx, |x, _| *x = DiffChoices::default());
+                            __window.refresh()
+                        })
+                        .render(_window, _cx),
+                )
+            }
+
+            rows.push(row)
+        }
+
+        let typed_name = new_name.read(_cx).clone();
+        let change_name = new_name.clone();
+        let submit_name = new_name.clone();
+        let submit_list = snapshots.clone();
+        let click_name = new_name.clone();
+        let click_list = snapshots.clone();
+        let compare_list = loaded.clone();
+
+        let body = div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .child(
+                div()
+                    .id("snapshot-entries")
+                    .flex()
+                    .flex_col()
+                    .w_full()
+                    .h(px(340.0))
+                    .overflow_y_scroll()
+                    .when(rows.is_empty(), |_self| {
+                        _self
+                            .text_color(&style.muted_text_colour)
+                            .child("Run the tests to take a snapshot of their results")
+                    })
+                    .children(rows),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .pt(style.padding.abs())
+                    .child(
+                        TextInput::new("snapshot-name-input")
+                            .value(typed_name)
+                            .placeholder("Snapshot name")
+                            .text_colour(&style.text_colour)
+                            .placeholder_colour(&style.muted_text_colour)
+                            .border_colour(&style.separator_colour)
+                            .focus_border_colour(&style.primary_colour)
+                            .rounding_all(style.rounding)
+                            .on_change(move |value, __window, __cx| {
+                                change_name.update(__cx, |x, _| *x = value.clone());
+                                __window.refresh()
+                            })
+                            .on_submit(move |_value, _, __cx| {
+                                take_snapshot(
+                                    project_id,
+                                    submit_name.clone(),
+                                    submit_list.clone(),
+                                    __cx,
+                                )
+                            })
+                            .render(_window, _cx),
+                    )
+                    .child(
+                        Button::new("take-snapshot-button")
+                            .text("Take Snapshot")
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(120.0))
+                            .h(Size::Px(26.0))
+                            .ml(style.margin)
+                            .colour(&style.primary_colour)
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(&style.text_colour)
+                            .rounding_all(style.rounding)
+                            .tooltip(
+                                "Save the current results so they can be compared against later",
+                            )
+                            .on_click(move |_e, _, __cx| {
+                                take_snapshot(
+                                    project_id,
+                                    click_name.clone(),
+                                    click_list.clone(),
+                                    __cx,
+                                )
+                            })
+                            .render(_window, _cx),
+                    ),
+            );
+
+        modal
+            .title(format!("Snapshots - {}", project.display_name()))
+            .body(body)
+            .w(px(640.0))
+            .h(px(520.0))
+            .rounding(style.rounding)
+            .bg_colour(&style.bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Compare".to_string(),
+                    colour: style.primary_colour.clone(),
+                    hover_colour: Some(style.hover_primary_colour.clone()),
+                    border_width: Size::Px(0.0),
+                    border_colour: None,
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(move |_e, __window, __cx| {
+                    compare(project_id, chosen, &compare_list, __window, __cx)
+                }),
+            ))
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: style.bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(style.separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: style.rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
@@ -0,0 +1,132 @@
+use crate::state::SourceViewState;
+use crate::style::{CodeStyle, StyleProvider};
+use crate::utils::highlight::TokenKind;
+use crate::utils::source::SourceFile;
+use crate::widgets::core::scrollbar::Scrollbar;
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AnyElement, App, HighlightStyle, IntoElement, ParentElement, RenderOnce, ScrollStrategy,
+    Styled, StyledText, Window, div, px, uniform_list,
+};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Lines shown above the test when it is scrolled to, so the attributes above it can be seen
+const CONTEXT_LINES: usize = 3;
+
+#[derive(IntoElement)]
+/// A read-only view of a source file, scrolled to where a test starts with the line it panicked on highlighted
+pub struct SourceView {
+    pub path: PathBuf,
+    pub file: Rc<SourceFile>,
+    /// The line the test starts on, or the line of the backtrace frame being shown, starting at 0
+    pub test_line: usize,
+    /// The line the test panicked on if it is in this file, starting at 0
+    pub panic_line: Option<usize>,
+}
+
+fn token_colour(kind: TokenKind, style: &CodeStyle) -> gpui::Hsla {
+    match kind {
+        TokenKind::Keyword => &style.keyword_colour,
+        TokenKind::String => &style.string_colour,
+        TokenKind::Number => &style.number_colour,
+        TokenKind::Comment => &style.comment_colour,
+        TokenKind::Attribute => &style.attribute_colour,
+        TokenKind::Macro => &style.macro_colour,
+        TokenKind::Type => &style.type_colour,
+        TokenKind::Function => &style.function_colour,
+        TokenKind::Lifetime => &style.lifetime_colour,
+    }
+    .into()
+}
+
+fn render_line(
+    file: &SourceFile,
+    line: usize,
+    test_line: usize,
+    panic_line: Option<usize>,
+    style: &CodeStyle,
+) -> AnyElement {
+    let highlights = file.tokens[line]
+        .iter()
+        .map(|x| {
+            (
+                x.range.clone(),
+                HighlightStyle {
+                    color: Some(token_colour(x.kind, style)),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    div()
+        .flex()
+        .flex_row()
+        .w_full()
+        .h(style.line_height.abs())
+        .whitespace_nowrap()
+        .overflow_hidden()
+        .when(line == test_line, |_self| _self.bg(&style.test_line_colour))
+        // the panic colour goes over the test colour when a test panics on its first line
+        .when(panic_line == Some(line), |_self| {
+            _self.bg(&style.panic_line_colour)
+        })
+        .child(
+            div()
+                .flex_none()
+                .w(px(48.0))
+                .pr(px(10.0))
+                .text_right()
+                .text_color(&style.line_number_colour)
+                .child((line + 1).to_string()),
+        )
+        .child(
+            div()
+                .flex_grow()
+                .child(StyledText::new(file.lines[line].clone()).with_highlights(highlights)),
+        )
+        .into_any_element()
+}
+
+impl RenderOnce for SourceView {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let style = cx.style().code.clone();
+        let scroll_handle = cx.global::<SourceViewState>().scroll_handle();
+
+        // only scrolled when a different test is shown so the code can still be scrolled through
+        let shown = Some((self.path.clone(), self.test_line));
+        if cx.global::<SourceViewState>().last_shown != shown {
+            cx.global_mut::<SourceViewState>().last_shown = shown;
+            scroll_handle.scroll_to_item_strict(
+                self.test_line.saturating_sub(CONTEXT_LINES),
+                ScrollStrategy::Top,
+            )
+        }
+
+        let file = self.file.clone();
+        let test_line = self.test_line;
+        let panic_line = self.panic_line;
+        let line_style = style.clone();
+
+        uniform_list(
+            "source-view",
+            file.lines.len(),
+            move |range, _window, _cx| {
+                range
+                    .map(|x| render_line(&file, x, test_line, panic_line, &line_style))
+                    .collect::<Vec<AnyElement>>()
+            },
+        )
+        .track_scroll(scroll_handle.clone())
+        .with_decoration(Scrollbar::new(scroll_handle).thumb_colour(&cx.style().separator_colour))
+        .w_full()
+        .h(px(320.0))
+        .flex_none()
+        .py(px(4.0))
+        .bg(&style.bg_colour)
+        .rounded(cx.style().rounding.abs())
+        .font_family("monospace")
+        .text_size(style.text_size.abs())
+    }
+}
@@ -1,17 +1,44 @@
-use crate::state::StateProvider;
+use crate::state::{StateProvider, TestListState};
 use crate::style::StyleProvider;
 use crate::widgets::core::divider::Divider;
 use crate::widgets::core::spinner::Spinner;
 use crate::widgets::styling::Direction;
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    AppContext, Context, IntoElement, ParentElement, Render, RenderOnce, Styled, Window, div, px,
+    AppContext, BorrowAppContext, Context, IntoElement, ParentElement, Render, RenderOnce, Styled,
+    Window, div, px,
 };
 
 pub struct StatusBar {}
 
 impl Render for StatusBar {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let cancelled = cx.state().active_project().map_or(false, |x| x.cancelled);
+        let active_runs = cx.state().active_runs();
+        // which run of a repeated run is in progress for the active project
+        let repeat_progress = cx
+            .state()
+            .active_project()
+            .filter(|x| x.running)
+            .and_then(|x| x.repeat.clone())
+            .filter(|x| !x.is_finished())
+            .map(|x| format!("Run {} of {}", x.completed + 1, x.total));
+        let watch_status = cx.state().active_project().filter(|x| x.watching).map(|x| {
+            if x.watch_queued {
+                "Watching, run queued"
+            } else {
+                "Watching"
+            }
+        });
+
+        // the count comes from the test list's rows so the tests aren't counted again every frame
+        let tests_run = cx.update_global::<TestListState, _>(|global, _cx| {
+            _cx.state()
+                .active_project()
+                .filter(|x| x.tests.is_some())
+                .map(|x| global.rows(x).total)
+        });
+
         div()
             .flex()
             .h(cx.style().statusbar.height.get())
@@ -22,7 +49,7 @@ impl Render for StatusBar {
             .border_t(px(1.0))
             .border_color(&cx.style().separator_colour)
             .child(div().w(px(100.0)).when_else(
-                cx.state().status.running_tests,
+                active_runs > 0,
                 |_self| {
                     _self.child(
                         div()
@@ -30,10 +57,14 @@ impl Render for StatusBar {
                             .flex_row()
                             .items_center()
                             .child(cx.new(|_| Spinner::new()))
-                            .child(div().ml(px(5.0)).child("Running tests")),
+                            .child(div().ml(px(5.0)).child(if active_runs > 1 {
+                                format!("{} runs active", active_runs)
+                            } else {
+                                repeat_progress.unwrap_or(String::from("Running tests"))
+                            })),
                     )
                 },
-                |_self| _self.child("Idle"),
+                |_self| _self.child(if cancelled { "Cancelled" } else { "Idle" }),
             ))
             .child(
                 Divider::new()
@@ -43,21 +74,21 @@ impl Render for StatusBar {
                     .margin(4.0)
                     .render(window, cx),
             )
-            .child(div().w(px(100.0)).when_else(
-                cx.state().get_active_project().is_some()
-                    && cx.state().get_active_project().unwrap().tests.is_some(),
-                |_self| {
-                    _self.child(format!(
-                        "{} Tests Run",
-                        cx.state()
-                            .get_active_project()
-                            .unwrap()
-                            .tests_linear()
-                            .unwrap()
-                            .len()
-                    ))
-                },
-                |_self| _self.child("No Tests Run"),
-            ))
+            .child(div().w(px(100.0)).child(match tests_run {
+                Some(count) => format!("{} Tests Run", count),
+                None => String::from("No Tests Run"),
+            }))
+            .when_some(watch_status, |_self, status| {
+                _self
+                    .child(
+                        Divider::new()
+                            .colour(&cx.style().separator_colour)
+                            .thickness(1.0)
+                            .direction(Direction::Vertical)
+                            .margin(4.0)
+                            .render(window, cx),
+                    )
+                    .child(div().text_color(&cx.style().primary_colour).child(status))
+            })
     }
 }
@@ -1,16 +1,27 @@
-use crate::state::State;
+use crate::components::control_bar::stop_watching;
+use crate::state::{State, StateProvider};
 use crate::style::{Size, Style, StyleProvider};
 use crate::widgets::core::button::button::{Button, ContentPosition};
 use crate::widgets::core::button::icon_button::IconButton;
 use crate::widgets::core::divider::Divider;
 use crate::widgets::core::icon::Icons;
+use crate::widgets::core::spinner::Spinner;
 use crate::widgets::styling::{Colour, Direction};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    AppContext, BorrowAppContext, Context, FontWeight, InteractiveElement, IntoElement,
+    App, AppContext, BorrowAppContext, Context, FontWeight, InteractiveElement, IntoElement,
     MouseButton, ParentElement, Render, RenderOnce, Styled, Window, div, px, rgb, rgba,
 };
 
+/// Stops the project's run and watcher and removes its tab
+pub fn close_project(id: u32, cx: &mut App) {
+    stop_watching(id, cx);
+    cx.update_global::<State, ()>(|global, _| {
+        global.cancel_run(id);
+        global.remove_project(id);
+    })
+}
+
 #[derive(Clone)]
 pub struct TabBarItem {
     pub name: String,
@@ -21,6 +32,7 @@ pub struct TabBarItem {
 impl Render for TabBarItem {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let id = self.project_id;
+        let running = cx.state().is_running(id);
 
         div()
             .flex()
@@ -33,7 +45,16 @@ impl Render for TabBarItem {
             .h(cx.style().tabbar.height.get())
             .pl(px(4.0))
             .font_weight(FontWeight(900.0))
-            .child(self.name.clone())
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .when(running, |_self| {
+                        _self.child(div().mr(px(4.0)).child(cx.new(|_| Spinner::new())))
+                    })
+                    .child(self.name.clone()),
+            )
             .border_b_4()
             .when_else(
                 self.active,
@@ -76,11 +97,8 @@ impl Render for TabBarItem {
                             .hover_colour(Colour::Rgba(0xffffff22))
                             .rounding_all(Size::Px(100.0))
                             .on_click(move |e, window, _cx| {
-                                _cx.update_global::<State, ()>(|global, _| {
-                                    global.remove_project(id);
-                                    println!("removed");
-                                    window.refresh()
-                                })
+                                close_project(id, _cx);
+                                window.refresh()
                             })
                             .render(window, cx),
                     )
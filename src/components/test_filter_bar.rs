@@ -0,0 +1,259 @@
+use crate::state::{State, StateProvider, TestListState};
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::filter::TestFilter;
+use crate::utils::test_tree::TestSort;
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::text_input::TextInput;
+use cargo_ptest::parse::{GeneralTestType, Status};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, BorrowAppContext, ElementId, InteractiveElement, IntoElement, MouseButton, ParentElement,
+    RenderOnce, SharedString, StatefulInteractiveElement, Styled, Window, div, px,
+};
+
+/// The height of the filter bar in pixels
+const FILTER_BAR_HEIGHT: f32 = 64.0;
+
+/// A toggleable filter option, filled in with the given colour while it's active
+fn chip(
+    id: impl Into<ElementId>,
+    text: String,
+    active: bool,
+    colour: &Colour,
+    cx: &App,
+    on_click: impl Fn(&mut TestFilter) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(id)
+        .flex()
+        .flex_none()
+        .items_center()
+        .h(px(22.0))
+        .mr(cx.style().margin.abs())
+        .px(px(8.0))
+        .rounded(cx.style().rounding.abs())
+        .border(px(1.0))
+        .border_color(colour)
+        .text_xs()
+        .when_else(
+            active,
+            |_self| _self.bg(colour).text_color(&cx.style().bg_colour),
+            |_self| {
+                _self
+                    .text_color(colour)
+                    .hover(|style| style.bg(Colour::Rgba(0xffffff22)))
+            },
+        )
+        .on_mouse_down(MouseButton::Left, move |_e, _window, _cx| {
+            _cx.update_global::<State, ()>(|global, _| {
+                global.update_filter(global.active_project, |x| on_click(x))
+            });
+            _window.refresh()
+        })
+        .child(text)
+}
+
+/// The search box, sort order and filter chips above the test list
+pub struct TestFilterBar {}
+
+impl RenderOnce for TestFilterBar {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let (filter, sort, total, has_durations) = match cx.state().active_project() {
+            Some(res) => (
+                res.filter.clone(),
+                res.sort,
+                res.tests.iter().flatten().map(|x| x.tests.len()).sum(),
+                !res.durations.is_empty(),
+            ),
+            None => (TestFilter::default(), TestSort::default(), 0, false),
+        };
+        // the rows are shared with the test list so the tests are only filtered once
+        let rows = cx.update_global::<TestListState, _>(|global, _cx| {
+            _cx.state().active_project().map(|x| global.rows(x))
+        });
+        let shown = rows.as_ref().map_or(0, |x| x.shown);
+        let search_focus =
+            cx.update_global::<TestListState, _>(|global, _cx| global.search_focus(_cx));
+
+        let mut chips = Vec::new();
+
+        for (status, text, colour) in [
+            (Status::Passed, "Passed", &cx.style().passed_colour),
+            (Status::Failed, "Failed", &cx.style().failed_colour),
+            (Status::Ignored, "Ignored", &cx.style().ignore_colour),
+        ] {
+            let active = filter.statuses.contains(&status);
+            chips.push(
+                chip(
+                    SharedString::from(format!("status-chip-{}", text)),
+                    text.to_string(),
+                    active,
+                    colour,
+                    cx,
+                    move |x| x.toggle_status(status.clone()),
+                )
+                .into_any_element(),
+            )
+        }
+
+        for (test_type, text) in [
+            (GeneralTestType::Normal, "Normal"),
+            (GeneralTestType::Doc, "Doc"),
+        ] {
+            let active = filter.test_types.contains(&test_type);
+            chips.push(
+                chip(
+                    SharedString::from(format!("type-chip-{}", text)),
+                    text.to_string(),
+                    active,
+                    &cx.style().doctest_colour,
+                    cx,
+                    move |x| x.toggle_test_type(test_type.clone()),
+                )
+                .into_any_element(),
+            )
+        }
+
+        // files that are filtered on are kept as chips even if no tests were run from them this time
+        let mut files = rows.map(|x| x.files.to_vec()).unwrap_or_default();
+        for file in filter.files.iter() {
+            if !files.contains(file) {
+                files.push(file.clone())
+            }
+        }
+
+        for file in files {
+            let active = filter.files.contains(&file);
+            let toggled = file.clone();
+            chips.push(
+                chip(
+                    SharedString::from(format!("file-chip-{}", file)),
+                    file,
+                    active,
+                    &cx.style().muted_text_colour,
+                    cx,
+                    move |x| x.toggle_file(toggled.clone()),
+                )
+                .into_any_element(),
+            )
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_none()
+            .w_full()
+            .h(px(FILTER_BAR_HEIGHT))
+            .px(cx.style().padding.abs())
+            .border_b(px(1.0))
+            .border_color(&cx.style().separator_colour)
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .h(px(34.0))
+                    .child(
+                        TextInput::new("test-search")
+                            .focus_handle(search_focus)
+                            .value(filter.query.clone())
+                            .placeholder("Search tests")
+                            .h(Size::Px(24.0))
+                            .text_size(Size::Px(13.0))
+                            .text_colour(&cx.style().text_colour)
+                            .placeholder_colour(&cx.style().muted_text_colour)
+                            .border_colour(&cx.style().separator_colour)
+                            .focus_border_colour(&cx.style().primary_colour)
+                            .rounding_all(cx.style().rounding)
+                            .on_change(|value, _window, _cx| {
+                                _cx.update_global::<State, ()>(|global, _| {
+                                    global.update_filter(global.active_project, |x| {
+                                        x.query = value.clone()
+                                    })
+                                });
+                                _window.refresh()
+                            })
+                            .render(window, cx),
+                    )
+                    .child(
+                        div().flex_none().ml(cx.style().margin.abs()).child(
+                            Button::new("test-sort-button")
+                                .text(format!("Sort: {}", sort.label()))
+                                .justify_content(ContentPosition::Centre)
+                                .align_text(ContentPosition::Centre)
+                                .w(Size::Px(150.0))
+                                .h(Size::Px(22.0))
+                                .colour(&cx.style().secondary_bg_colour)
+                                .hover_colour(Colour::Rgba(0xffffff22))
+                                .text_colour(&cx.style().text_colour)
+                                .text_size(Size::Px(13.0))
+                                .rounding_all(cx.style().rounding)
+                                .tooltip("Change the order of the test list")
+                                .on_click(move |_e, _window, _cx| {
+                                    _cx.update_global::<State, ()>(|global, _| {
+                                        global.set_sort(global.active_project, sort.next())
+                                    });
+                                    _window.refresh()
+                                })
+                                .render(window, cx),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .mx(cx.style().margin.abs())
+                            .text_sm()
+                            .text_color(&cx.style().muted_text_colour)
+                            .child(format!("{} of {}", shown, total)),
+                    )
+                    .when(filter.is_active(), |_self| {
+                        _self.child(
+                            div().flex_none().child(
+                                Button::new("clear-filter-button")
+                                    .text("Clear")
+                                    .justify_content(ContentPosition::Centre)
+                                    .align_text(ContentPosition::Centre)
+                                    .w(Size::Px(60.0))
+                                    .h(Size::Px(22.0))
+                                    .colour(&cx.style().secondary_bg_colour)
+                                    .hover_colour(Colour::Rgba(0xffffff22))
+                                    .text_colour(&cx.style().text_colour)
+                                    .text_size(Size::Px(13.0))
+                                    .rounding_all(cx.style().rounding)
+                                    .tooltip("Clear the search and filters")
+                                    .on_click(|_e, _window, _cx| {
+                                        _cx.update_global::<State, ()>(|global, _| {
+                                            global.update_filter(global.active_project, |x| {
+                                                *x = TestFilter::default()
+                                            })
+                                        });
+                                        _window.refresh()
+                                    })
+                                    .render(window, cx),
+                            ),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .id("filter-chips")
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .h(px(28.0))
+                    .overflow_x_scroll()
+                    // sorting by time does nothing until times have been recorded, which is off by default
+                    .when(sort == TestSort::Duration && !has_durations, |_self| {
+                        _self.child(
+                            div()
+                                .flex_none()
+                                .mr(cx.style().margin.abs())
+                                .text_xs()
+                                .text_color(&cx.style().ignore_colour)
+                                .child("No test times, turn on Report Times in the run settings"),
+                        )
+                    })
+                    .children(chips),
+            )
+    }
+}
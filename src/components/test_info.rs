@@ -1,16 +1,91 @@
-use crate::state::StateProvider;
-use crate::style::StyleProvider;
+use crate::AlertHandler;
+use crate::components::backtrace_view::BacktraceView;
+use crate::components::output_view::OutputView;
+use crate::components::panic_view::PanicView;
+use crate::components::source_view::SourceView;
+use crate::state::{SourceViewState, StateProvider};
+use crate::style::{Size, StyleProvider};
+use crate::utils::editor;
+use crate::utils::panic::parse_panic;
+use crate::utils::source::{panic_location, test_location};
+use crate::utils::utils::format_duration;
+use crate::widgets::core::button::button::{Button, ContentPosition};
 use crate::widgets::core::divider::Divider;
 use crate::widgets::styling::Direction;
 use cargo_ptest::parse::{GeneralTestType, ParsedTest, Status};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    App, AppContext, InteractiveElement, IntoElement, ParentElement, Render, RenderOnce,
-    StatefulInteractiveElement, Styled, Window, div, px, rgb,
+    App, AppContext, BorrowAppContext, InteractiveElement, IntoElement, ParentElement, Render,
+    RenderOnce, StatefulInteractiveElement, Styled, Window, div, px, rgb,
 };
+use std::path::Path;
 
 pub struct TestInfo {}
 
+/// Opens a file in the editor from the settings, the line and column start at 1
+pub fn open_in_editor(path: &Path, line: usize, column: usize, cx: &mut App) {
+    let template = cx.state().settings.editor_command.clone();
+
+    if let Err(err) = editor::open_in_editor(&template, path, line, column) {
+        cx.alert_error(Some("Could not open the editor"), err, Some(5000))
+    }
+}
+
+/// Opens one of the active project's tests in the editor from the settings, at the top of the file if the test itself can't be found
+pub fn open_test_in_editor(test: &ParsedTest, cx: &mut App) {
+    let location = cx.state().active_project().and_then(|project| {
+        let file = cx.state().get_test_file(test)?;
+        test_location(&project.path, &file, test)
+    });
+
+    match location {
+        Some((path, line)) => open_in_editor(&path, line.unwrap_or(0) + 1, 1, cx),
+        None => cx.alert_warning(
+            None::<String>,
+            format!("Could not find the file {} is in", test.module_path),
+            Some(3000),
+        ),
+    }
+}
+
+/// Finds the selected test's code or the backtrace frame picked for it, None if the file or the test itself can't be found
+fn source_view(test: &ParsedTest, cx: &mut App) -> Option<SourceView> {
+    let project_path = cx.state().active_project()?.path.clone();
+    let file = cx.state().get_test_file(test)?;
+
+    let panic = test.error_reason.as_deref().and_then(panic_location);
+
+    // a frame picked from the backtrace is shown in place of the test
+    let (path, source, test_line, panic_path) =
+        cx.update_global::<SourceViewState, _>(|global, _| {
+            let (path, line) = match global.frame(test) {
+                Some(frame) => frame,
+                None => {
+                    let path = global.test_source(&project_path, &file, test)?;
+                    let line = global.test_line(&path, test)?;
+                    (path, line)
+                }
+            };
+            let source = global.file(&path)?;
+            let panic_path = panic
+                .as_ref()
+                .and_then(|x| global.resolve_file(&project_path, &x.file));
+            Some((path, source, line, panic_path))
+        })?;
+
+    // the panic is only shown if it happened in the same file, e.g. not in a helper somewhere else
+    let panic_line = panic
+        .filter(|_| panic_path.is_some_and(|x| x == path))
+        .map(|x| x.line.saturating_sub(1));
+
+    Some(SourceView {
+        path,
+        file: source,
+        test_line,
+        panic_line,
+    })
+}
+
 impl RenderOnce for TestInfo {
     fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
         let has_selected_test =
@@ -18,6 +93,42 @@ impl RenderOnce for TestInfo {
 
         if has_selected_test {
             let test = cx.state().get_selected_test().unwrap();
+            let duration = cx.state().get_selected_test_duration();
+            let slow = duration.is_some_and(|x| cx.state().settings.is_slow(x));
+            let source = source_view(&test, cx);
+            let has_source = source.is_some();
+            let showing_frame = cx.global::<SourceViewState>().frame(&test).is_some();
+            // errors that aren't a panic, e.g. a should_panic test that didn't, are shown as they are
+            let panic = test
+                .error_reason
+                .as_deref()
+                .filter(|_| test.status == Status::Failed)
+                .and_then(parse_panic)
+                .zip(cx.state().active_project().map(|x| x.path.clone()))
+                .map(|(panic, project_path)| PanicView {
+                    panic,
+                    project_path,
+                });
+            let has_panic = panic.is_some();
+            let output = cx.state().get_test_output(&test);
+            let output_missing = output.is_none();
+            // the run uses the active profile's args when there is one
+            let show_output = cx
+                .state()
+                .active_project()
+                .and_then(|x| {
+                    let target = cx.state().active_run_settings(x.id);
+                    cx.state().get_run_args(&target)
+                })
+                .is_some_and(|x| x.show_output);
+            let backtrace = panic
+                .as_ref()
+                .filter(|x| !x.panic.backtrace.is_empty())
+                .map(|x| BacktraceView {
+                    frames: x.panic.backtrace.clone(),
+                    test: test.clone(),
+                    project_path: x.project_path.clone(),
+                });
             div()
                 .flex()
                 .flex_col()
@@ -79,8 +190,8 @@ impl RenderOnce for TestInfo {
                             div()
                                 .grid()
                                 .grid_cols(2)
-                                .grid_rows(3)
-                                .h(px(87.0))
+                                .grid_rows(4)
+                                .h(px(116.0))
                                 //.gap(cx.style().test_info.grid_padding.def())
                                 .border_color(&cx.style().separator_colour)
                                 .child(
@@ -128,9 +239,36 @@ impl RenderOnce for TestInfo {
                                         .child(
                                             test.file_path.unwrap_or_else(|| "Unknown".to_string()),
                                         ),
+                                )
+                                .child(
+                                    div()
+                                        .border_r(px(1.0))
+                                        .border_color(&cx.style().separator_colour)
+                                        .child("Duration"),
+                                )
+                                .child(
+                                    div()
+                                        .pl(cx.style().test_info.grid_padding.def())
+                                        .when(slow, |__self| {
+                                            __self.text_color(&cx.style().ignore_colour)
+                                        })
+                                        .child(match duration {
+                                            Some(duration) if slow => {
+                                                format!("{} (slow)", format_duration(duration))
+                                            }
+                                            Some(duration) => format_duration(duration),
+                                            None => String::from("Not recorded"),
+                                        }),
                                 ),
                         )
-                        .when(test.status == Status::Failed, |_self| {
+                        .when_some(panic, |_self, panic| {
+                            _self.child(div().w_full().py(cx.style().padding.abs()).child(panic))
+                        })
+                        .when_some(backtrace, |_self, backtrace| {
+                            _self
+                                .child(div().w_full().pb(cx.style().padding.abs()).child(backtrace))
+                        })
+                        .when(test.status == Status::Failed && !has_panic, |_self| {
                             _self.child(
                                 div()
                                     .bg(&cx.style().test_info.error_background)
@@ -158,6 +296,111 @@ impl RenderOnce for TestInfo {
                                         .child(test.ignore_reason.unwrap()),
                                 )
                             },
+                        )
+                        .when_some(output, |_self, output| {
+                            _self.child(
+                                div()
+                                    .w_full()
+                                    .pt(cx.style().padding.abs())
+                                    .child(OutputView { output }),
+                            )
+                        })
+                        // passing tests' output is only captured with --show-output
+                        .when(
+                            test.status == Status::Passed && !show_output && output_missing,
+                            |_self| {
+                                _self.child(
+                                    div()
+                                        .pt(cx.style().padding.abs())
+                                        .text_sm()
+                                        .text_color(&cx.style().muted_text_colour)
+                                        .child("Turn on Show Output in the run settings to see what passing tests print"),
+                                )
+                            },
+                        )
+                        .child(
+                            div()
+                                .w_full()
+                                .py(cx.style().padding.abs())
+                                .when_some(source, |_self, source| {
+                                    let path = source.path.clone();
+                                    let line = source.test_line + 1;
+
+                                    _self
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .flex_row()
+                                                .items_center()
+                                                .pb(cx.style().padding.abs())
+                                                .child(
+                                                    div()
+                                                        .flex_grow()
+                                                        .overflow_hidden()
+                                                        .text_sm()
+                                                        .text_color(&cx.style().muted_text_colour)
+                                                        .child(format!(
+                                                            "{}:{}",
+                                                            path.display(),
+                                                            line
+                                                        )),
+                                                )
+                                                .when(showing_frame, |__self| {
+                                                    __self.child(
+                                                        Button::new("back-to-test")
+                                                            .text("Back to test")
+                                                            .justify_content(
+                                                                ContentPosition::Centre,
+                                                            )
+                                                            .align_text(ContentPosition::Centre)
+                                                            .w(Size::Px(100.0))
+                                                            .h(Size::Px(24.0))
+                                                            .mr(cx.style().margin)
+                                                            .colour(&cx.style().bg_colour)
+                                                            .hover_colour(
+                                                                &cx.style().hover_primary_colour,
+                                                            )
+                                                            .text_colour(&cx.style().text_colour)
+                                                            .text_size(Size::Px(13.0))
+                                                            .rounding_all(cx.style().rounding)
+                                                            .on_click(|_e, _window, _cx| {
+                                                                _cx.global_mut::<SourceViewState>()
+                                                                    .clear_frame();
+                                                                _window.refresh()
+                                                            })
+                                                            .render(window, cx),
+                                                    )
+                                                })
+                                                .child(
+                                                    Button::new("open-in-editor")
+                                                        .text("Open in editor")
+                                                        .justify_content(ContentPosition::Centre)
+                                                        .align_text(ContentPosition::Centre)
+                                                        .w(Size::Px(110.0))
+                                                        .h(Size::Px(24.0))
+                                                        .colour(&cx.style().bg_colour)
+                                                        .hover_colour(
+                                                            &cx.style().hover_primary_colour,
+                                                        )
+                                                        .text_colour(&cx.style().text_colour)
+                                                        .text_size(Size::Px(13.0))
+                                                        .rounding_all(cx.style().rounding)
+                                                        .tooltip(
+                                                            &cx.state().settings.editor_command,
+                                                        )
+                                                        .on_click(move |_e, _window, _cx| {
+                                                            open_in_editor(&path, line, 1, _cx)
+                                                        })
+                                                        .render(window, cx),
+                                                ),
+                                        )
+                                        .child(source)
+                                })
+                                .when(!has_source, |_self| {
+                                    _self
+                                        .text_color(&cx.style().muted_text_colour)
+                                        .child("Source code not found")
+                                }),
                         ),
                 )
         } else {
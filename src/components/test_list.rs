@@ -1,88 +1,167 @@
+use crate::components::collapsable_stack::CollapsableStack;
 use crate::components::test_list_item::TestListItem;
-use crate::state::{Project, ScrollHandles, StateProvider};
+use crate::state::{State, StateProvider, TestListState, test_id};
+use crate::style::{Colour, StyleProvider};
+use crate::utils::test_tree::{TestCounts, TestRow};
+use crate::widgets::core::scrollbar::Scrollbar;
 use gpui::{
-    App, AppContext, BorrowAppContext, Context, Element, InteractiveElement, IntoElement,
-    ParentElement, Render, RenderOnce, StatefulInteractiveElement, Styled, UniformListScrollHandle,
-    Window, div, px, uniform_list,
+    AnyElement, App, AppContext, BorrowAppContext, IntoElement, ParentElement, RenderOnce,
+    ScrollStrategy, SharedString, Styled, Window, div, uniform_list,
 };
 
-pub struct TestList {
-    pub test_list_viewport: f32,
+/// The tests of the active project grouped into a tree, only the rows on screen are rendered
+pub struct TestList {}
+
+/// Renders the header of a node in the test tree with its aggregated counts
+fn render_node(
+    key: String,
+    label: String,
+    counts: TestCounts,
+    collapsed: bool,
+    window: &mut Window,
+    cx: &mut App,
+) -> AnyElement {
+    let project_id = cx.state().active_project;
+    let toggled = key.clone();
+    let mut count_labels = Vec::new();
+
+    for (count, text, colour) in [
+        (counts.passed, "passed", &cx.style().passed_colour),
+        (counts.failed, "failed", &cx.style().failed_colour),
+        (counts.ignored, "ignored", &cx.style().ignore_colour),
+    ] {
+        if count > 0 {
+            count_labels.push(
+                div()
+                    .flex_none()
+                    .mr(cx.style().margin.abs())
+                    .text_sm()
+                    .text_color(colour)
+                    .child(format!("{} {}", count, text)),
+            )
+        }
+    }
+
+    // the rows inside the node are separate rows of the list so the stack never has children of its own
+    CollapsableStack::new(SharedString::from(key))
+        .collapsed(collapsed)
+        .header(
+            div()
+                .flex()
+                .flex_row()
+                .flex_grow()
+                .items_center()
+                .overflow_hidden()
+                .child(div().flex_grow().child(label))
+                .children(count_labels),
+        )
+        .chevron_colour(&cx.style().muted_text_colour)
+        .hover_colour(Colour::Rgba(0xffffff22))
+        .border_colour(&cx.style().separator_colour)
+        .on_toggle(move |_collapsed, _window, _cx| {
+            _cx.update_global::<State, ()>(|global, _| {
+                global.toggle_collapsed(project_id, toggled.clone())
+            });
+            _window.refresh()
+        })
+        .render(window, cx)
+        .into_any_element()
+}
+
+fn render_row(row: &TestRow, window: &mut Window, cx: &mut App) -> AnyElement {
+    let depth = match row {
+        TestRow::Node { depth, .. } | TestRow::Test { depth, .. } => *depth,
+    };
+
+    let element = match row.clone() {
+        TestRow::Node {
+            key,
+            label,
+            counts,
+            collapsed,
+            ..
+        } => render_node(key, label, counts, collapsed, window, cx),
+        TestRow::Test { test, .. } => {
+            let project = cx.state().active_project();
+            let id = test_id(&test.test);
+            let flakiness = project.and_then(|x| x.flakiness.get(&id).copied());
+            let pass_rate = project
+                .and_then(|x| x.repeat.as_ref())
+                .and_then(|x| x.results.get(&id).copied());
+            let duration = project.and_then(|x| x.durations.get(&id).copied());
+
+            cx.new(|_| TestListItem {
+                index: test.index,
+                test_data: test.test,
+                label: Some(test.name),
+                diff: None,
+                flakiness,
+                pass_rate,
+                duration,
+            })
+            .into_any_element()
+        }
+    };
+
+    div()
+        .w_full()
+        .pl(cx.style().padding.px() * depth as f32)
+        .child(element)
+        .into_any_element()
 }
 
 impl RenderOnce for TestList {
-    fn render(mut self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
-        let tests = cx
-            .state()
-            .get_active_project()
-            .unwrap_or(Project::default())
-            .tests_linear()
-            .unwrap_or(Vec::new());
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        // the project is only borrowed as cloning all of its tests every frame is slow with large projects
+        let (project_id, selected_test, filtering) = match cx.state().active_project() {
+            Some(res) => (res.id, res.selected_test, res.filter.is_active()),
+            None => return div().into_any_element(),
+        };
 
-        let line_height = 30.0;
-        let raw_height = line_height * tests.len() as f32;
-        let height = px(raw_height);
+        let (rows, scroll_handle) = cx.update_global::<TestListState, _>(|global, _cx| {
+            let rows = global.rows(_cx.state().active_project().unwrap());
+            (rows, global.scroll_handle(project_id))
+        });
 
-        let scroll_speed = 2.0;
+        if rows.rows.is_empty() && filtering {
+            return div()
+                .p(cx.style().padding.abs())
+                .text_color(&cx.style().muted_text_colour)
+                .child("No tests match the search and filters")
+                .into_any_element();
+        }
 
-        div()
-            .absolute()
-            .top(px(cx.global::<ScrollHandles>().test_list))
-            .left(px(0.0))
-            .h(height)
-            .on_scroll_wheel(move |e, _, cx| {
-                let current = cx.global::<ScrollHandles>().test_list;
-                let delta = e.delta.pixel_delta(px(1.0)).y.to_f64() as f32 * scroll_speed;
+        // a newly selected test is scrolled to, e.g. when it was picked from the slowest tests
+        let selected = selected_test.map(|x| (project_id, x));
+        let last_selected = cx.global::<TestListState>().last_selected;
+        if selected != last_selected {
+            cx.global_mut::<TestListState>().last_selected = selected;
 
-                cx.update_global::<ScrollHandles, ()>(|global, _| {
-                    if current + delta < -(raw_height - self.test_list_viewport) {
-                        global.test_list = -(raw_height - self.test_list_viewport);
-                    } else if current + delta > 0.0 {
-                        global.test_list = 0.0;
-                    } else {
-                        global.test_list += delta;
-                    }
-                })
-            })
-            .w_full()
-            .child(
-                div()
-                    .id("test-list")
-                    .flex()
-                    .flex_col()
-                    .w_full()
-                    .h(height)
-                    .overflow_scroll()
-                    .children({
-                        let mut elements = Vec::new();
+            if let Some((_, index)) = selected
+                && let Some(row) = rows
+                    .rows
+                    .iter()
+                    .position(|x| matches!(x, TestRow::Test { test, .. } if test.index == index))
+            {
+                scroll_handle.scroll_to_item(row, ScrollStrategy::Center)
+            }
+        }
 
-                        for (index, test) in tests.iter().enumerate() {
-                            elements.push(cx.new(|_| TestListItem {
-                                index,
-                                test_data: test.clone(),
-                            }))
-                        }
+        let list_rows = rows.rows.clone();
 
-                        elements
-                    }),
-            )
-        // .child(
-        //     uniform_list(
-        //         "uniform_test_list",
-        //         tests.len(),
-        //         cx.processor(move |_this, range, _window, _cx| {
-        //             let mut items = Vec::new();
-        //             for i in range {
-        //                 let test_data = tests[i as usize].clone();
-        //                 items.push(_cx.new(|_| TestItem {test_data}))
-        //             }
-        //             items
-        //         })
-        //
-        //     )
-        //         .id("test_uniform_list")
-        //         .overflow_scroll()
-        // )
-        // .overflow_scroll()
+        uniform_list(
+            SharedString::from(format!("test-list-{}", project_id)),
+            list_rows.len(),
+            move |range, _window, _cx| {
+                range
+                    .map(|x| render_row(&list_rows[x], _window, _cx))
+                    .collect::<Vec<AnyElement>>()
+            },
+        )
+        .track_scroll(scroll_handle.clone())
+        .with_decoration(Scrollbar::new(scroll_handle).thumb_colour(&cx.style().separator_colour))
+        .flex_grow()
+        .w_full()
+        .into_any_element()
     }
 }
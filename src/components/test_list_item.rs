@@ -1,21 +1,302 @@
+use crate::components::control_bar::{start_repeated_run, start_run};
+use crate::components::test_info::open_test_in_editor;
+use crate::components::tests::badge;
 use crate::state::{State, StateProvider};
-use crate::style::StyleProvider;
+use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::flaky::Flakiness;
+use crate::utils::runner::{RunScope, target_scope};
+use crate::utils::snapshot::{TestChange, TestDiff};
+use crate::utils::utils::format_duration;
+use crate::widgets::core::button::button::{Button, ContentPosition};
+use crate::widgets::core::button::icon_button::IconButton;
 use crate::widgets::core::divider::Divider;
+use crate::widgets::core::icon::Icons;
+use crate::widgets::core::modal::ModalButtonOptions;
+use crate::widgets::core::text_input::TextInput;
 use crate::widgets::styling::Direction;
-use cargo_ptest::parse::{ParsedTest, Status};
+use crate::{AlertHandler, ModalHelper};
+use cargo_ptest::parse::{GeneralTestType, ParsedTest, Status};
+use gpui::prelude::FluentBuilder;
 use gpui::{
     App, BorrowAppContext, Context, InteractiveElement, IntoElement, MouseButton, ParentElement,
     Render, RenderOnce, Styled, Window, div, px, rgba,
 };
 
+/// How many times the run menu's repeat buttons run the tests unless another count is typed
+const DEFAULT_REPEAT_COUNT: u32 = 10;
+
+/// Opens a menu with the different ways the test at the given index can be re-run
+fn open_run_menu(index: usize, test: ParsedTest, window: &mut Window, cx: &mut App) {
+    let group = cx.state().active_project().and_then(|x| x.group_of(index));
+
+    // the module a test is in, doc tests are named after the item they document so they don't have one
+    let module = match test.test_type {
+        GeneralTestType::Normal => test.module_path.rsplit_once("::").map(|x| x.0.to_string()),
+        GeneralTestType::Doc => None,
+    };
+
+    window.open_modal(cx, move |modal, _window, _cx| {
+        let mut options: Vec<(String, RunScope)> = vec![(
+            String::from("Run test"),
+            RunScope::Tests(vec![test.clone()]),
+        )];
+
+        if let Some(module) = module.clone() {
+            options.push((format!("Run module {}", module), RunScope::Module(module)))
+        }
+
+        if let Some(group) = group.clone() {
+            let name = if group.file_path.is_empty() {
+                group.crate_name.clone()
+            } else {
+                group.file_path.join("/")
+            };
+
+            // the whole target is run rather than every test by name, which would make a very long command for big targets
+            let scope = target_scope(&group).unwrap_or(RunScope::Tests(group.tests));
+            options.push((format!("Run all tests in {}", name), scope))
+        }
+
+        let style = _cx.style().clone();
+        let repeat_count =
+            _window.use_keyed_state("repeat-count", _cx, |_, _| DEFAULT_REPEAT_COUNT.to_string());
+        let typed_count = repeat_count.read(_cx).clone();
+        // an invalid count disables the repeat buttons rather than guessing what was meant
+        let times = typed_count.trim().parse::<u32>().ok().filter(|x| *x > 1);
+        let mut buttons = Vec::new();
+
+        for (option_index, (text, scope)) in options.into_iter().enumerate() {
+            let repeat_scope = scope.clone();
+
+            buttons.push(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .mb(style.margin.abs())
+                    .child(
+                        Button::new(("run-menu-option", option_index))
+                            .text(text)
+                            .justify_content(ContentPosition::Start)
+                            .align_text(ContentPosition::Centre)
+                            .h(Size::Px(30.0))
+                            .pa(style.padding)
+                            .colour(&style.secondary_bg_colour)
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(&style.text_colour)
+                            .rounding_all(style.rounding)
+                            .on_click(move |_e, __window, __cx| {
+                                __window.close_modal(__cx);
+                                start_run(scope.clone(), __cx)
+                            })
+                            .render(_window, _cx),
+                    )
+                    .child(
+                        Button::new(("run-menu-repeat", option_index))
+                            .text(match times {
+                                Some(times) => format!("x{}", times),
+                                None => String::from("x?"),
+                            })
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(50.0))
+                            .h(Size::Px(30.0))
+                            .ml(style.margin)
+                            .colour(&style.secondary_bg_colour)
+                            .hover_colour(&style.hover_primary_colour)
+                            .text_colour(if times.is_some() {
+                                &style.text_colour
+                            } else {
+                                &style.muted_text_colour
+                            })
+                            .rounding_all(style.rounding)
+                            .tooltip("Run repeatedly and count how often each test passes")
+                            .on_click(move |_e, __window, __cx| match times {
+                                Some(times) => {
+                                    __window.close_modal(__cx);
+                                    start_repeated_run(repeat_scope.clone(), times, __cx)
+                                }
+                                None => __cx.alert_warning(
+                                    None::<String>,
+                                    "The repeat count must be a number above 1",
+                                    Some(3000),
+                                ),
+                            })
+                            .render(_window, _cx),
+                    )
+                    .into_any_element(),
+            )
+        }
+
+        let change_count = repeat_count.clone();
+        let editor_test = test.clone();
+
+        buttons.push(
+            Button::new("run-menu-open-in-editor")
+                .text("Open in editor")
+                .justify_content(ContentPosition::Start)
+                .align_text(ContentPosition::Centre)
+                .h(Size::Px(30.0))
+                .mb(style.margin)
+                .pa(style.padding)
+                .colour(&style.secondary_bg_colour)
+                .hover_colour(&style.hover_primary_colour)
+                .text_colour(&style.text_colour)
+                .rounding_all(style.rounding)
+                .on_click(move |_e, __window, __cx| {
+                    __window.close_modal(__cx);
+                    open_test_in_editor(&editor_test, __cx)
+                })
+                .render(_window, _cx)
+                .into_any_element(),
+        );
+
+        let body = div().flex().flex_col().w_full().children(buttons).child(
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .pt(style.padding.abs())
+                .child(
+                    div()
+                        .flex_none()
+                        .mr(style.margin.abs())
+                        .text_color(&style.muted_text_colour)
+                        .child("Repeat count"),
+                )
+                .child(
+                    TextInput::new("repeat-count-input")
+                        .value(typed_count)
+                        .placeholder(DEFAULT_REPEAT_COUNT)
+                        .w(Size::Px(80.0))
+                        .text_colour(&style.text_colour)
+                        .placeholder_colour(&style.muted_text_colour)
+                        .border_colour(&style.separator_colour)
+                        .focus_border_colour(&style.primary_colour)
+                        .rounding_all(style.rounding)
+                        .on_change(move |value, __window, __cx| {
+                            change_count.update(__cx, |x, _| *x = value.clone());
+                            __window.refresh()
+                        })
+                        .render(_window, _cx),
+                ),
+        );
+
+        modal
+            .title("Run")
+            .body(body)
+            .w(px(400.0))
+            .h(px(340.0))
+            .rounding(_cx.style().rounding)
+            .bg_colour(&_cx.style().bg_colour)
+            .p(Size::Px(10.0))
+            .accept_button_options(None)
+            .cancel_button_options(Some(
+                ModalButtonOptions {
+                    show: true,
+                    text: "Close".to_string(),
+                    colour: _cx.style().bg_colour.clone(),
+                    hover_colour: Some(Colour::Rgba(0xffffff22)),
+                    border_width: Size::Px(1.0),
+                    border_colour: Some(_cx.style().separator_colour.clone()),
+                    padding: Size::Px(50.0),
+                    rounding: _cx.style().rounding,
+                    on_click: None,
+                }
+                .on_click(|_e, __window, __cx| __window.close_modal(__cx)),
+            ))
+            .on_close(|_e, __window, __cx| __window.close_modal(__cx))
+    })
+}
+
 pub struct TestListItem {
     pub index: usize,
     pub test_data: ParsedTest,
+    /// Shown in place of the module path, used when the test's modules are already shown by the tree it's in
+    pub label: Option<String>,
+    /// Set when the row is part of a diff, the index is then the test's index in the diff
+    pub diff: Option<TestDiff>,
+    /// How flaky the test has been across the project's run history
+    pub flakiness: Option<Flakiness>,
+    /// How many times the test passed and failed during the last repeated run
+    pub pass_rate: Option<(u32, u32)>,
+    /// How long the test took in seconds, None if the time wasn't reported
+    pub duration: Option<f64>,
+}
+
+/// Formats a duration change for a diff badge, e.g. "120ms -> 1.40s"
+fn duration_change(durations: (Option<f64>, Option<f64>)) -> String {
+    match durations {
+        (Some(before), Some(after)) => {
+            format!("{} -> {}", format_duration(before), format_duration(after))
+        }
+        _ => String::new(),
+    }
 }
 
 impl Render for TestListItem {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let index = self.index.clone();
+        let test = self.test_data.clone();
+        let menu_test = self.test_data.clone();
+        let in_diff = self.diff.is_some();
+        // removed tests can't be run as they no longer exist
+        let runnable = !self
+            .diff
+            .as_ref()
+            .is_some_and(|x| x.changes.contains(&TestChange::Removed));
+
+        let mut badges = Vec::new();
+        if let Some(diff) = &self.diff {
+            for change in diff.changes.iter() {
+                let colour = match change {
+                    TestChange::NewlyFailing => &cx.style().failed_colour,
+                    TestChange::NewlyPassing => &cx.style().passed_colour,
+                    TestChange::NewlyIgnored => &cx.style().ignore_colour,
+                    TestChange::Added | TestChange::Removed => &cx.style().doctest_colour,
+                    TestChange::Slower | TestChange::Faster => &cx.style().muted_text_colour,
+                };
+                let text = match change {
+                    TestChange::Slower | TestChange::Faster => {
+                        format!("{} {}", change.label(), duration_change(diff.durations))
+                    }
+                    _ => change.label().to_string(),
+                };
+
+                badges.push(badge(text, colour, cx).ml(cx.style().margin.abs()))
+            }
+        }
+
+        if let Some(flakiness) = self.flakiness.filter(|x| x.is_flaky()) {
+            badges.push(
+                badge(
+                    format!("Flaky {:.0}%", flakiness.score() * 100.0),
+                    &cx.style().ignore_colour,
+                    cx,
+                )
+                .ml(cx.style().margin.abs()),
+            )
+        }
+
+        if let Some((passed, failed)) = self.pass_rate {
+            let colour = if failed == 0 {
+                &cx.style().passed_colour
+            } else if passed == 0 {
+                &cx.style().failed_colour
+            } else {
+                &cx.style().ignore_colour
+            };
+
+            badges.push(
+                badge(format!("Passed {}/{}", passed, passed + failed), colour, cx)
+                    .ml(cx.style().margin.abs()),
+            )
+        }
+
+        let slow = self
+            .duration
+            .is_some_and(|x| cx.state().settings.is_slow(x));
+
         div()
             .flex()
             .flex_col()
@@ -24,13 +305,26 @@ impl Render for TestListItem {
             .justify_center()
             .hover(|style| style.bg(rgba(0xffffff22)))
             .on_mouse_down(MouseButton::Left, move |e, _window, _cx| {
-                _cx.update_global::<State, ()>(move |global, _| global.select_test(index))
+                _cx.update_global::<State, ()>(move |global, _| {
+                    if in_diff {
+                        global.select_diff_test(index)
+                    } else {
+                        global.select_test(index)
+                    }
+                })
+            })
+            .when(!in_diff, |_self| {
+                // the run menu's options are based on where the test is in the project's tests
+                _self.on_mouse_down(MouseButton::Right, move |_e, _window, _cx| {
+                    open_run_menu(index, menu_test.clone(), _window, _cx)
+                })
             })
             .child(
                 div()
                     .flex()
                     .flex_row()
                     .h_full()
+                    .items_center()
                     .child(
                         div()
                             .w(px(100.0))
@@ -46,7 +340,49 @@ impl Render for TestListItem {
                                 Status::Passed => "Passed",
                             }),
                     )
-                    .child(self.test_data.module_path.clone()),
+                    .child(
+                        div().flex_grow().child(
+                            self.label
+                                .clone()
+                                .unwrap_or(self.test_data.module_path.clone()),
+                        ),
+                    )
+                    .children(badges)
+                    .when_some(self.duration, |_self, duration| {
+                        _self.child(
+                            div()
+                                .flex_none()
+                                .ml(cx.style().margin.abs())
+                                .text_sm()
+                                .when_else(
+                                    slow,
+                                    |__self| __self.text_color(&cx.style().ignore_colour),
+                                    |__self| __self.text_color(&cx.style().muted_text_colour),
+                                )
+                                .child(format_duration(duration)),
+                        )
+                    })
+                    .when(runnable, |_self| {
+                        _self.child(
+                            IconButton::new(("run-test-button", index))
+                                .icon(Icons::Play)
+                                .justify_content(ContentPosition::Centre)
+                                .align_text(ContentPosition::Centre)
+                                .w(Size::Px(22.0))
+                                .h(Size::Px(22.0))
+                                .icon_size(Size::Px(14.0))
+                                .mx(cx.style().margin)
+                                .colour(Colour::Rgba(0x00000000))
+                                .hover_colour(Colour::Rgba(0xffffff22))
+                                .icon_colour(&cx.style().passed_colour)
+                                .rounding_all(cx.style().rounding)
+                                .tooltip("Re-run this test, right click for more options")
+                                .on_click(move |_e, _window, _cx| {
+                                    start_run(RunScope::Tests(vec![test.clone()]), _cx)
+                                })
+                                .render(window, cx),
+                        )
+                    }),
             )
             .child(
                 Divider::new()
@@ -1,41 +1,139 @@
+use crate::components::diff_list::DiffList;
+use crate::components::test_filter_bar::TestFilterBar;
 use crate::components::test_info::TestInfo;
 use crate::components::test_list::TestList;
 use crate::components::test_list_item::TestListItem;
-use crate::state::{Project, State, StateProvider};
+use crate::state::{State, StateProvider};
 use crate::style::{Colour, Size, StyleProvider};
+use crate::utils::history::format_age;
+use crate::utils::snapshot::TestChange;
+use crate::widgets::core::button::button::{Button, ContentPosition};
 use crate::widgets::core::divider::Divider;
 use crate::widgets::core::spinner::Spinner;
 use crate::widgets::styling::Direction;
-use cargo_ptest::parse::AggregateSummary;
+use cargo_ptest::parse::Summary;
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    AppContext, BorrowAppContext, Context, Element, InteractiveElement, IntoElement, MouseButton,
-    ParentElement, Render, RenderOnce, SharedString, StatefulInteractiveElement, Styled,
-    TextOverflow, UniformListScrollHandle, Window, div, percentage, px, rgb,
+    App, AppContext, BorrowAppContext, Context, Div, Element, InteractiveElement, IntoElement,
+    MouseButton, ParentElement, Render, RenderOnce, SharedString, StatefulInteractiveElement,
+    Styled, TextOverflow, UniformListScrollHandle, Window, div, percentage, px, rgb,
 };
 
 pub struct Tests {}
 
+/// A small outlined label, e.g. for the run being a partial re-run or how a test changed
+pub fn badge(label: impl Into<SharedString>, colour: &Colour, cx: &App) -> Div {
+    div()
+        .flex()
+        .items_center()
+        .h(px(20.0))
+        .px(px(6.0))
+        .rounded(cx.style().rounding.abs())
+        .border(px(1.0))
+        .border_color(colour)
+        .text_color(colour)
+        .text_xs()
+        .child(label.into())
+}
+
 impl Render for Tests {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let tests_option = cx
+        // the project is borrowed rather than cloned as cloning every test each frame is slow with large projects
+        let show_test = cx
             .state()
-            .get_active_project()
-            .unwrap_or(Project::default())
-            .tests_linear();
-        let show_test = tests_option.is_some();
+            .active_project()
+            .is_some_and(|x| x.tests.is_some());
+        let diff = cx.state().active_project().and_then(|x| x.diff.clone());
+        let showing_diff = diff.is_some();
         let position_side_by_side =
             <gpui::Pixels as Into<f32>>::into(window.viewport_size().width.into()) > 1000.0;
         let summary_line;
 
-        if show_test {
-            let summary = cx
-                .state()
-                .get_active_project()
-                .unwrap()
-                .tests
-                .unwrap()
-                .aggregate_summary();
+        if let Some(diff) = diff {
+            let mut counts = Vec::new();
+
+            for change in [
+                TestChange::NewlyFailing,
+                TestChange::NewlyPassing,
+                TestChange::NewlyIgnored,
+                TestChange::Added,
+                TestChange::Removed,
+                TestChange::Slower,
+                TestChange::Faster,
+            ] {
+                let count = diff.count(change);
+
+                if count > 0 {
+                    counts.push(
+                        div()
+                            .ml(cx.style().margin.abs())
+                            .text_color(match change {
+                                TestChange::NewlyFailing => &cx.style().failed_colour,
+                                TestChange::NewlyPassing => &cx.style().passed_colour,
+                                TestChange::NewlyIgnored => &cx.style().ignore_colour,
+                                _ => &cx.style().muted_text_colour,
+                            })
+                            .child(format!("{} {}", count, change.label())),
+                    )
+                }
+            }
+
+            summary_line = div()
+                .id("diff-summary")
+                .flex()
+                .flex_row()
+                .items_center()
+                .h(px(30.0))
+                .w_full()
+                .pl(cx.style().padding.abs())
+                .border_b(px(2.0))
+                .border_color(&cx.style().separator_colour)
+                .child(format!("Comparing {} to {}", diff.before, diff.after))
+                .child(
+                    Divider::new()
+                        .thickness(1.0)
+                        .colour(&cx.style().separator_colour)
+                        .direction(Direction::Vertical)
+                        .margin(5.0)
+                        .render(window, cx),
+                )
+                .children(counts)
+                .child(
+                    div().ml_auto().mr(cx.style().margin.abs()).child(
+                        Button::new("close-diff-button")
+                            .text("Close Diff")
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(90.0))
+                            .h(Size::Px(22.0))
+                            .colour(&cx.style().secondary_bg_colour)
+                            .hover_colour(Colour::Rgba(0xffffff22))
+                            .text_colour(&cx.style().text_colour)
+                            .text_size(Size::Px(13.0))
+                            .rounding_all(cx.style().rounding)
+                            .tooltip("Go back to the test list")
+                            .on_click(|_e, _window, _cx| {
+                                _cx.update_global::<State, ()>(|global, _| {
+                                    global.close_diff(global.active_project)
+                                });
+                                _window.refresh()
+                            })
+                            .render(window, cx),
+                    ),
+                );
+        } else if show_test {
+            let project = cx.state().active_project().unwrap();
+            let mut summary = Summary::default();
+            for group in project.tests.iter().flatten() {
+                if let Some(group_summary) = &group.summary {
+                    summary += group_summary.clone()
+                }
+            }
+            let partial_run = project.last_run.clone().filter(|x| x.is_partial());
+            let viewing_history = project.viewing_history;
+            let repeat = project.repeat.clone();
+            let flaky_count = project.flakiness.values().filter(|x| x.is_flaky()).count();
+            let flaky_only = project.flaky_only;
 
             summary_line = div()
                 .id("summary")
@@ -132,22 +230,100 @@ impl Render for Tests {
                         .text_color(&cx.style().doctest_colour)
                         .child(summary.time.to_string()),
                 )
+                // pushes the badges to the right hand side
+                .child(div().flex_grow())
+                .when_some(partial_run, |_self, scope| {
+                    _self.child(
+                        badge(
+                            format!("Partial re-run of {}", scope.description()),
+                            &cx.style().muted_text_colour,
+                            cx,
+                        )
+                        .mr(cx.style().margin.abs()),
+                    )
+                })
+                .when_some(viewing_history, |_self, timestamp| {
+                    _self.child(
+                        badge(
+                            format!("History: run from {}", format_age(timestamp)),
+                            &cx.style().muted_text_colour,
+                            cx,
+                        )
+                        .mr(cx.style().margin.abs()),
+                    )
+                })
+                .when_some(repeat, |_self, repeat| {
+                    _self.child(
+                        badge(
+                            format!(
+                                "Repeated run {} of {}, {} inconsistent",
+                                repeat.completed,
+                                repeat.total,
+                                repeat.inconsistent()
+                            ),
+                            &cx.style().muted_text_colour,
+                            cx,
+                        )
+                        .mr(cx.style().margin.abs()),
+                    )
+                })
+                .when(flaky_count > 0 || flaky_only, |_self| {
+                    _self.child(
+                        div().my(px(4.0)).mr(cx.style().margin.abs()).child(
+                            Button::new("flaky-only-button")
+                                .text(format!("Flaky only ({})", flaky_count))
+                                .justify_content(ContentPosition::Centre)
+                                .align_text(ContentPosition::Centre)
+                                .w(Size::Px(120.0))
+                                .h(Size::Px(22.0))
+                                .colour(if flaky_only {
+                                    &cx.style().primary_colour
+                                } else {
+                                    &cx.style().secondary_bg_colour
+                                })
+                                .hover_colour(&cx.style().hover_primary_colour)
+                                .text_colour(&cx.style().text_colour)
+                                .text_size(Size::Px(13.0))
+                                .rounding_all(cx.style().rounding)
+                                .tooltip("Only show tests whose results changed without the code changing")
+                                .on_click(|_e, _window, _cx| {
+                                    _cx.update_global::<State, ()>(|global, _| {
+                                        global.toggle_flaky_only(global.active_project)
+                                    });
+                                    _window.refresh()
+                                })
+                                .render(window, cx),
+                        ),
+                    )
+                })
         } else {
             summary_line = div().id("empty-summary");
         }
 
-        let test_list_viewport = 600.0;
-
         let test_list = div()
-            .id("test-list-parent")
-            .relative()
+            .flex()
+            .flex_col()
             .when_else(
                 position_side_by_side,
-                |_self| _self.max_w_1_3().h_full(),
-                |_self| _self.w_full()./*h_auto().max_*/h(px(test_list_viewport)),
+                |_self| _self.w_1_3().h_full(),
+                |_self| _self.w_full().h_1_2(),
             )
-            .overflow_y_scroll()
-            .child(TestList { test_list_viewport }.render(window, cx));
+            .when(showing_diff, |_self| {
+                _self.child(
+                    div()
+                        .id("test-list-parent")
+                        .w_full()
+                        .flex_grow()
+                        .overflow_y_scroll()
+                        .child(DiffList {}.render(window, cx)),
+                )
+            })
+            .when(!showing_diff, |_self| {
+                // this is needed over a when_else so both closures don't borrow cx
+                _self
+                    .child(TestFilterBar {}.render(window, cx))
+                    .child(TestList {}.render(window, cx))
+            });
 
         let test_info = div()
             .flex()
@@ -186,10 +362,10 @@ impl Render for Tests {
             .flex_col()
             .h_full()
             .w_full()
-            .when(show_test, |_self| {
+            .when(show_test || showing_diff, |_self| {
                 _self.child(summary_line).child(tests_display)
             })
-            .when(!show_test, |_self| {
+            .when(!show_test && !showing_diff, |_self| {
                 // this is needed over a when_else so both closures don't borrow cx
                 _self.child(
                     div()
@@ -1,3 +1,6 @@
+use crate::components::control_bar::refresh_flakiness;
+use crate::components::settings::open_settings;
+use crate::components::shortcuts::open_shortcuts;
 use crate::state::{Project, State, StateProvider};
 use crate::style::{Style, StyleProvider};
 use crate::widgets::styling::{Colour, Size};
@@ -9,7 +12,7 @@ use crate::widgets::core::modal::{Modal, ModalButtonOptions};
 use crate::{AlertHandler, AsyncAlertHandler, ModalHelper};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    AppContext, AsyncApp, Context, Div, DragMoveEvent, InteractiveElement, IntoElement,
+    App, AppContext, AsyncApp, Context, Div, DragMoveEvent, InteractiveElement, IntoElement,
     MouseButton, MouseDownEvent, ParentElement, PathPromptOptions, Render, Styled, Window, div, px,
     rgba,
 };
@@ -19,6 +22,62 @@ use zed_util::ResultExt;
 
 const BUTTON_HOVER_COLOUR: u32 = 0xffffff22;
 
+/// Asks for the folders to open as projects and adds them to the open projects
+pub fn open_project(cx: &mut App) {
+    let options = PathPromptOptions {
+        files: false,
+        directories: true,
+        multiple: true,
+        prompt: None,
+    };
+
+    let rec = cx.prompt_for_paths(options);
+
+    cx.spawn(
+        async move |__cx| match rec.await.anyhow().and_then(|res| res) {
+            Ok(res) => {
+                match res {
+                    Some(path) => {
+                        __cx.update(|___cx| {
+                            let res = ___cx.has_global::<State>();
+
+                            if res {
+                                let _ = ___cx.update_global::<State, ()>(|global, _| {
+                                    path.iter()
+                                        .for_each(|x| global.add_project_by_path(x.clone()));
+                                });
+                                // the newly opened projects won't have their history loaded yet
+                                for project in ___cx.state().open_projects.clone() {
+                                    if project.flakiness.is_empty() {
+                                        refresh_flakiness(project.id, ___cx)
+                                    }
+                                }
+                            } else {
+                                println!("No global state set")
+                            }
+                        })
+                        .ok();
+                    }
+                    None => {
+                        __cx.alert_error(None::<String>, "Could not open this path", Some(5000));
+                        println!("No path was found")
+                    }
+                }
+            }
+            Err(err) => {
+                __cx.alert_error(
+                    None::<String>,
+                    "An error occurred when opening new folder",
+                    Some(5000),
+                );
+                println!("The following error occurred when opening new folder");
+                println!("{}", err)
+            }
+        },
+    )
+    .detach();
+}
+
 pub struct ToolBar {}
 // cx.style().toolbar.bg_colour.get()
 impl Render for ToolBar {
@@ -39,67 +98,21 @@ impl Render for ToolBar {
                     .items_center()
                     .h_full()
                     .bg(&cx.style().secondary_bg_colour)
-                    .child(div()
-                        .text_xl()
-                        .px(px(10.0))
-                        .child("Apollo".to_string()))
-                    .child(Button::new("open-projects-button")
-                        .text(String::from("Open Project"))
-                        .text_colour(&cx.style().text_colour)
-                        .justify_content(ContentPosition::Centre)
-                        .align_text(ContentPosition::Centre)
-                        .w(Size::Px(100.0))
-                        .h(cx.style().toolbar.button_height)
-                        .mx(cx.style().margin)
-                        .colour(&cx.style().secondary_bg_colour)
-                        .hover_colour(Colour::Rgba(BUTTON_HOVER_COLOUR))
-                        .rounding_all(cx.style().rounding)
-                        .on_click(|_e, _window, _cx| {
-                            // Everything inside is owned/moved
-                            let options = PathPromptOptions {
-                                files: false,
-                                directories: true,
-                                multiple: true,
-                                prompt: None
-                            };
-
-                            let rec = _cx.prompt_for_paths(options);
-
-                            _cx.spawn(
-                                async move |__cx| match rec.await.anyhow().and_then(|res| res) {
-                                    Ok(res) => {
-                                        match res {
-                                            Some(path) => {
-                                                __cx.update(|___cx| {
-                                                    let res = ___cx.has_global::<State>();
-
-                                                    if res {
-                                                        let _ = ___cx.update_global::<State, ()>(|global, _| {
-                                                            path.iter().for_each(|x|global.add_project_by_path(x.clone()));
-                                                        });
-                                                    } else {
-                                                        println!("No global state set")
-                                                    }
-
-                                                })
-                                                    .ok();
-                                            },
-                                            None => {
-                                                __cx.alert_error(None::<String>, "Could not open this path", Some(5000));
-                                                println!("No path was found")
-                                            }
-                                        }
-
-                                    }
-                                    Err(err) => {
-                                        __cx.alert_error(None::<String>, "An error occurred when opening new folder", Some(5000));
-                                        println!("The following error occurred when opening new folder");
-                                        println!("{}", err)
-                                    }
-                                },
-                            )
-                                .detach();
-                        }).render(window, cx)
+                    .child(div().text_xl().px(px(10.0)).child("Apollo".to_string()))
+                    .child(
+                        Button::new("open-projects-button")
+                            .text(String::from("Open Project"))
+                            .text_colour(&cx.style().text_colour)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(100.0))
+                            .h(cx.style().toolbar.button_height)
+                            .mx(cx.style().margin)
+                            .colour(&cx.style().secondary_bg_colour)
+                            .hover_colour(Colour::Rgba(BUTTON_HOVER_COLOUR))
+                            .rounding_all(cx.style().rounding)
+                            .on_click(|_e, _window, _cx| open_project(_cx))
+                            .render(window, cx),
                     )
                     .child(Button::new("about-button")
                         .text(String::from("About"))
@@ -142,6 +155,36 @@ impl Render for ToolBar {
                             )
                         }).render(window, cx)
                     )
+                    .child(
+                        Button::new("shortcuts-button")
+                            .text(String::from("Shortcuts"))
+                            .text_colour(&cx.style().text_colour)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(80f32))
+                            .h(cx.style().toolbar.button_height)
+                            .mx(cx.style().margin)
+                            .colour(&cx.style().secondary_bg_colour)
+                            .hover_colour(Colour::Rgba(BUTTON_HOVER_COLOUR))
+                            .rounding_all(cx.style().rounding)
+                            .on_click(|_e, _window, _cx| open_shortcuts(_window, _cx))
+                            .render(window, cx),
+                    )
+                    .child(
+                        Button::new("settings-button")
+                            .text(String::from("Settings"))
+                            .text_colour(&cx.style().text_colour)
+                            .justify_content(ContentPosition::Centre)
+                            .align_text(ContentPosition::Centre)
+                            .w(Size::Px(70f32))
+                            .h(cx.style().toolbar.button_height)
+                            .mx(cx.style().margin)
+                            .colour(&cx.style().secondary_bg_colour)
+                            .hover_colour(Colour::Rgba(BUTTON_HOVER_COLOUR))
+                            .rounding_all(cx.style().rounding)
+                            .on_click(|_e, _window, _cx| open_settings(_window, _cx))
+                            .render(window, cx),
+                    )
                     .child(Button::new("test-button")
                         .text(String::from("test"))
                         .text_colour(&cx.style().text_colour)
@@ -1,3 +1,4 @@
+mod actions;
 mod components;
 mod events;
 mod state;
@@ -5,12 +6,24 @@ mod style;
 mod utils;
 mod widgets;
 
+use crate::actions::{
+    CancelRun, CloseTab, CommandPalette, FocusSearch, NextTab, OpenProject, PreviousTab,
+    RerunFailed, RunTests, SelectNextFailure, SelectNextTest, SelectPreviousTest, ShowShortcuts,
+    bind_shortcuts, cancel_run, close_active_tab, focus_search, next_tab, open_project_prompt,
+    previous_tab, rerun_failed_tests, run_tests, select_next_failure, select_next_test,
+    select_previous_test,
+};
 use crate::components::alert::AlertDisplay;
+use crate::components::command_palette::open_command_palette;
+use crate::components::control_bar::refresh_flakiness;
+use crate::components::shortcuts::open_shortcuts;
 use crate::components::status_bar::StatusBar;
 use crate::components::test_list::TestList;
 use crate::components::toolbar::ToolBar;
 use crate::components::workspace::Workspace;
-use crate::state::{Alert, AlertSeverity, AlertType, ScrollHandles, State, StateProvider};
+use crate::state::{
+    Alert, AlertSeverity, AlertType, SourceViewState, State, StateProvider, TestListState, Watchers,
+};
 use crate::style::{GlobalStyle, Style, StyleProvider};
 use crate::utils::assets::Assets;
 use crate::utils::file::{load_state, save_state};
@@ -19,11 +32,10 @@ use crate::widgets::core::modal::Modal;
 use cargo_ptest::config::Config;
 use cargo_ptest::run::run;
 use gpui::{
-    App, Application, AsyncApp, Bounds, Context, SharedString, Task, TitlebarOptions, Window,
-    WindowBounds, WindowOptions, anchored, div, prelude::*, px, size,
+    App, Application, AsyncApp, Bounds, Context, FocusHandle, SharedString, Task, TitlebarOptions,
+    Window, WindowBounds, WindowOptions, anchored, div, prelude::*, px, size,
 };
 use std::env;
-use std::env::set_current_dir;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -402,11 +414,41 @@ pub struct ModalBuilder {
 
 struct Base {
     modals: Vec<ModalBuilder>,
+    focus_handle: FocusHandle,
 }
 
 impl Render for Base {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        // actions are dispatched from the focused element so the base is focused whenever nothing else is
+        if window.focused(cx).is_none() {
+            window.focus(&self.focus_handle)
+        }
+
         div()
+            .track_focus(&self.focus_handle)
+            // shortcuts are ignored while a modal is open so they don't change what is behind it
+            .when(self.modals.is_empty(), |_self| {
+                _self
+                    .on_action(|_: &RunTests, _window, _cx| run_tests(_window, _cx))
+                    .on_action(|_: &RerunFailed, _window, _cx| rerun_failed_tests(_window, _cx))
+                    .on_action(|_: &CancelRun, _window, _cx| cancel_run(_window, _cx))
+                    .on_action(|_: &SelectNextTest, _window, _cx| select_next_test(_window, _cx))
+                    .on_action(|_: &SelectPreviousTest, _window, _cx| {
+                        select_previous_test(_window, _cx)
+                    })
+                    .on_action(|_: &SelectNextFailure, _window, _cx| {
+                        select_next_failure(_window, _cx)
+                    })
+                    .on_action(|_: &NextTab, _window, _cx| next_tab(_window, _cx))
+                    .on_action(|_: &PreviousTab, _window, _cx| previous_tab(_window, _cx))
+                    .on_action(|_: &CloseTab, _window, _cx| close_active_tab(_window, _cx))
+                    .on_action(|_: &OpenProject, _window, _cx| open_project_prompt(_window, _cx))
+                    .on_action(|_: &FocusSearch, _window, _cx| focus_search(_window, _cx))
+                    .on_action(|_: &ShowShortcuts, _window, _cx| open_shortcuts(_window, _cx))
+                    .on_action(|_: &CommandPalette, _window, _cx| {
+                        open_command_palette(_window, _cx)
+                    })
+            })
             .flex()
             .flex_col()
             .size_full()
@@ -478,7 +520,16 @@ fn main() {
             state.csd = csd;
             cx.set_global(state);
             cx.set_global(GlobalStyle(Arc::new(Style::default())));
-            cx.set_global(ScrollHandles { test_list: 0.0 });
+            cx.set_global(Watchers::default());
+            cx.set_global(TestListState::default());
+            cx.set_global(SourceViewState::default());
+
+            let keymap = cx.state().keymap.clone();
+            bind_shortcuts(&keymap, cx);
+
+            for project in cx.state().open_projects.clone() {
+                refresh_flakiness(project.id, cx)
+            }
 
             let _ = cx
                 .on_app_quit(|_cx| {
@@ -489,7 +540,10 @@ fn main() {
                 .detach();
 
             cx.open_window(window_options, |_, cx| {
-                cx.new(|_cx| Base { modals: Vec::new() })
+                cx.new(|_cx| Base {
+                    modals: Vec::new(),
+                    focus_handle: _cx.focus_handle(),
+                })
             })
             .unwrap();
 
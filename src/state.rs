@@ -1,13 +1,83 @@
 use crate::display_vec;
 use crate::utils::logger::warning;
-use cargo_ptest::parse::{ParsedTest, ParsedTestGroup};
-use gpui::{App, Global};
+use crate::utils::filter::TestFilter;
+use crate::utils::editor::EDITOR_PRESETS;
+use crate::utils::flaky::{Flakiness, RepeatRun};
+use crate::utils::history::HistoryEntry;
+use crate::utils::snapshot::RunDiff;
+use crate::utils::source::{SourceFile, find_test_line, resolve_file, test_source_file};
+use crate::utils::test_tree::{
+    TestRow, TestSort, test_file, test_files, test_rows, test_tree,
+};
+use crate::utils::runner::{RunEvent, RunHandle, RunScope};
+use cargo_ptest::parse::{
+    GeneralTestType, ParsedTest, ParsedTestGroup, Status as TestStatus, Summary,
+};
+use gpui::{App, FocusHandle, Global, Task, UniformListScrollHandle};
+use notify::RecommendedWatcher;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
 // PROJECT
 
+/// Splits a doc test's module path into the item it documents and the line its code block is on, e.g. "foo::bar (line 5)"
+pub fn split_doc_test_path(module_path: &str) -> (&str, Option<usize>) {
+    let line = module_path
+        .rfind("(line ")
+        .filter(|_| module_path.ends_with(')'))
+        .and_then(|start| {
+            let line = module_path[start + 6..module_path.len() - 1].parse().ok()?;
+            Some((module_path[..start].trim_end(), line))
+        });
+
+    match line {
+        Some((item, line)) => (item, Some(line)),
+        None => (module_path, None),
+    }
+}
+
+/// The module path cargo_ptest would give a doc test with the line cargo test printed added on, e.g. "foo::bar (line 5)"
+pub fn doc_test_path(item: &str, line: usize) -> String {
+    format!("{} (line {})", item, line).trim_start().to_string()
+}
+
+/// A unique identifier for a test, doc tests don't have a unique module path so their file is included.
+/// For doc tests this is the name cargo test prints for them, e.g. "src/lib.rs - foo (line 5)".
+pub fn test_id(test: &ParsedTest) -> String {
+    match test.test_type {
+        GeneralTestType::Normal => test.module_path.clone(),
+        GeneralTestType::Doc => format!(
+            "{} - {}",
+            test.file_path.clone().unwrap_or_default(),
+            test.module_path
+        ),
+    }
+}
+
+/// Recounts the passed, failed and ignored tests in a group after tests have been merged into it
+fn recount_summary(group: &mut ParsedTestGroup) {
+    let mut summary = group.summary.clone().unwrap_or_default();
+    summary.passed = 0;
+    summary.failed = 0;
+    summary.ignored = 0;
+
+    for test in group.tests.iter() {
+        summary += test.status.clone()
+    }
+
+    summary.status = if summary.failed > 0 {
+        TestStatus::Failed
+    } else {
+        TestStatus::Passed
+    };
+
+    group.summary = Some(summary)
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 /// Stores data about open projects
 pub struct Project {
@@ -18,6 +88,69 @@ pub struct Project {
     #[serde(skip_serializing, skip_deserializing)]
     /// The index of the selected tests from the tests_linear() function
     pub selected_test: Option<usize>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// The tests included in the most recent run
+    pub last_run: Option<RunScope>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Whether the tests are re-run when the project's files change
+    pub watching: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Whether files changed while tests were running, in which case the tests are run again once the run finishes
+    pub watch_queued: bool,
+    #[serde(default)]
+    /// Args passed to cargo test when running this project's tests
+    pub run_args: RunArgs,
+    #[serde(default)]
+    /// The name of the run profile used instead of run_args, if None then run_args is used
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    /// Environment variables and working directory used when running this project's tests
+    pub environment: RunEnvironment,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Whether this project's tests are being run
+    pub running: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Whether the last run was stopped before it finished
+    pub cancelled: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Handle for the test run in progress, used to cancel it
+    pub run_handle: Option<RunHandle>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// When the run being shown from the history finished, None if the tests are from the latest run
+    pub viewing_history: Option<u64>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// The comparison shown in place of the test list, None if no diff is open
+    pub diff: Option<RunDiff>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// How long each test took in seconds the last time it was run, keyed by test_id()
+    pub durations: HashMap<String, f64>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// What each test printed the last time it was run, keyed by test_id()
+    pub outputs: HashMap<String, String>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// How flaky each test has been across the project's run history, keyed by test_id()
+    pub flakiness: HashMap<String, Flakiness>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// The results of the tests being run several times in a row, None if the last run wasn't repeated
+    pub repeat: Option<RepeatRun>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Whether the test list only shows tests that have been flaky
+    pub flaky_only: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// The keys of the test tree nodes that are collapsed
+    pub collapsed: HashSet<String>,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// The search and filters narrowing down the test list
+    pub filter: TestFilter,
+    #[serde(default)]
+    /// The order the test list is shown in
+    pub sort: TestSort,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// Goes up whenever the tests or their results change, used to know when the test list has to be rebuilt
+    pub revision: u64,
+    #[serde(skip_serializing, skip_deserializing)]
+    /// When each test's status last changed across the project's run history, in seconds since the unix epoch keyed by test_id()
+    pub status_changes: HashMap<String, u64>,
 }
 
 impl Project {
@@ -27,6 +160,27 @@ impl Project {
             path,
             tests: None,
             selected_test: None,
+            last_run: None,
+            watching: false,
+            watch_queued: false,
+            run_args: RunArgs::default(),
+            active_profile: None,
+            environment: RunEnvironment::default(),
+            running: false,
+            cancelled: false,
+            run_handle: None,
+            viewing_history: None,
+            diff: None,
+            durations: HashMap::new(),
+            outputs: HashMap::new(),
+            flakiness: HashMap::new(),
+            repeat: None,
+            flaky_only: false,
+            collapsed: HashSet::new(),
+            filter: TestFilter::default(),
+            sort: TestSort::default(),
+            status_changes: HashMap::new(),
+            revision: 0,
         }
     }
 
@@ -73,6 +227,123 @@ impl Project {
 
         Some(tests)
     }
+
+    /// Gets every failed test from tests_linear()
+    pub fn failed_tests(&self) -> Vec<ParsedTest> {
+        self.tests_linear()
+            .unwrap_or(Vec::new())
+            .into_iter()
+            .filter(|x| x.status == TestStatus::Failed)
+            .collect()
+    }
+
+    /// Whether the tests shown are from a run that only included some of the tests
+    pub fn is_partial_run(&self) -> bool {
+        self.last_run.as_ref().is_some_and(|x| x.is_partial())
+    }
+
+    /// Gets the group containing the test at the given index of tests_linear()
+    pub fn group_of(&self, index: usize) -> Option<ParsedTestGroup> {
+        let mut offset = 0;
+
+        for group in self.tests.as_ref()? {
+            if index < offset + group.tests.len() {
+                return Some(group.clone());
+            }
+            offset += group.tests.len();
+        }
+
+        None
+    }
+
+    /// The id of the selected test, it's used to find the test again after the tests have been moved around
+    fn selected_test_id(&self) -> Option<String> {
+        let index = self.selected_test?;
+
+        self.tests
+            .as_ref()?
+            .iter()
+            .flat_map(|x| x.tests.iter())
+            .nth(index)
+            .map(test_id)
+    }
+
+    /// Runs an update that can change the indexes of the tests, the selected test stays selected if it's still there
+    fn keep_selection(&mut self, update: impl FnOnce(&mut Project)) {
+        let selected = self.selected_test_id();
+
+        update(self);
+        self.revision += 1;
+
+        self.selected_test = selected.and_then(|id| {
+            self.tests
+                .as_ref()?
+                .iter()
+                .flat_map(|x| x.tests.iter())
+                .position(|x| test_id(x) == id)
+        })
+    }
+
+    /// Replaces an existing test with the same id, or adds it to the last group if it's a new test
+    fn merge_test(&mut self, test: ParsedTest) {
+        let groups = self.tests.get_or_insert(Vec::new());
+        let id = test_id(&test);
+
+        for group in groups.iter_mut() {
+            if let Some(existing) = group.tests.iter_mut().find(|x| test_id(x) == id) {
+                *existing = test;
+                recount_summary(group);
+                return;
+            }
+        }
+
+        match groups.last_mut() {
+            Some(group) => {
+                group.tests.push(test);
+                recount_summary(group)
+            }
+            None => groups.push(ParsedTestGroup {
+                crate_name: String::new(),
+                file_path: Vec::new(),
+                tests: vec![test],
+                summary: Some(Summary::default()),
+            }),
+        }
+    }
+
+    /// Merges the results of a partial run into the existing tests, tests that weren't run are left untouched
+    fn merge_groups(&mut self, new_groups: Vec<ParsedTestGroup>) {
+        let groups = self.tests.get_or_insert(Vec::new());
+        let mut unmatched = Vec::new();
+
+        for new_group in new_groups {
+            // a filtered run still reports the test binaries that had no matching tests
+            if new_group.tests.is_empty() {
+                continue;
+            }
+
+            match groups.iter_mut().find(|x| {
+                x.crate_name == new_group.crate_name && x.file_path == new_group.file_path
+            }) {
+                Some(group) => {
+                    for test in new_group.tests {
+                        let id = test_id(&test);
+                        match group.tests.iter_mut().find(|x| test_id(x) == id) {
+                            Some(existing) => *existing = test,
+                            None => group.tests.push(test),
+                        }
+                    }
+                    recount_summary(group)
+                }
+                // the tests might have been streamed into a group that didn't know its crate name yet
+                None => unmatched.extend(new_group.tests),
+            }
+        }
+
+        for test in unmatched {
+            self.merge_test(test)
+        }
+    }
 }
 
 impl Default for Project {
@@ -82,6 +353,27 @@ impl Default for Project {
             path: PathBuf::new(),
             tests: None,
             selected_test: None,
+            last_run: None,
+            watching: false,
+            watch_queued: false,
+            run_args: RunArgs::default(),
+            active_profile: None,
+            environment: RunEnvironment::default(),
+            running: false,
+            cancelled: false,
+            run_handle: None,
+            viewing_history: None,
+            diff: None,
+            durations: HashMap::new(),
+            outputs: HashMap::new(),
+            flakiness: HashMap::new(),
+            repeat: None,
+            flaky_only: false,
+            collapsed: HashSet::new(),
+            filter: TestFilter::default(),
+            sort: TestSort::default(),
+            status_changes: HashMap::new(),
+            revision: 0,
         }
     }
 }
@@ -104,22 +396,6 @@ impl Display for Project {
     }
 }
 
-// STATUS
-
-#[derive(Clone, Serialize)]
-/// Stores information for what to display on the status bar
-pub struct Status {
-    pub running_tests: bool,
-}
-
-impl Default for Status {
-    fn default() -> Self {
-        Self {
-            running_tests: false,
-        }
-    }
-}
-
 // ALERT
 
 #[derive(Clone)]
@@ -146,61 +422,394 @@ pub struct Alert {
 
 // RUN ARGS
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// The options passed to cargo test, list options are stored as they are typed and split on commas or spaces
 pub struct RunArgs {
+    /// --lib
     pub lib: bool,
-    pub bin: bool,
-    pub docs: bool,
+    /// --bins, runs the tests of every binary target
+    pub bins: bool,
+    /// --doc
+    pub doc: bool,
+    /// --workspace
     pub workspace: bool,
+    /// --no-fail-fast
     pub no_fail_fast: bool,
+    /// --package, one per name
+    pub package: String,
+    /// --exclude, one per name, only valid with --workspace
+    pub exclude: String,
+    /// --features
+    pub features: String,
+    /// --all-features
+    pub all_features: bool,
+    /// --no-default-features
+    pub no_default_features: bool,
+    /// --release
+    pub release: bool,
+    /// --profile
+    pub profile: String,
+    /// --target
+    pub target: String,
+    /// --bin, one per name
+    pub bin_targets: String,
+    /// --test, one per name
+    pub test_targets: String,
+    /// --bench, one per name
+    pub bench_targets: String,
+    /// --example, one per name
+    pub example_targets: String,
+    /// --jobs
+    pub jobs: String,
+    /// --test-threads, passed to the test binaries
+    pub test_threads: String,
+    /// --include-ignored, passed to the test binaries
+    pub include_ignored: bool,
+    /// --nocapture, passed to the test binaries
+    pub nocapture: bool,
+    /// --show-output, passed to the test binaries so the output of passing tests is printed too
+    pub show_output: bool,
+    /// --report-time, passed to the test binaries. This is an unstable libtest option so RUSTC_BOOTSTRAP is set when it is on
+    pub report_time: bool,
+    /// Sets RUST_BACKTRACE=1 so failing tests print a backtrace
+    pub backtrace: bool,
+    /// Sets RUST_BACKTRACE=full, which includes every frame instead of starting at the panic
+    pub full_backtrace: bool,
 }
 
 impl Default for RunArgs {
     fn default() -> Self {
         Self {
             lib: false,
-            bin: false,
-            docs: false,
+            bins: false,
+            doc: false,
             workspace: true,
             no_fail_fast: true,
+            package: String::new(),
+            exclude: String::new(),
+            features: String::new(),
+            all_features: false,
+            no_default_features: false,
+            release: false,
+            profile: String::new(),
+            target: String::new(),
+            bin_targets: String::new(),
+            test_targets: String::new(),
+            bench_targets: String::new(),
+            example_targets: String::new(),
+            jobs: String::new(),
+            test_threads: String::new(),
+            include_ignored: false,
+            nocapture: false,
+            show_output: false,
+            report_time: false,
+            backtrace: false,
+            full_backtrace: false,
+        }
+    }
+}
+
+/// Splits a list option into its values, values can be separated by commas or spaces
+fn split_list(list: &str) -> Vec<String> {
+    list.split(|x: char| x == ',' || x.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+        .collect()
+}
+
+/// Checks that a number option is empty or a positive whole number
+fn validate_count(name: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        return Ok(());
+    }
+
+    match value.trim().parse::<u32>() {
+        Ok(res) if res > 0 => Ok(()),
+        _ => Err(format!("{} must be a whole number greater than 0", name)),
+    }
+}
+
+/// Checks that every value in a list option only contains characters cargo accepts in names
+fn validate_names(name: &str, list: &str, extra_chars: &[char]) -> Result<(), String> {
+    for value in split_list(list) {
+        if !value
+            .chars()
+            .all(|x| x.is_alphanumeric() || x == '-' || x == '_' || extra_chars.contains(&x))
+        {
+            return Err(format!("'{}' is not a valid {}", value, name));
         }
     }
+
+    Ok(())
 }
 
 impl RunArgs {
-    pub fn default_vec() -> Vec<String> {
-        vec!["--no-fail-fast".to_string(), "--workspace".to_string()]
+    /// The args with no targets picked, for runs that pick their own target
+    pub fn without_targets(self) -> Self {
+        Self {
+            lib: false,
+            bins: false,
+            doc: false,
+            bin_targets: String::new(),
+            test_targets: String::new(),
+            bench_targets: String::new(),
+            example_targets: String::new(),
+            ..self
+        }
+    }
+    /// Checks the args for values cargo would reject, this should be called before the args are used
+    pub fn validate(&self) -> Result<(), String> {
+        validate_names("package", &self.package, &['@', '.', ':'])?;
+        validate_names("excluded package", &self.exclude, &['@', '.', ':', '*'])?;
+        validate_names("feature", &self.features, &['/'])?;
+        validate_names("binary", &self.bin_targets, &[])?;
+        validate_names("test target", &self.test_targets, &[])?;
+        validate_names("bench target", &self.bench_targets, &[])?;
+        validate_names("example", &self.example_targets, &[])?;
+        validate_names("profile", &self.profile, &[])?;
+        validate_names("target triple", &self.target, &['.'])?;
+        validate_count("Jobs", &self.jobs)?;
+        validate_count("Test threads", &self.test_threads)?;
+
+        if split_list(&self.profile).len() > 1 {
+            return Err(String::from("Only one profile can be used"));
+        }
+
+        if self.release && !self.profile.trim().is_empty() {
+            return Err(String::from("Release and a profile can't both be set"));
+        }
+
+        if !self.exclude.trim().is_empty() && !self.workspace {
+            return Err(String::from(
+                "Excluding packages requires workspace to be enabled",
+            ));
+        }
+
+        Ok(())
     }
 }
 
-impl Into<Vec<String>> for RunArgs {
-    fn into(self) -> Vec<String> {
+impl From<RunArgs> for Vec<String> {
+    fn from(run_args: RunArgs) -> Self {
         let mut args = Vec::new();
+        let mut list_arg = |flag: &str, list: &str| {
+            for value in split_list(list) {
+                args.push(String::from(flag));
+                args.push(value)
+            }
+        };
+
+        list_arg("--package", &run_args.package);
+        list_arg("--exclude", &run_args.exclude);
+        list_arg("--bin", &run_args.bin_targets);
+        list_arg("--test", &run_args.test_targets);
+        list_arg("--bench", &run_args.bench_targets);
+        list_arg("--example", &run_args.example_targets);
+        list_arg("--profile", &run_args.profile);
+        list_arg("--target", &run_args.target);
+        list_arg("--jobs", &run_args.jobs);
+
+        let features = split_list(&run_args.features);
+        if !features.is_empty() {
+            args.push(String::from("--features"));
+            args.push(features.join(","))
+        }
 
-        if self.lib {
-            args.push(String::from("--lib"))
+        for (enabled, flag) in [
+            (run_args.lib, "--lib"),
+            (run_args.bins, "--bins"),
+            (run_args.doc, "--doc"),
+            (run_args.workspace, "--workspace"),
+            (run_args.no_fail_fast, "--no-fail-fast"),
+            (run_args.all_features, "--all-features"),
+            (run_args.no_default_features, "--no-default-features"),
+            (run_args.release, "--release"),
+        ] {
+            if enabled {
+                args.push(String::from(flag))
+            }
         }
 
-        if self.bin {
-            args.push(String::from("--bin"))
+        // everything after this is passed to the test binaries rather than cargo
+        let mut binary_args = Vec::new();
+
+        if !run_args.test_threads.trim().is_empty() {
+            binary_args.push(format!("--test-threads={}", run_args.test_threads.trim()))
         }
 
-        if self.docs {
-            args.push(String::from("--docs"))
+        if run_args.include_ignored {
+            binary_args.push(String::from("--include-ignored"))
         }
 
-        if self.workspace {
-            args.push(String::from("--workspace"))
+        if run_args.nocapture {
+            binary_args.push(String::from("--nocapture"))
         }
 
-        if self.no_fail_fast {
-            args.push(String::from("--no-fail-fast"))
+        if run_args.show_output {
+            binary_args.push(String::from("--show-output"))
+        }
+
+        if run_args.report_time {
+            binary_args.push(String::from("-Zunstable-options"));
+            binary_args.push(String::from("--report-time"))
+        }
+
+        if !binary_args.is_empty() {
+            args.push(String::from("--"));
+            args.extend(binary_args)
         }
 
         args
     }
 }
 
+// RUN ENVIRONMENT
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// The environment cargo test is run in
+pub struct RunEnvironment {
+    /// Environment variables set for cargo test and the test binaries, e.g. RUST_BACKTRACE or RUST_LOG
+    pub env: Vec<EnvVar>,
+    /// The folder cargo test is run in, relative to the project or absolute, if empty the project folder is used
+    pub working_dir: String,
+}
+
+impl RunEnvironment {
+    /// Checks the environment for values that can't be used, this should be called before the environment is used
+    pub fn validate(&self) -> Result<(), String> {
+        for var in self.env.iter() {
+            if var.key.trim().is_empty() {
+                if var.value.is_empty() {
+                    continue;
+                }
+                return Err(format!(
+                    "The environment variable set to '{}' needs a name",
+                    var.value
+                ));
+            }
+
+            if var.key.contains('=') || var.key.contains(char::is_whitespace) {
+                return Err(format!(
+                    "'{}' is not a valid environment variable name",
+                    var.key
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Combines two environments, variables and the working directory in overrides replace the ones in self
+    pub fn with_overrides(&self, overrides: &RunEnvironment) -> RunEnvironment {
+        let mut env = self.env.clone();
+
+        for var in overrides.env.iter() {
+            match env.iter_mut().find(|x| x.key == var.key) {
+                Some(existing) => existing.value = var.value.clone(),
+                None => env.push(var.clone()),
+            }
+        }
+
+        RunEnvironment {
+            env,
+            working_dir: if overrides.working_dir.trim().is_empty() {
+                self.working_dir.clone()
+            } else {
+                overrides.working_dir.clone()
+            },
+        }
+    }
+
+    /// The variables to set, skipping any rows that haven't been filled in
+    pub fn vars(&self) -> Vec<(String, String)> {
+        self.env
+            .iter()
+            .filter(|x| !x.key.trim().is_empty())
+            .map(|x| (x.key.trim().to_string(), x.value.clone()))
+            .collect()
+    }
+
+    /// The folder to run cargo test in for a project at the given path
+    pub fn resolve_working_dir(&self, project_path: &Path) -> PathBuf {
+        let working_dir = self.working_dir.trim();
+
+        if working_dir.is_empty() {
+            project_path.to_path_buf()
+        } else {
+            // join keeps absolute paths as they are
+            project_path.join(working_dir)
+        }
+    }
+}
+
+// RUN PROFILES
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// A named set of run settings for a project, used instead of the project's own run args when it is selected
+pub struct RunProfile {
+    pub name: String,
+    pub run_args: RunArgs,
+    /// Only tests with names containing this are run, ignored when only some of the tests are re-run
+    pub filter: String,
+    /// Added on top of the project's environment, anything set here replaces the project's value
+    pub environment: RunEnvironment,
+}
+
+impl Default for RunProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            run_args: RunArgs::default(),
+            filter: String::new(),
+            environment: RunEnvironment::default(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+/// The run settings being edited, either a project's own settings or one of its profiles
+pub enum RunSettingsTarget {
+    Project(u32),
+    /// The project id and the profile name
+    Profile(u32, String),
+}
+
+// SETTINGS
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// App wide preferences, saved separately from the open projects
+pub struct Settings {
+    /// Tests that take at least this many seconds are highlighted as slow
+    pub slow_test_threshold: f64,
+    /// The command used to open a test in an editor, {file}, {line} and {column} are swapped for where the test is
+    pub editor_command: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            slow_test_threshold: 1.0,
+            editor_command: EDITOR_PRESETS[0].1.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_slow(&self, duration: f64) -> bool {
+        duration >= self.slow_test_threshold
+    }
+}
+
 // STATE
 
 #[derive(Clone)]
@@ -209,13 +818,17 @@ pub struct State {
     /// All of the currently open projects
     pub open_projects: Vec<Project>,
     pub active_project: u32,
-    pub status: Status,
     /// Client-side decorations for wayland
     pub csd: bool,
     /// Stores data for the alert banner
     pub alert: Option<Alert>,
-    /// Args passed into cargo_ptest::Run::run()
-    pub run_args: RunArgs,
+    /// Run profiles keyed by the path of the project they belong to, so they are kept when a project is closed
+    pub profiles: HashMap<String, Vec<RunProfile>>,
+    pub settings: Settings,
+    /// The keys bound to each shortcut keyed by the shortcut's name, as loaded from the keymap file
+    pub keymap: HashMap<String, String>,
+    /// How many repeated runs have been started, used to give each one its own id
+    pub repeats_started: u32,
 }
 
 impl State {
@@ -278,28 +891,25 @@ impl State {
         }
         self.active_project = id;
     }
-    pub fn get_active_project(&self) -> Option<Project> {
-        let search = self
-            .open_projects
+    pub fn get_project(&self, id: u32) -> Option<Project> {
+        self.open_projects.iter().find(|x| x.id == id).cloned()
+    }
+    /// Borrows the active project instead of cloning it, this should be used when the project's tests are only being read
+    pub fn active_project(&self) -> Option<&Project> {
+        self.open_projects
             .iter()
-            .filter_map(|x| {
-                if x.id == self.active_project {
-                    Some(x.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<Project>>();
-        if search.len() == 1 {
-            Some(search[0].clone())
-        } else {
-            None
-        }
+            .find(|x| x.id == self.active_project)
     }
     pub fn has_active_project(&self) -> bool {
-        self.get_active_project().is_some()
+        self.active_project().is_some()
     }
     pub fn set_tests(&mut self, id: u32, tests: Vec<ParsedTestGroup>) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.keep_selection(|x| x.tests = Some(tests))
+        }
+    }
+    /// Empties the test list of a project so streamed tests can be added to it
+    pub fn start_streamed_run(&mut self, id: u32) {
         self.open_projects = self
             .open_projects
             .clone()
@@ -307,7 +917,11 @@ impl State {
             .map(|x| {
                 if x.id == id {
                     Project {
-                        tests: Some(tests.clone()),
+                        tests: Some(Vec::new()),
+                        selected_test: None,
+                        durations: HashMap::new(),
+                        outputs: HashMap::new(),
+                        revision: x.revision + 1,
                         ..x
                     }
                 } else {
@@ -316,6 +930,385 @@ impl State {
             })
             .collect::<Vec<Project>>();
     }
+    /// Adds a test that was streamed from a run that is still in progress, the summary of its group is updated as tests come in.
+    /// If merge is true the test replaces the existing test with the same id instead of being added to a new group.
+    /// Events from a run that has been cancelled or replaced by a newer run are ignored.
+    pub fn add_run_event(&mut self, id: u32, handle: &RunHandle, event: RunEvent, merge: bool) {
+        let project = match self.open_projects.iter_mut().find(|x| x.id == id) {
+            Some(res) => res,
+            None => return,
+        };
+
+        if !project
+            .run_handle
+            .as_ref()
+            .is_some_and(|x| x.is_same(handle))
+        {
+            return;
+        }
+
+        if let RunEvent::TestFinished(test, Some(time)) = &event {
+            project.durations.insert(test_id(test), *time);
+        }
+        project.revision += 1;
+
+        if merge {
+            if let RunEvent::TestFinished(test, _) = event {
+                project.keep_selection(|x| x.merge_test(test))
+            }
+            return;
+        }
+
+        let groups = project.tests.get_or_insert(Vec::new());
+
+        // groups are only given their crate name and file path once the run is parsed at the end
+        let new_group = ParsedTestGroup {
+            crate_name: String::new(),
+            file_path: Vec::new(),
+            tests: Vec::new(),
+            summary: Some(Summary::default()),
+        };
+
+        match event {
+            RunEvent::GroupStarted => groups.push(new_group),
+            RunEvent::TestFinished(test, _) => {
+                if groups.is_empty() {
+                    groups.push(new_group)
+                }
+
+                let group = groups.last_mut().unwrap();
+                *group.summary.get_or_insert(Summary::default()) += test.status.clone();
+                group.tests.push(test);
+            }
+        }
+    }
+    /// Records which tests are being run for a project
+    pub fn set_last_run(&mut self, id: u32, scope: RunScope) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.last_run = Some(scope)
+        }
+    }
+    /// Changes the run args of a project or one of its profiles
+    pub fn update_run_args(
+        &mut self,
+        target: &RunSettingsTarget,
+        update: impl FnOnce(&mut RunArgs),
+    ) {
+        match target {
+            RunSettingsTarget::Project(id) => {
+                if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == *id) {
+                    update(&mut project.run_args)
+                }
+            }
+            RunSettingsTarget::Profile(id, name) => {
+                self.update_profile(*id, name, |profile| update(&mut profile.run_args))
+            }
+        }
+    }
+    /// Gets the run args of a project or one of its profiles
+    pub fn get_run_args(&self, target: &RunSettingsTarget) -> Option<RunArgs> {
+        match target {
+            RunSettingsTarget::Project(id) => self.get_project(*id).map(|x| x.run_args),
+            RunSettingsTarget::Profile(id, name) => self
+                .get_profiles(*id)
+                .into_iter()
+                .find(|x| &x.name == name)
+                .map(|x| x.run_args),
+        }
+    }
+    /// Changes the environment of a project or one of its profiles
+    pub fn update_environment(
+        &mut self,
+        target: &RunSettingsTarget,
+        update: impl FnOnce(&mut RunEnvironment),
+    ) {
+        match target {
+            RunSettingsTarget::Project(id) => {
+                if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == *id) {
+                    update(&mut project.environment)
+                }
+            }
+            RunSettingsTarget::Profile(id, name) => {
+                self.update_profile(*id, name, |profile| update(&mut profile.environment))
+            }
+        }
+    }
+    /// Gets the environment of a project or one of its profiles, this doesn't include the project's environment for profiles
+    pub fn get_environment(&self, target: &RunSettingsTarget) -> Option<RunEnvironment> {
+        match target {
+            RunSettingsTarget::Project(id) => self.get_project(*id).map(|x| x.environment),
+            RunSettingsTarget::Profile(id, name) => self
+                .get_profiles(*id)
+                .into_iter()
+                .find(|x| &x.name == name)
+                .map(|x| x.environment),
+        }
+    }
+    pub fn get_profiles(&self, id: u32) -> Vec<RunProfile> {
+        self.get_project(id)
+            .and_then(|x| self.profiles.get(&x.path_string()).cloned())
+            .unwrap_or(Vec::new())
+    }
+    /// Gets the profile a project is using, None if it is using its own run args
+    pub fn get_active_profile(&self, id: u32) -> Option<RunProfile> {
+        let name = self.get_project(id)?.active_profile?;
+        self.get_profiles(id).into_iter().find(|x| x.name == name)
+    }
+    /// The run settings a project's runs use, its active profile if it has one
+    pub fn active_run_settings(&self, id: u32) -> RunSettingsTarget {
+        match self.get_active_profile(id) {
+            Some(profile) => RunSettingsTarget::Profile(id, profile.name),
+            None => RunSettingsTarget::Project(id),
+        }
+    }
+    pub fn set_active_profile(&mut self, id: u32, name: Option<String>) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.active_profile = name
+        }
+    }
+    /// Adds a profile to a project starting from the project's current run args, the name must be unique within the project
+    pub fn add_profile(&mut self, id: u32, name: String) -> Result<(), String> {
+        let name = name.trim().to_string();
+        let project = match self.get_project(id) {
+            Some(res) => res,
+            None => return Err(String::from("The project is not open")),
+        };
+
+        if name.is_empty() {
+            return Err(String::from("Profiles need a name"));
+        }
+
+        let profiles = self.profiles.entry(project.path_string()).or_default();
+
+        if profiles.iter().any(|x| x.name == name) {
+            return Err(format!("There is already a profile called {}", name));
+        }
+
+        profiles.push(RunProfile {
+            name,
+            run_args: project.run_args,
+            ..Default::default()
+        });
+
+        Ok(())
+    }
+    pub fn remove_profile(&mut self, id: u32, name: &String) {
+        let path = match self.get_project(id) {
+            Some(res) => res.path_string(),
+            None => return,
+        };
+
+        if let Some(profiles) = self.profiles.get_mut(&path) {
+            profiles.retain(|x| &x.name != name)
+        }
+
+        // any project using the profile goes back to its own run args
+        for project in self.open_projects.iter_mut() {
+            if project.path_string() == path && project.active_profile.as_ref() == Some(name) {
+                project.active_profile = None
+            }
+        }
+    }
+    pub fn update_profile(&mut self, id: u32, name: &String, update: impl FnOnce(&mut RunProfile)) {
+        let path = match self.get_project(id) {
+            Some(res) => res.path_string(),
+            None => return,
+        };
+
+        if let Some(profile) = self
+            .profiles
+            .get_mut(&path)
+            .and_then(|x| x.iter_mut().find(|x| &x.name == name))
+        {
+            update(profile)
+        }
+    }
+    pub fn set_watching(&mut self, id: u32, watching: bool) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.watching = watching;
+            project.watch_queued = false;
+        }
+    }
+    pub fn set_watch_queued(&mut self, id: u32, queued: bool) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.watch_queued = queued
+        }
+    }
+    pub fn is_running(&self, id: u32) -> bool {
+        self.open_projects.iter().any(|x| x.id == id && x.running)
+    }
+    /// The number of projects that currently have tests running
+    pub fn active_runs(&self) -> usize {
+        self.open_projects.iter().filter(|x| x.running).count()
+    }
+    /// Marks a project as running, the handle is kept so the run can be cancelled
+    pub fn start_run(&mut self, id: u32, handle: RunHandle) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.running = true;
+            project.cancelled = false;
+            project.run_handle = Some(handle);
+            project.viewing_history = None;
+        }
+    }
+    /// Whether the handle is for the run the project is currently doing, false once the run has been cancelled
+    pub fn is_current_run(&self, id: u32, handle: &RunHandle) -> bool {
+        self.open_projects
+            .iter()
+            .find(|x| x.id == id)
+            .and_then(|x| x.run_handle.as_ref())
+            .is_some_and(|x| x.is_same(handle))
+    }
+    /// Marks a project's run as finished, does nothing if the handle isn't for the project's current run
+    pub fn finish_run(&mut self, id: u32, handle: &RunHandle) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id)
+            && project
+                .run_handle
+                .as_ref()
+                .is_some_and(|x| x.is_same(handle))
+        {
+            project.running = false;
+            project.run_handle = None;
+        }
+    }
+    /// Stops a project's test run, any tests that have already been streamed are kept
+    pub fn cancel_run(&mut self, id: u32) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            if let Some(handle) = project.run_handle.take() {
+                handle.cancel();
+                project.running = false;
+                project.cancelled = true;
+            }
+        }
+    }
+    /// Stores what each test printed in a finished run, if merge is true only the output of the tests that were run is replaced
+    pub fn set_outputs(
+        &mut self,
+        id: u32,
+        outputs: HashMap<String, String>,
+        run: &[ParsedTestGroup],
+        merge: bool,
+    ) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            if merge {
+                // tests that printed nothing this time shouldn't keep their old output
+                for test in run.iter().flat_map(|x| x.tests.iter()) {
+                    project.outputs.remove(&test_id(test));
+                }
+                project.outputs.extend(outputs)
+            } else {
+                project.outputs = outputs
+            }
+            project.revision += 1;
+        }
+    }
+    /// Shows the results of a past run in place of the project's current tests
+    pub fn open_history_entry(&mut self, id: u32, entry: &HistoryEntry) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.tests = Some(entry.tests());
+            project.durations = entry.durations.clone();
+            // output isn't kept in the history
+            project.outputs = HashMap::new();
+            project.selected_test = None;
+            project.last_run = None;
+            project.viewing_history = Some(entry.timestamp);
+            project.revision += 1;
+        }
+    }
+    pub fn update_settings(&mut self, update: impl FnOnce(&mut Settings)) {
+        update(&mut self.settings)
+    }
+    pub fn set_flakiness(&mut self, id: u32, flakiness: HashMap<String, Flakiness>) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.flakiness = flakiness;
+            project.revision += 1;
+        }
+    }
+    pub fn set_status_changes(&mut self, id: u32, status_changes: HashMap<String, u64>) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.status_changes = status_changes;
+            project.revision += 1;
+        }
+    }
+    pub fn set_sort(&mut self, id: u32, sort: TestSort) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.sort = sort
+        }
+    }
+    pub fn toggle_flaky_only(&mut self, id: u32) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.flaky_only = !project.flaky_only
+        }
+    }
+    pub fn update_filter(&mut self, id: u32, update: impl FnOnce(&mut TestFilter)) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            update(&mut project.filter)
+        }
+    }
+    /// Collapses the test tree node with the given key if it's expanded, otherwise expands it
+    pub fn toggle_collapsed(&mut self, id: u32, key: String) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            if !project.collapsed.remove(&key) {
+                project.collapsed.insert(key);
+            }
+        }
+    }
+    /// Starts collecting the results of a run that will be repeated the given number of times, replacing any previous results.
+    /// Returns the id the repeated runs need to be started with for their results to be counted
+    pub fn start_repeat(&mut self, id: u32, total: u32) -> u32 {
+        self.repeats_started += 1;
+        let repeat_id = self.repeats_started;
+
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.repeat = Some(RepeatRun::new(repeat_id, total))
+        }
+        repeat_id
+    }
+    pub fn clear_repeat(&mut self, id: u32) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.repeat = None
+        }
+    }
+    /// Adds the results of one of the runs of a repeated run, does nothing if the run wasn't started as part of the project's repeated run
+    pub fn record_repeat(&mut self, id: u32, repeat_id: u32, tests: &[ParsedTestGroup]) {
+        if let Some(repeat) = self
+            .open_projects
+            .iter_mut()
+            .find(|x| x.id == id)
+            .and_then(|x| x.repeat.as_mut())
+            .filter(|x| x.id == repeat_id)
+        {
+            repeat.record(tests)
+        }
+    }
+    /// Shows a diff in place of the project's test list
+    pub fn open_diff(&mut self, id: u32, diff: RunDiff) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.diff = Some(diff)
+        }
+    }
+    pub fn close_diff(&mut self, id: u32) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.diff = None
+        }
+    }
+    pub fn select_diff_test(&mut self, index: usize) {
+        let active = self.active_project;
+
+        if let Some(diff) = self
+            .open_projects
+            .iter_mut()
+            .find(|x| x.id == active)
+            .and_then(|x| x.diff.as_mut())
+        {
+            diff.selected = Some(index)
+        }
+    }
+    /// Merges the results of a partial run into a project's existing tests
+    pub fn merge_tests(&mut self, id: u32, tests: Vec<ParsedTestGroup>) {
+        if let Some(project) = self.open_projects.iter_mut().find(|x| x.id == id) {
+            project.keep_selection(|x| x.merge_groups(tests))
+        }
+    }
     pub fn clear_tests(&mut self, id: u32) {
         self.open_projects = self
             .open_projects
@@ -326,6 +1319,8 @@ impl State {
                     Project {
                         tests: None,
                         selected_test: None,
+                        repeat: None,
+                        revision: x.revision + 1,
                         ..x
                     }
                 } else {
@@ -368,24 +1363,53 @@ impl State {
             })
             .collect::<Vec<Project>>();
     }
-    pub fn get_selected_test(&self) -> Option<ParsedTest> {
-        if !self.has_active_project() {
-            return None;
-        }
-        if self.get_active_project()?.tests_linear().is_none() {
-            return None;
-        }
-        if self.get_active_project()?.selected_test.is_none() {
-            return None;
+    /// How long the selected test took, in a diff this is the newer side's time if it has one
+    pub fn get_selected_test_duration(&self) -> Option<f64> {
+        let project = self.active_project()?;
+
+        if let Some(diff) = &project.diff {
+            let test = diff.tests.get(diff.selected?)?;
+            return test.durations.1.or(test.durations.0);
         }
-        match self
-            .get_active_project()?
-            .tests_linear()?
-            .get(self.get_active_project()?.selected_test?)
-        {
-            Some(res) => Some(res.clone()),
-            None => None,
+
+        project
+            .durations
+            .get(&test_id(&self.get_selected_test()?))
+            .copied()
+    }
+    pub fn get_selected_test(&self) -> Option<ParsedTest> {
+        let project = self.active_project()?;
+
+        // while a diff is open the selected test is one of its tests
+        if let Some(diff) = &project.diff {
+            return diff.tests.get(diff.selected?).map(|x| x.test.clone());
         }
+
+        project
+            .tests
+            .as_ref()?
+            .iter()
+            .flat_map(|x| x.tests.iter())
+            .nth(project.selected_test?)
+            .cloned()
+    }
+    /// What one of the active project's tests printed the last time it was run, None if it printed nothing
+    pub fn get_test_output(&self, test: &ParsedTest) -> Option<String> {
+        self.active_project()?.outputs.get(&test_id(test)).cloned()
+    }
+    /// The file one of the active project's tests was run from, as given by test_file()
+    pub fn get_test_file(&self, test: &ParsedTest) -> Option<String> {
+        let project = self.active_project()?;
+        let id = test_id(test);
+
+        // the test is looked up by its id as tests from a diff aren't in the current results at the same index
+        project.tests.as_ref()?.iter().find_map(|group| {
+            group
+                .tests
+                .iter()
+                .find(|x| test_id(x) == id)
+                .map(|x| test_file(group, x))
+        })
     }
 }
 
@@ -413,17 +1437,278 @@ impl Default for State {
         Self {
             open_projects: Vec::new(),
             active_project: 0,
-            status: Default::default(),
             csd: false,
             alert: None,
-            run_args: Default::default(),
+            profiles: HashMap::new(),
+            settings: Settings::default(),
+            keymap: HashMap::new(),
+            repeats_started: 0,
+        }
+    }
+}
+
+// TEST LIST
+
+#[derive(Clone, PartialEq)]
+/// What the test list's rows were built from, the tests themselves are tracked by Project::revision
+struct TestRowsSource {
+    project_id: u32,
+    revision: u64,
+    filter: TestFilter,
+    sort: TestSort,
+    collapsed: HashSet<String>,
+    flaky_only: bool,
+}
+
+#[derive(Clone)]
+/// The rows of the test list for the active project
+pub struct TestRows {
+    source: TestRowsSource,
+    pub rows: Rc<Vec<TestRow>>,
+    /// How many tests are left after filtering, including those in collapsed nodes
+    pub shown: usize,
+    /// How many tests there are before filtering
+    pub total: usize,
+    /// Every file tests were run from, used for the file filters
+    pub files: Rc<Vec<String>>,
+}
+
+#[derive(Default)]
+/// Kept between frames so the test list is only rebuilt when something it shows changes
+pub struct TestListState {
+    rows: Option<TestRows>,
+    /// Each project's scroll position keyed by project id
+    scroll_handles: HashMap<u32, UniformListScrollHandle>,
+    /// The project and test that were selected when the list was last shown, used to scroll to newly selected tests
+    pub last_selected: Option<(u32, usize)>,
+    /// Shared with the search box so it can be focused with a shortcut
+    search_focus: Option<FocusHandle>,
+}
+
+impl Global for TestListState {}
+
+impl TestListState {
+    /// Gets the rows for the project, only rebuilding them if the project has changed since they were last built
+    pub fn rows(&mut self, project: &Project) -> TestRows {
+        let source = TestRowsSource {
+            project_id: project.id,
+            revision: project.revision,
+            filter: project.filter.clone(),
+            sort: project.sort,
+            collapsed: project.collapsed.clone(),
+            flaky_only: project.flaky_only,
+        };
+
+        if let Some(rows) = self.rows.as_ref().filter(|x| x.source == source) {
+            return rows.clone();
+        }
+
+        let tree = test_tree(project, |group, test| {
+            (!project.flaky_only
+                || project
+                    .flakiness
+                    .get(&test_id(test))
+                    .is_some_and(|x| x.is_flaky()))
+                && project.filter.matches(&test_file(group, test), test)
+        });
+        let shown = tree
+            .iter()
+            .map(|x| (x.counts.passed + x.counts.failed + x.counts.ignored) as usize)
+            .sum();
+        let rows = TestRows {
+            source,
+            rows: Rc::new(test_rows(tree, &project.collapsed)),
+            shown,
+            total: project.tests.iter().flatten().map(|x| x.tests.len()).sum(),
+            files: Rc::new(test_files(project.tests.as_ref().unwrap_or(&Vec::new()))),
+        };
+
+        self.rows = Some(rows.clone());
+        rows
+    }
+
+    pub fn scroll_handle(&mut self, id: u32) -> UniformListScrollHandle {
+        self.scroll_handles.entry(id).or_default().clone()
+    }
+
+    pub fn search_focus(&mut self, cx: &mut App) -> FocusHandle {
+        self.search_focus
+            .get_or_insert_with(|| cx.focus_handle())
+            .clone()
+    }
+
+    /// The index of each test in the order they are shown and whether it failed, tests in collapsed nodes are left out
+    fn shown_tests(&mut self, project: &Project) -> Vec<(usize, bool)> {
+        self.rows(project)
+            .rows
+            .iter()
+            .filter_map(|x| match x {
+                TestRow::Test { test, .. } => {
+                    Some((test.index, test.test.status == TestStatus::Failed))
+                }
+                TestRow::Node { .. } => None,
+            })
+            .collect()
+    }
+
+    /// The index of the test shown after or before the selected one, stopping at either end of the list
+    pub fn adjacent_test(&mut self, project: &Project, forward: bool) -> Option<usize> {
+        let tests = self.shown_tests(project);
+        if tests.is_empty() {
+            return None;
         }
+
+        let current = project
+            .selected_test
+            .and_then(|x| tests.iter().position(|test| test.0 == x));
+        let next = match current {
+            Some(res) if forward => (res + 1).min(tests.len() - 1),
+            Some(res) => res.saturating_sub(1),
+            None if forward => 0,
+            None => tests.len() - 1,
+        };
+
+        Some(tests[next].0)
     }
+
+    /// The index of the next failed test shown after the selected one, wrapping around to the top of the list
+    pub fn next_failure(&mut self, project: &Project) -> Option<usize> {
+        let tests = self.shown_tests(project);
+
+        let start = project
+            .selected_test
+            .and_then(|x| tests.iter().position(|test| test.0 == x))
+            .map_or(0, |x| x + 1);
+
+        (0..tests.len())
+            .map(|x| tests[(start + x) % tests.len()])
+            .find(|x| x.1)
+            .map(|x| x.0)
+    }
+}
+
+// SOURCE VIEWER
+
+#[derive(Default)]
+/// Kept between frames so source files are only read again when they change
+pub struct SourceViewState {
+    /// Each loaded file and when it was last changed
+    files: HashMap<PathBuf, (SystemTime, Rc<SourceFile>)>,
+    /// The test last looked up and the file its code was found in
+    located: Option<(String, Option<PathBuf>)>,
+    /// The test whose line was last found, when its file was last changed and the line
+    test_line: Option<(String, SystemTime, Option<usize>)>,
+    /// Files from the located test's panic and backtrace, by the path cargo gave
+    resolved: HashMap<String, Option<PathBuf>>,
+    scroll_handle: UniformListScrollHandle,
+    /// The file and line last scrolled to, so the code is only scrolled when a different test is selected
+    pub last_shown: Option<(PathBuf, usize)>,
+    /// A backtrace frame shown instead of the test's code, with the id of the test it is from
+    frame: Option<(String, PathBuf, usize)>,
 }
 
-// SCROLL HANDLING
-pub struct ScrollHandles {
-    pub test_list: f32,
+impl Global for SourceViewState {}
+
+impl SourceViewState {
+    /// Gets a file's lines, reading it again if it has changed since it was last read
+    pub fn file(&mut self, path: &Path) -> Option<Rc<SourceFile>> {
+        let modified = path.metadata().and_then(|x| x.modified()).ok()?;
+
+        if let Some((time, file)) = self.files.get(path)
+            && *time == modified
+        {
+            return Some(file.clone());
+        }
+
+        let file = Rc::new(SourceFile::load(path)?);
+        self.files
+            .insert(path.to_path_buf(), (modified, file.clone()));
+        Some(file)
+    }
+
+    /// The file a test's code is in, only searched for again when a different test is given
+    pub fn test_source(
+        &mut self,
+        project_path: &Path,
+        file: &str,
+        test: &ParsedTest,
+    ) -> Option<PathBuf> {
+        let key = format!("{}:{}:{}", project_path.display(), file, test_id(test));
+
+        match &self.located {
+            Some((located, path)) if *located == key => path.clone(),
+            _ => {
+                let path = test_source_file(project_path, file, test);
+                self.located = Some((key, path.clone()));
+                self.resolved.clear();
+                path
+            }
+        }
+    }
+
+    /// The line a test starts on in a file, only searched for again when a different test is given or the file changes
+    pub fn test_line(&mut self, path: &Path, test: &ParsedTest) -> Option<usize> {
+        let modified = path.metadata().and_then(|x| x.modified()).ok()?;
+        let key = format!("{}:{}", path.display(), test_id(test));
+
+        if let Some((located, time, line)) = &self.test_line
+            && *located == key
+            && *time == modified
+        {
+            return *line;
+        }
+
+        let line = find_test_line(&self.file(path)?.lines, test);
+        self.test_line = Some((key, modified, line));
+        line
+    }
+
+    /// Finds a file from a panic or backtrace of the located test, see resolve_file()
+    pub fn resolve_file(&mut self, project_path: &Path, file: &str) -> Option<PathBuf> {
+        self.resolved
+            .entry(file.to_string())
+            .or_insert_with(|| resolve_file(project_path, file))
+            .clone()
+    }
+
+    pub fn scroll_handle(&self) -> UniformListScrollHandle {
+        self.scroll_handle.clone()
+    }
+    /// Shows a file from one of a test's backtrace frames until another test is selected, the line starts at 0
+    pub fn show_frame(&mut self, test: &ParsedTest, path: PathBuf, line: usize) {
+        self.frame = Some((test_id(test), path, line))
+    }
+    pub fn clear_frame(&mut self) {
+        self.frame = None
+    }
+    /// The frame being shown if it is from the given test
+    pub fn frame(&self, test: &ParsedTest) -> Option<(PathBuf, usize)> {
+        self.frame
+            .as_ref()
+            .filter(|x| x.0 == test_id(test))
+            .map(|x| (x.1.clone(), x.2))
+    }
+}
+
+// WATCHING
+
+/// A file watcher and the task that re-runs tests when it sees a change, dropping this stops watching
+pub struct ProjectWatch {
+    pub _watcher: RecommendedWatcher,
+    pub _task: Task<()>,
+}
+
+/// The watchers for every project in watch mode, keyed by project id
+pub struct Watchers {
+    pub watchers: HashMap<u32, ProjectWatch>,
+}
+
+impl Default for Watchers {
+    fn default() -> Self {
+        Self {
+            watchers: HashMap::new(),
+        }
+    }
 }
 
-impl Global for ScrollHandles {}
+impl Global for Watchers {}
@@ -73,6 +73,40 @@ pub struct TestInfoStyle {
     pub block_padding: Size,
     /// Padding for the information grid
     pub grid_padding: Size,
+    /// Background colour of the characters only in the left value of a failed assertion
+    pub diff_left_background: Colour,
+    /// Background colour of the characters only in the right value of a failed assertion
+    pub diff_right_background: Colour,
+}
+
+#[derive(Clone)]
+/// Contains all the styling specific to the source code viewer
+pub struct CodeStyle {
+    /// Background colour of the code
+    pub bg_colour: Colour,
+    /// Colour of the line numbers
+    pub line_number_colour: Colour,
+    /// Background colour of the line the test starts on
+    pub test_line_colour: Colour,
+    /// Background colour of the line the test panicked on
+    pub panic_line_colour: Colour,
+    /// Background colour of text matching a search
+    pub search_match_colour: Colour,
+    /// Background colour of the search match that was jumped to
+    pub current_match_colour: Colour,
+    /// Height of each line
+    pub line_height: Size,
+    /// Font size of the code
+    pub text_size: Size,
+    pub keyword_colour: Colour,
+    pub string_colour: Colour,
+    pub number_colour: Colour,
+    pub comment_colour: Colour,
+    pub attribute_colour: Colour,
+    pub macro_colour: Colour,
+    pub type_colour: Colour,
+    pub function_colour: Colour,
+    pub lifetime_colour: Colour,
 }
 
 #[derive(Clone)]
@@ -122,6 +156,8 @@ pub struct Style {
     pub alert: AlertStyle,
     /// Styling for the test info panel
     pub test_info: TestInfoStyle,
+    /// Styling for the source code viewer in the test info panel
+    pub code: CodeStyle,
 }
 
 impl Default for ToolBarStyle {
@@ -184,6 +220,32 @@ impl Default for TestInfoStyle {
             note_foreground: Colour::Rgb(0xde5544),
             block_padding: Size::Px(8.0),
             grid_padding: Size::Px(4.0),
+            diff_left_background: Colour::Rgba(0xde554466),
+            diff_right_background: Colour::Rgba(0x6bcb6166),
+        }
+    }
+}
+
+impl Default for CodeStyle {
+    fn default() -> Self {
+        Self {
+            bg_colour: Colour::Rgb(0x1e1f22),
+            line_number_colour: Colour::Rgba(0xffffff55),
+            test_line_colour: Colour::Rgba(0x2563eb44),
+            panic_line_colour: Colour::Rgba(0xde554444),
+            search_match_colour: Colour::Rgba(0xd7ba7d55),
+            current_match_colour: Colour::Rgba(0xe8a33dcc),
+            line_height: Size::Px(18.0),
+            text_size: Size::Px(13.0),
+            keyword_colour: Colour::Rgb(0xcf8e6d),
+            string_colour: Colour::Rgb(0x6aab73),
+            number_colour: Colour::Rgb(0x2aacb8),
+            comment_colour: Colour::Rgb(0x7a7e85),
+            attribute_colour: Colour::Rgb(0xb3ae60),
+            macro_colour: Colour::Rgb(0x56a8f5),
+            type_colour: Colour::Rgb(0x9ccbd4),
+            function_colour: Colour::Rgb(0x56a8f5),
+            lifetime_colour: Colour::Rgb(0x20999d),
         }
     }
 }
@@ -213,6 +275,7 @@ impl Default for Style {
             controlbar: Default::default(),
             alert: Default::default(),
             test_info: Default::default(),
+            code: Default::default(),
         }
     }
 }
@@ -0,0 +1,214 @@
+use std::ops::Range;
+
+/// The 16 basic terminal colours as rgb, the first 8 are the normal colours and the rest are their bright versions
+const BASIC_COLOURS: [u32; 16] = [
+    0x1e1e1e, 0xde5554, 0x6bcb61, 0xd7ba7d, 0x569cd6, 0xc586c0, 0x4ec9b0, 0xd4d4d4, 0x808080,
+    0xf14c4c, 0x23d18b, 0xf5f543, 0x3b8eea, 0xd670d6, 0x29b8db, 0xffffff,
+];
+
+#[derive(Clone, Copy, Default, PartialEq)]
+/// How a piece of text was styled by SGR escape codes, colours are rgb
+pub struct AnsiStyle {
+    pub foreground: Option<u32>,
+    pub background: Option<u32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl AnsiStyle {
+    fn is_plain(&self) -> bool {
+        *self == AnsiStyle::default()
+    }
+}
+
+#[derive(Clone, Default)]
+/// A line of output with its escape codes removed, spans are byte ranges into text
+pub struct AnsiLine {
+    pub text: String,
+    pub spans: Vec<(Range<usize>, AnsiStyle)>,
+}
+
+/// The rgb value of a colour from the 256 colour palette
+fn palette_colour(index: u8) -> u32 {
+    match index {
+        0..=15 => BASIC_COLOURS[index as usize],
+        // a 6x6x6 cube of colours
+        16..=231 => {
+            let index = index - 16;
+            let level = |x: u8| if x == 0 { 0 } else { 55 + x as u32 * 40 };
+            (level(index / 36) << 16) | (level((index / 6) % 6) << 8) | level(index % 6)
+        }
+        // 24 shades of grey
+        _ => {
+            let grey = 8 + (index - 232) as u32 * 10;
+            (grey << 16) | (grey << 8) | grey
+        }
+    }
+}
+
+/// Reads an extended colour, e.g. the 5;208 of 38;5;208 or the 2;255;0;0 of 38;2;255;0;0
+fn extended_colour(codes: &mut impl Iterator<Item = u16>) -> Option<u32> {
+    match codes.next()? {
+        5 => Some(palette_colour(codes.next()?.min(255) as u8)),
+        2 => {
+            let r = codes.next()?.min(255) as u32;
+            let g = codes.next()?.min(255) as u32;
+            let b = codes.next()?.min(255) as u32;
+            Some((r << 16) | (g << 8) | b)
+        }
+        _ => None,
+    }
+}
+
+/// Applies the codes of one SGR sequence, e.g. the 1;31 of \x1b[1;31m
+fn apply_codes(style: &mut AnsiStyle, params: &str) {
+    let mut codes = params
+        .split(';')
+        // an empty code is the same as 0
+        .map(|x| x.parse::<u16>().unwrap_or(0));
+
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = AnsiStyle::default(),
+            1 => style.bold = true,
+            3 => style.italic = true,
+            4 => style.underline = true,
+            22 => style.bold = false,
+            23 => style.italic = false,
+            24 => style.underline = false,
+            30..=37 => style.foreground = Some(BASIC_COLOURS[(code - 30) as usize]),
+            38 => style.foreground = extended_colour(&mut codes),
+            39 => style.foreground = None,
+            40..=47 => style.background = Some(BASIC_COLOURS[(code - 40) as usize]),
+            48 => style.background = extended_colour(&mut codes),
+            49 => style.background = None,
+            90..=97 => style.foreground = Some(BASIC_COLOURS[(code - 90 + 8) as usize]),
+            100..=107 => style.background = Some(BASIC_COLOURS[(code - 100 + 8) as usize]),
+            _ => {}
+        }
+    }
+}
+
+/// Splits output into lines with the colours given by its escape codes, styles carry on from one line to the next.
+/// Escape codes other than colours and text styles are removed.
+pub fn parse_ansi(output: &str) -> Vec<AnsiLine> {
+    let mut lines = Vec::new();
+    let mut style = AnsiStyle::default();
+
+    for raw in output.lines() {
+        let mut line = AnsiLine::default();
+        let mut chars = raw.chars().peekable();
+
+        while let Some(character) = chars.next() {
+            if character == '\u{1b}' {
+                if chars.peek() != Some(&'[') {
+                    chars.next();
+                    continue;
+                }
+                chars.next();
+
+                // the parameters end at the first letter, which says what the sequence does
+                let mut params = String::new();
+                let mut command = None;
+                for x in chars.by_ref() {
+                    if x.is_ascii_alphabetic() || x == '~' {
+                        command = Some(x);
+                        break;
+                    }
+                    params.push(x)
+                }
+
+                if command == Some('m') {
+                    apply_codes(&mut style, &params)
+                }
+                continue;
+            }
+
+            // carriage returns from progress output would show as boxes
+            if character == '\r' {
+                continue;
+            }
+
+            let start = line.text.len();
+            line.text.push(character);
+            let end = line.text.len();
+
+            if style.is_plain() {
+                continue;
+            }
+            match line.spans.last_mut() {
+                Some((range, last)) if range.end == start && *last == style => range.end = end,
+                _ => line.spans.push((start..end, style)),
+            }
+        }
+
+        lines.push(line)
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text and style of each span in a line
+    fn spans(line: &AnsiLine) -> Vec<(&str, AnsiStyle)> {
+        line.spans
+            .iter()
+            .map(|(range, style)| (&line.text[range.clone()], *style))
+            .collect()
+    }
+
+    #[test]
+    fn plain() {
+        let lines = parse_ansi("hello\nworld");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].text, "world");
+        assert!(lines.iter().all(|x| x.spans.is_empty()));
+    }
+
+    #[test]
+    fn basic_colours() {
+        let lines = parse_ansi("hello \x1b[31mred\x1b[0m and \x1b[1;94mbright\x1b[m");
+        assert_eq!(lines[0].text, "hello red and bright");
+
+        let red = AnsiStyle {
+            foreground: Some(BASIC_COLOURS[1]),
+            ..Default::default()
+        };
+        let bright = AnsiStyle {
+            foreground: Some(BASIC_COLOURS[12]),
+            bold: true,
+            ..Default::default()
+        };
+        assert!(spans(&lines[0]) == vec![("red", red), ("bright", bright)]);
+    }
+
+    #[test]
+    fn extended_colours() {
+        let lines = parse_ansi("\x1b[38;5;196ma\x1b[48;2;1;2;3mb\x1b[39;49mc");
+        let spans = spans(&lines[0]);
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].1.foreground, Some(0xff0000));
+        assert_eq!(spans[1].1.foreground, Some(0xff0000));
+        assert_eq!(spans[1].1.background, Some(0x010203));
+        assert_eq!(lines[0].text, "abc");
+    }
+
+    #[test]
+    fn style_carries_to_next_line() {
+        let lines = parse_ansi("\x1b[4mstart\nend\x1b[24m done");
+        assert!(lines[1].spans.len() == 1 && lines[1].spans[0].1.underline);
+        assert_eq!(&lines[1].text[lines[1].spans[0].0.clone()], "end");
+    }
+
+    #[test]
+    fn other_escapes_removed() {
+        let lines = parse_ansi("\x1b[2K\x1b[1Gprogress\r done\x1b7");
+        assert_eq!(lines[0].text, "progress done");
+        assert!(lines[0].spans.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use regex::{Captures, Regex};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::sync::LazyLock;
+use std::thread;
+
+/// Editor commands that can be picked in the settings, {file}, {line} and {column} are swapped for where the test is
+pub const EDITOR_PRESETS: &[(&str, &str)] = &[
+    ("VS Code", "code -g {file}:{line}"),
+    ("Zed", "zed {file}:{line}"),
+    ("$EDITOR", "$EDITOR +{line} {file}"),
+];
+
+/// An environment variable in an editor command, e.g. $EDITOR or ${EDITOR}
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$(?:\{(\w+)\}|(\w+))").unwrap());
+
+/// Swaps $NAME and ${NAME} for the value of the environment variable
+fn expand_env(template: &str) -> Result<String, String> {
+    let mut missing = None;
+
+    let expanded = VARIABLE.replace_all(template, |capture: &Captures| {
+        let name = capture.get(1).or(capture.get(2)).unwrap().as_str();
+        env::var(name).unwrap_or_else(|_| {
+            missing = Some(name.to_string());
+            String::new()
+        })
+    });
+
+    match missing {
+        Some(name) => Err(format!("The environment variable {} is not set", name)),
+        None => Ok(expanded.to_string()),
+    }
+}
+
+/// Splits a command into its arguments on spaces, keeping anything in quotes together
+fn split_command(command: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut started = false;
+
+    for character in command.chars() {
+        match (character, quote) {
+            ('"' | '\'', None) => {
+                quote = Some(character);
+                started = true;
+            }
+            (_, Some(open)) if character == open => quote = None,
+            (_, None) if character.is_whitespace() => {
+                if started {
+                    args.push(current.clone());
+                    current.clear();
+                    started = false;
+                }
+            }
+            _ => {
+                current.push(character);
+                started = true;
+            }
+        }
+    }
+
+    if started {
+        args.push(current)
+    }
+
+    args
+}
+
+/// The program and arguments to run for an editor command, the line and column start at 1
+pub fn editor_command(
+    template: &str,
+    file: &Path,
+    line: usize,
+    column: usize,
+) -> Result<Vec<String>, String> {
+    // the placeholders are filled in after splitting so paths with spaces stay as one argument
+    let args: Vec<String> = split_command(&expand_env(template)?)
+        .into_iter()
+        .map(|x| {
+            x.replace("{file}", &file.display().to_string())
+                .replace("{line}", &line.to_string())
+                .replace("{column}", &column.to_string())
+        })
+        .collect();
+
+    if args.is_empty() {
+        return Err(String::from("No editor command has been set"));
+    }
+
+    Ok(args)
+}
+
+/// Starts the editor at the given place in a file without waiting for it to close
+pub fn open_in_editor(
+    template: &str,
+    file: &Path,
+    line: usize,
+    column: usize,
+) -> Result<(), String> {
+    let args = editor_command(template, file, line, column)?;
+
+    let mut child = Command::new(&args[0])
+        .args(&args[1..])
+        .spawn()
+        .map_err(|err| format!("Could not start {}: {}", args[0], err))?;
+
+    // the editor is waited for on another thread so it doesn't stay around as a zombie process once closed
+    thread::spawn(move || child.wait());
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
+use crate::actions::SHORTCUTS;
 use crate::display_vec;
-use crate::state::{Project, State, Status};
+use crate::state::{Project, RunProfile, Settings, State};
+use crate::utils::history::HistoryEntry;
+use crate::utils::snapshot::Snapshot;
+use crate::utils::utils::stable_hash;
+use std::collections::{BTreeMap, HashMap};
 use crate::utils::logger::warning;
 use serde::{Deserialize, Serialize};
 use std::env::home_dir;
@@ -7,7 +12,7 @@ use std::fmt::{Debug, Formatter};
 use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn config_folder() -> Result<PathBuf, String> {
     match home_dir() {
@@ -60,6 +65,9 @@ impl Debug for SaveOpenProjects {
 }
 
 pub fn save_state(state: State) {
+    save_profiles(&state.profiles);
+    save_settings(&state.settings);
+
     let save_obj = SaveOpenProjects {
         open_projects: state.open_projects,
         active_project: state.active_project,
@@ -118,7 +126,12 @@ pub fn load_state() -> State {
                         "Could not open open_projects file (load), using empty State: {}",
                         err
                     );
-                    return State::default();
+                    return State {
+                        profiles: load_profiles(),
+                        settings: load_settings(),
+                        keymap: load_keymap(),
+                        ..Default::default()
+                    };
                 }
             };
             //read from file
@@ -131,10 +144,294 @@ pub fn load_state() -> State {
             State {
                 open_projects: saved_state.open_projects,
                 active_project: saved_state.active_project,
-                status: Status::default(),
+                profiles: load_profiles(),
+                settings: load_settings(),
+                keymap: load_keymap(),
                 ..Default::default()
             }
         }
         Err(_) => State::default(),
     }
 }
+
+fn save_profiles(profiles: &HashMap<String, Vec<RunProfile>>) {
+    let str = match serde_json::to_string(profiles) {
+        Ok(res) => res,
+        Err(err) => {
+            warning!("Could not serialise run profiles: {}", err);
+            return;
+        }
+    };
+
+    match config_folder() {
+        Ok(res) => {
+            if let Err(err) = fs::write(res.join("profiles.json"), str) {
+                warning!("Could not write run profiles to file: {}", err);
+            }
+        }
+        Err(_) => {
+            warning!("Writing run profiles to file failed.");
+        }
+    }
+}
+
+fn load_profiles() -> HashMap<String, Vec<RunProfile>> {
+    let path = match config_folder() {
+        Ok(res) => res.join("profiles.json"),
+        Err(_) => return HashMap::new(),
+    };
+
+    // there won't be a file until a profile has been made
+    if !path.exists() {
+        return HashMap::new();
+    }
+
+    match fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(data.as_str()).unwrap_or_else(|err| {
+            warning!("Could not parse run profiles, using none: {}", err);
+            HashMap::new()
+        }),
+        Err(err) => {
+            warning!("Could not open run profiles file (load): {}", err);
+            HashMap::new()
+        }
+    }
+}
+
+fn save_settings(settings: &Settings) {
+    let str = match serde_json::to_string(settings) {
+        Ok(res) => res,
+        Err(err) => {
+            warning!("Could not serialise settings: {}", err);
+            return;
+        }
+    };
+
+    match config_folder() {
+        Ok(res) => {
+            if let Err(err) = fs::write(res.join("settings.json"), str) {
+                warning!("Could not write settings to file: {}", err);
+            }
+        }
+        Err(_) => {
+            warning!("Writing settings to file failed.");
+        }
+    }
+}
+
+fn load_settings() -> Settings {
+    let path = match config_folder() {
+        Ok(res) => res.join("settings.json"),
+        Err(_) => return Settings::default(),
+    };
+
+    // there won't be a file until the app has been closed once
+    if !path.exists() {
+        return Settings::default();
+    }
+
+    match fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(data.as_str()).unwrap_or_else(|err| {
+            warning!("Could not parse settings, using the defaults: {}", err);
+            Settings::default()
+        }),
+        Err(err) => {
+            warning!("Could not open settings file (load): {}", err);
+            Settings::default()
+        }
+    }
+}
+
+pub fn keymap_file() -> Result<PathBuf, String> {
+    Ok(config_folder()?.join("keymap.json"))
+}
+
+/// Loads the keys bound to each shortcut, any shortcut missing from the file keeps its default keys
+pub fn load_keymap() -> HashMap<String, String> {
+    let mut keymap: HashMap<String, String> = SHORTCUTS
+        .iter()
+        .map(|x| (x.name.to_string(), x.default_keys.to_string()))
+        .collect();
+
+    let path = match keymap_file() {
+        Ok(res) => res,
+        Err(_) => return keymap,
+    };
+
+    // the defaults are written out the first time so there is a file for the user to edit
+    if !path.exists() {
+        // sorted so the file is easier to read
+        match serde_json::to_string_pretty(&keymap.iter().collect::<BTreeMap<_, _>>()) {
+            Ok(res) => {
+                if let Err(err) = fs::write(&path, res) {
+                    warning!("Could not write keymap to file: {}", err);
+                }
+            }
+            Err(err) => warning!("Could not serialise keymap: {}", err),
+        }
+        return keymap;
+    }
+
+    match fs::read_to_string(path) {
+        Ok(data) => match serde_json::from_str::<HashMap<String, String>>(data.as_str()) {
+            Ok(res) => keymap.extend(res),
+            Err(err) => warning!("Could not parse keymap, using the defaults: {}", err),
+        },
+        Err(err) => warning!("Could not open keymap file (load): {}", err),
+    }
+
+    keymap
+}
+
+/// The most runs kept in a project's history, the oldest are removed first
+const MAX_HISTORY_ENTRIES: usize = 200;
+
+/// A name for a project that is safe to use in file names, the same path always gives the same name
+fn project_hash(project_path: &Path) -> String {
+    let path = project_path
+        .canonicalize()
+        .unwrap_or_else(|_| project_path.to_path_buf());
+    format!("{:016x}", stable_hash(path.to_string_lossy().as_bytes()))
+}
+
+/// Each project's history has its own file, named after a hash of the project's path
+fn history_file(project_path: &PathBuf) -> Result<PathBuf, String> {
+    let folder = config_folder()?.join("history");
+
+    if !folder.exists() {
+        if let Err(err) = fs::create_dir_all(folder.clone()) {
+            warning!("Failed when making history directory at {:?}", folder);
+            return Err(err.to_string());
+        }
+    }
+
+    Ok(folder.join(format!("{}.json", project_hash(project_path))))
+}
+
+/// Loads a project's run history, oldest first
+pub fn load_history(project_path: &PathBuf) -> Vec<HistoryEntry> {
+    let path = match history_file(project_path) {
+        Ok(res) => res,
+        Err(_) => return Vec::new(),
+    };
+
+    // there won't be a file until the project's tests have been run
+    if !path.exists() {
+        return Vec::new();
+    }
+
+    match fs::read_to_string(path) {
+        Ok(data) => serde_json::from_str(data.as_str()).unwrap_or_else(|err| {
+            warning!("Could not parse run history, using none: {}", err);
+            Vec::new()
+        }),
+        Err(err) => {
+            warning!("Could not open history file (load): {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Adds a run to the end of a project's history
+pub fn add_history_entry(project_path: &PathBuf, entry: HistoryEntry) {
+    let mut history = load_history(project_path);
+    history.push(entry);
+
+    if history.len() > MAX_HISTORY_ENTRIES {
+        history.drain(0..history.len() - MAX_HISTORY_ENTRIES);
+    }
+
+    let str = match serde_json::to_string(&history) {
+        Ok(res) => res,
+        Err(err) => {
+            warning!("Could not serialise run history: {}", err);
+            return;
+        }
+    };
+
+    match history_file(project_path) {
+        Ok(res) => {
+            if let Err(err) = fs::write(res, str) {
+                warning!("Could not write run history to file: {}", err);
+            }
+        }
+        Err(_) => {
+            warning!("Writing run history to file failed.");
+        }
+    }
+}
+
+/// Each project's snapshots are kept in their own folder, named after a hash of the project's path
+fn snapshot_folder(project_path: &PathBuf) -> Result<PathBuf, String> {
+    let folder = config_folder()?
+        .join("snapshots")
+        .join(project_hash(project_path));
+
+    if !folder.exists() {
+        if let Err(err) = fs::create_dir_all(folder.clone()) {
+            warning!("Failed when making snapshot directory at {:?}", folder);
+            return Err(err.to_string());
+        }
+    }
+
+    Ok(folder)
+}
+
+/// Loads a project's snapshots along with the file each is stored in, oldest first
+pub fn load_snapshots(project_path: &PathBuf) -> Vec<(PathBuf, Snapshot)> {
+    let folder = match snapshot_folder(project_path) {
+        Ok(res) => res,
+        Err(_) => return Vec::new(),
+    };
+
+    let entries = match fs::read_dir(folder) {
+        Ok(res) => res,
+        Err(err) => {
+            warning!("Could not open snapshot folder (load): {}", err);
+            return Vec::new();
+        }
+    };
+
+    let mut snapshots = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        match fs::read_to_string(&path) {
+            Ok(data) => match serde_json::from_str::<Snapshot>(data.as_str()) {
+                Ok(snapshot) => snapshots.push((path, snapshot)),
+                Err(err) => warning!("Could not parse snapshot {:?}, skipping it: {}", path, err),
+            },
+            Err(err) => warning!("Could not open snapshot file (load): {}", err),
+        }
+    }
+
+    snapshots.sort_by_key(|x| x.1.run.timestamp);
+    snapshots
+}
+
+/// Writes a snapshot to its own file in the project's snapshot folder
+pub fn save_snapshot(project_path: &PathBuf, snapshot: Snapshot) -> Result<(), String> {
+    let folder = snapshot_folder(project_path)?;
+
+    let str = match serde_json::to_string(&snapshot) {
+        Ok(res) => res,
+        Err(err) => return Err(format!("Could not serialise snapshot: {}", err)),
+    };
+
+    // more than one snapshot can be taken in the same second so a number is added until the name is free
+    let mut path = folder.join(format!("{}.json", snapshot.run.timestamp));
+    let mut suffix = 1;
+    while path.exists() {
+        path = folder.join(format!("{}-{}.json", snapshot.run.timestamp, suffix));
+        suffix += 1;
+    }
+
+    fs::write(path, str).map_err(|err| format!("Could not write snapshot to file: {}", err))
+}
+
+pub fn delete_snapshot(path: &PathBuf) {
+    if let Err(err) = fs::remove_file(path) {
+        warning!("Could not delete snapshot file: {}", err);
+    }
+}
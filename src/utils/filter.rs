@@ -0,0 +1,52 @@
+use crate::utils::fuzzy::fuzzy_score;
+use cargo_ptest::parse::{GeneralTestType, ParsedTest, Status};
+
+#[derive(Clone, PartialEq, Default)]
+/// Narrows down the tests shown in the test list, an empty list of statuses, types or files means none are filtered out
+pub struct TestFilter {
+    /// Fuzzy matched against each test's module path
+    pub query: String,
+    pub statuses: Vec<Status>,
+    pub test_types: Vec<GeneralTestType>,
+    /// The files the tests were run from, as given by test_file()
+    pub files: Vec<String>,
+}
+
+/// Adds the item to the list if it isn't in it, otherwise removes it
+fn toggle<T: PartialEq>(list: &mut Vec<T>, item: T) {
+    match list.iter().position(|x| *x == item) {
+        Some(res) => {
+            list.remove(res);
+        }
+        None => list.push(item),
+    }
+}
+
+impl TestFilter {
+    /// Whether any tests could be hidden by the filter
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+            || !self.statuses.is_empty()
+            || !self.test_types.is_empty()
+            || !self.files.is_empty()
+    }
+
+    pub fn matches(&self, file: &str, test: &ParsedTest) -> bool {
+        (self.statuses.is_empty() || self.statuses.contains(&test.status))
+            && (self.test_types.is_empty() || self.test_types.contains(&test.test_type))
+            && (self.files.is_empty() || self.files.iter().any(|x| x == file))
+            && fuzzy_score(&self.query, &test.module_path).is_some()
+    }
+
+    pub fn toggle_status(&mut self, status: Status) {
+        toggle(&mut self.statuses, status)
+    }
+
+    pub fn toggle_test_type(&mut self, test_type: GeneralTestType) {
+        toggle(&mut self.test_types, test_type)
+    }
+
+    pub fn toggle_file(&mut self, file: String) {
+        toggle(&mut self.files, file)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::state::test_id;
+use crate::utils::history::HistoryEntry;
+use cargo_ptest::parse::{ParsedTestGroup, Status};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Default)]
+/// How consistently a test has passed or failed across the project's run history
+pub struct Flakiness {
+    /// How many times the test passed or failed, ignored runs aren't counted
+    pub runs: u32,
+    pub passed: u32,
+    pub failed: u32,
+    /// How many pairs of back to back runs were made against the same source
+    pub comparisons: u32,
+    /// How many of those pairs had a different result, e.g. passing then failing
+    pub flips: u32,
+}
+
+impl Flakiness {
+    /// A test is flaky if its result has changed without the source changing
+    pub fn is_flaky(&self) -> bool {
+        self.flips > 0
+    }
+
+    /// How often the result changed between runs of the same source, from 0.0 to 1.0
+    pub fn score(&self) -> f64 {
+        if self.comparisons == 0 {
+            0.0
+        } else {
+            self.flips as f64 / self.comparisons as f64
+        }
+    }
+}
+
+/// Works out the flakiness of every test in a project's history, keyed by test_id()
+pub fn flakiness(history: &[HistoryEntry]) -> HashMap<String, Flakiness> {
+    let mut scores: HashMap<String, Flakiness> = HashMap::new();
+    // the source state and result of each test's previous run
+    let mut previous: HashMap<String, (Option<String>, Status)> = HashMap::new();
+
+    for entry in history {
+        for (id, status) in entry.statuses() {
+            if status == Status::Ignored {
+                continue;
+            }
+
+            let score = scores.entry(id.clone()).or_default();
+            score.runs += 1;
+            if status == Status::Passed {
+                score.passed += 1
+            } else {
+                score.failed += 1
+            }
+
+            // runs without a source state aren't in a git repo so there's no way to tell if the code changed
+            if let Some((Some(source), last_status)) = previous.get(&id)
+                && entry.source_state.as_ref() == Some(source)
+            {
+                score.comparisons += 1;
+                if *last_status != status {
+                    score.flips += 1
+                }
+            }
+
+            previous.insert(id, (entry.source_state.clone(), status));
+        }
+    }
+
+    scores
+}
+
+#[derive(Clone)]
+/// The results of running the same tests several times in a row
+pub struct RepeatRun {
+    /// Only the runs started with this id are counted, so a run started some other way isn't taken as one of the repeats
+    pub id: u32,
+    /// How many times the tests will be run
+    pub total: u32,
+    pub completed: u32,
+    /// How many times each test passed and failed, keyed by test_id()
+    pub results: HashMap<String, (u32, u32)>,
+}
+
+impl RepeatRun {
+    pub fn new(id: u32, total: u32) -> Self {
+        Self {
+            id,
+            total,
+            completed: 0,
+            results: HashMap::new(),
+        }
+    }
+
+    /// Adds the results of one of the runs, runs after the last one aren't part of the repeated run so are ignored
+    pub fn record(&mut self, groups: &[ParsedTestGroup]) {
+        if self.is_finished() {
+            return;
+        }
+        self.completed += 1;
+
+        for test in groups.iter().flat_map(|x| x.tests.iter()) {
+            let result = self.results.entry(test_id(test)).or_default();
+            match test.status {
+                Status::Passed => result.0 += 1,
+                Status::Failed => result.1 += 1,
+                Status::Ignored => {}
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.completed >= self.total
+    }
+
+    /// The tests that both passed and failed during the repeated runs
+    pub fn inconsistent(&self) -> usize {
+        self.results
+            .values()
+            .filter(|(passed, failed)| *passed > 0 && *failed > 0)
+            .count()
+    }
+}
@@ -0,0 +1,42 @@
+/// Characters that split words in module paths, file paths and names
+const WORD_SEPARATORS: [char; 7] = [':', '_', '/', '\\', '-', '.', ' '];
+
+/// Scores how well the query matches the text, ignoring case. None if any word of the query doesn't match.
+/// Each word of the query has to appear in the text in order but can have gaps, e.g. "tstfl" matches "test_fail".
+/// Higher scores are better, characters that follow the previous match or start a word score the most.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.iter().flat_map(|x| x.to_lowercase()).collect();
+    // lowercasing can change the length of some characters, in which case word starts can't be lined up
+    let lined_up = lower.len() == text.len();
+    let mut score = 0;
+
+    for word in query.split_whitespace() {
+        let mut position = 0;
+        let mut previous: Option<usize> = None;
+
+        for character in word.chars().flat_map(|x| x.to_lowercase()) {
+            let found = lower[position..].iter().position(|x| *x == character)? + position;
+
+            score += 1;
+            if previous.is_some_and(|x| x + 1 == found) {
+                score += 5
+            } else if let Some(previous) = previous {
+                // long gaps between matched characters make the match worse
+                score -= (found - previous).min(5) as i64
+            }
+
+            let word_start = found == 0
+                || WORD_SEPARATORS.contains(&lower[found - 1])
+                || (lined_up && text[found].is_uppercase() && text[found - 1].is_lowercase());
+            if word_start {
+                score += 8
+            }
+
+            previous = Some(found);
+            position = found + 1;
+        }
+    }
+
+    Some(score)
+}
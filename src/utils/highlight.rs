@@ -0,0 +1,299 @@
+use std::ops::Range;
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    /// e.g. #[test]
+    Attribute,
+    /// The name of a macro call including the !
+    Macro,
+    /// Names starting with a capital letter
+    Type,
+    /// Names followed by an opening bracket
+    Function,
+    Lifetime,
+}
+
+/// A part of a line that is coloured differently to plain code, the range is in bytes
+pub struct Token {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+/// What a line ended inside of, which carries on to the start of the next line
+enum Carry {
+    None,
+    /// How many block comments deep the line ended
+    BlockComment(usize),
+    String,
+    /// How many #s the raw string was opened with
+    RawString(usize),
+}
+
+/// Finds the end of a block comment, returning where it ends and how many comments are still open
+fn scan_block_comment(chars: &[(usize, char)], start: usize, depth: usize) -> (usize, usize) {
+    let mut depth = depth;
+    let mut i = start;
+
+    while i < chars.len() {
+        let next = chars.get(i + 1).map(|x| x.1);
+        match (chars[i].1, next) {
+            ('/', Some('*')) => {
+                depth += 1;
+                i += 2;
+            }
+            ('*', Some('/')) => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return (i, 0);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    (i, depth)
+}
+
+/// Finds the end of a string, returning where it ends and whether it was closed on this line
+fn scan_string(chars: &[(usize, char)], start: usize) -> (usize, bool) {
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i].1 {
+            '\\' => i += 2,
+            '"' => return (i + 1, true),
+            _ => i += 1,
+        }
+    }
+
+    (chars.len(), false)
+}
+
+/// Finds the end of a raw string, which is a " followed by as many #s as it was opened with
+fn scan_raw_string(chars: &[(usize, char)], start: usize, hashes: usize) -> (usize, bool) {
+    let mut i = start;
+
+    while i < chars.len() {
+        if chars[i].1 == '"' && (1..=hashes).all(|x| chars.get(i + x).is_some_and(|c| c.1 == '#')) {
+            return (i + 1 + hashes, true);
+        }
+        i += 1;
+    }
+
+    (chars.len(), false)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Splits a line of rust into tokens, carrying on from whatever the previous line ended inside of
+fn highlight_line(line: &str, carry: &mut Carry) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let offset = |i: usize| chars.get(i).map_or(line.len(), |x| x.0);
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let push = |tokens: &mut Vec<Token>, start: usize, end: usize, kind: TokenKind| {
+        if end > start {
+            tokens.push(Token {
+                range: offset(start)..offset(end),
+                kind,
+            })
+        }
+    };
+
+    match *carry {
+        Carry::BlockComment(depth) => {
+            let (end, depth) = scan_block_comment(&chars, 0, depth);
+            push(&mut tokens, 0, end, TokenKind::Comment);
+            *carry = if depth > 0 {
+                Carry::BlockComment(depth)
+            } else {
+                Carry::None
+            };
+            i = end;
+        }
+        Carry::String => {
+            let (end, closed) = scan_string(&chars, 0);
+            push(&mut tokens, 0, end, TokenKind::String);
+            *carry = if closed { Carry::None } else { Carry::String };
+            i = end;
+        }
+        Carry::RawString(hashes) => {
+            let (end, closed) = scan_raw_string(&chars, 0, hashes);
+            push(&mut tokens, 0, end, TokenKind::String);
+            *carry = if closed {
+                Carry::None
+            } else {
+                Carry::RawString(hashes)
+            };
+            i = end;
+        }
+        Carry::None => {}
+    }
+
+    while i < chars.len() {
+        let c = chars[i].1;
+        let next = chars.get(i + 1).map(|x| x.1);
+
+        match c {
+            '/' if next == Some('/') => {
+                push(&mut tokens, i, chars.len(), TokenKind::Comment);
+                break;
+            }
+            '/' if next == Some('*') => {
+                let (end, depth) = scan_block_comment(&chars, i + 2, 1);
+                push(&mut tokens, i, end, TokenKind::Comment);
+                if depth > 0 {
+                    *carry = Carry::BlockComment(depth)
+                }
+                i = end;
+            }
+            '"' => {
+                let (end, closed) = scan_string(&chars, i + 1);
+                push(&mut tokens, i, end, TokenKind::String);
+                if !closed {
+                    *carry = Carry::String
+                }
+                i = end;
+            }
+            'b' if next == Some('"') => {
+                let (end, closed) = scan_string(&chars, i + 2);
+                push(&mut tokens, i, end, TokenKind::String);
+                if !closed {
+                    *carry = Carry::String
+                }
+                i = end;
+            }
+            'r' | 'b'
+                if {
+                    // r"..", r#".."# and br".."
+                    let start = if c == 'b' && next == Some('r') {
+                        i + 2
+                    } else {
+                        i + 1
+                    };
+                    (c == 'r' || next == Some('r'))
+                        && chars[start..]
+                            .iter()
+                            .find(|x| x.1 != '#')
+                            .is_some_and(|x| x.1 == '"')
+                        && (i == 0 || !is_ident_char(chars[i - 1].1))
+                } =>
+            {
+                let start = if c == 'b' { i + 2 } else { i + 1 };
+                let hashes = chars[start..].iter().take_while(|x| x.1 == '#').count();
+                let (end, closed) = scan_raw_string(&chars, start + hashes + 1, hashes);
+                push(&mut tokens, i, end, TokenKind::String);
+                if !closed {
+                    *carry = Carry::RawString(hashes)
+                }
+                i = end;
+            }
+            '\'' => {
+                // either a char e.g. 'a' or '\n', or a lifetime e.g. 'a
+                let end = if next == Some('\\') {
+                    // the escaped character is skipped in case it is a quote
+                    chars
+                        .get(i + 3..)
+                        .and_then(|rest| rest.iter().position(|x| x.1 == '\''))
+                        .map_or(chars.len(), |x| i + 4 + x)
+                } else if chars.get(i + 2).is_some_and(|x| x.1 == '\'') {
+                    i + 3
+                } else {
+                    let name = chars[i + 1..]
+                        .iter()
+                        .take_while(|x| is_ident_char(x.1))
+                        .count();
+                    push(&mut tokens, i, i + 1 + name, TokenKind::Lifetime);
+                    i += 1 + name;
+                    continue;
+                };
+                push(&mut tokens, i, end, TokenKind::String);
+                i = end;
+            }
+            '#' if next == Some('[')
+                || (next == Some('!') && chars.get(i + 2).is_some_and(|x| x.1 == '[')) =>
+            {
+                let mut depth = 0;
+                let mut end = chars.len();
+                for (x, (_, character)) in chars.iter().enumerate().skip(i) {
+                    match character {
+                        '[' => depth += 1,
+                        ']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = x + 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                push(&mut tokens, i, end, TokenKind::Attribute);
+                i = end;
+            }
+            _ if c.is_ascii_digit() => {
+                let mut end = i;
+                while end < chars.len() {
+                    let character = chars[end].1;
+                    // stops at ranges e.g. 0..10
+                    let range = character == '.' && chars.get(end + 1).is_some_and(|x| x.1 == '.');
+                    if range || !(is_ident_char(character) || character == '.') {
+                        break;
+                    }
+                    end += 1;
+                }
+                push(&mut tokens, i, end, TokenKind::Number);
+                i = end;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let end = i + chars[i..].iter().take_while(|x| is_ident_char(x.1)).count();
+                let word = &line[offset(i)..offset(end)];
+                let after = chars[end..].iter().find(|x| x.1 != ' ').map(|x| x.1);
+
+                if chars.get(end).is_some_and(|x| x.1 == '!')
+                    && chars.get(end + 1).is_none_or(|x| x.1 != '=')
+                {
+                    push(&mut tokens, i, end + 1, TokenKind::Macro);
+                    i = end + 1;
+                    continue;
+                } else if KEYWORDS.contains(&word) {
+                    push(&mut tokens, i, end, TokenKind::Keyword)
+                } else if after == Some('(') {
+                    push(&mut tokens, i, end, TokenKind::Function)
+                } else if c.is_uppercase() {
+                    push(&mut tokens, i, end, TokenKind::Type)
+                }
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    tokens
+}
+
+/// Highlights each line of a rust file, strings and comments can carry on over multiple lines
+pub fn highlight_rust(lines: &[String]) -> Vec<Vec<Token>> {
+    let mut carry = Carry::None;
+
+    lines
+        .iter()
+        .map(|x| highlight_line(x, &mut carry))
+        .collect()
+}
@@ -0,0 +1,329 @@
+use crate::state::{RunArgs, test_id};
+use crate::utils::utils::stable_hash;
+use cargo_ptest::parse::{
+    AggregateSummary, GeneralTestType, ParsedTest, ParsedTestGroup, Status, Summary,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// cargo_ptest's types can't be serialised, so these mirror them for saving to disk
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SavedStatus {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+impl From<Status> for SavedStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Passed => SavedStatus::Passed,
+            Status::Failed => SavedStatus::Failed,
+            Status::Ignored => SavedStatus::Ignored,
+        }
+    }
+}
+
+impl From<SavedStatus> for Status {
+    fn from(status: SavedStatus) -> Self {
+        match status {
+            SavedStatus::Passed => Status::Passed,
+            SavedStatus::Failed => Status::Failed,
+            SavedStatus::Ignored => Status::Ignored,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedTest {
+    /// Whether this is a doc test
+    pub doc: bool,
+    pub module_path: String,
+    pub status: SavedStatus,
+    pub file_path: Option<String>,
+    pub note: Option<String>,
+    pub error_reason: Option<String>,
+    pub ignore_reason: Option<String>,
+}
+
+impl From<ParsedTest> for SavedTest {
+    fn from(test: ParsedTest) -> Self {
+        Self {
+            doc: test.test_type == GeneralTestType::Doc,
+            module_path: test.module_path,
+            status: test.status.into(),
+            file_path: test.file_path,
+            note: test.note,
+            error_reason: test.error_reason,
+            ignore_reason: test.ignore_reason,
+        }
+    }
+}
+
+impl From<SavedTest> for ParsedTest {
+    fn from(test: SavedTest) -> Self {
+        ParsedTest {
+            test_type: if test.doc {
+                GeneralTestType::Doc
+            } else {
+                GeneralTestType::Normal
+            },
+            module_path: test.module_path,
+            status: test.status.into(),
+            file_path: test.file_path,
+            note: test.note,
+            error_reason: test.error_reason,
+            ignore_reason: test.ignore_reason,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedSummary {
+    pub status: SavedStatus,
+    pub passed: u32,
+    pub failed: u32,
+    pub ignored: u32,
+    pub measured: u32,
+    pub filtered: u32,
+    pub time: f64,
+}
+
+impl From<Summary> for SavedSummary {
+    fn from(summary: Summary) -> Self {
+        Self {
+            status: summary.status.into(),
+            passed: summary.passed,
+            failed: summary.failed,
+            ignored: summary.ignored,
+            measured: summary.measured,
+            filtered: summary.filtered,
+            time: summary.time,
+        }
+    }
+}
+
+impl From<SavedSummary> for Summary {
+    fn from(summary: SavedSummary) -> Self {
+        Summary {
+            status: summary.status.into(),
+            passed: summary.passed,
+            failed: summary.failed,
+            ignored: summary.ignored,
+            measured: summary.measured,
+            filtered: summary.filtered,
+            time: summary.time,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedTestGroup {
+    pub crate_name: String,
+    pub file_path: Vec<String>,
+    pub tests: Vec<SavedTest>,
+    pub summary: Option<SavedSummary>,
+}
+
+impl From<ParsedTestGroup> for SavedTestGroup {
+    fn from(group: ParsedTestGroup) -> Self {
+        Self {
+            crate_name: group.crate_name,
+            file_path: group.file_path,
+            tests: group.tests.into_iter().map(|x| x.into()).collect(),
+            summary: group.summary.map(|x| x.into()),
+        }
+    }
+}
+
+impl From<SavedTestGroup> for ParsedTestGroup {
+    fn from(group: SavedTestGroup) -> Self {
+        ParsedTestGroup {
+            crate_name: group.crate_name,
+            file_path: group.file_path,
+            tests: group.tests.into_iter().map(|x| x.into()).collect(),
+            summary: group.summary.map(|x| x.into()),
+        }
+    }
+}
+
+// HISTORY
+
+#[derive(Clone, Serialize, Deserialize)]
+/// A single completed test run
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch when the run finished
+    pub timestamp: u64,
+    pub run_args: RunArgs,
+    /// The profile the run used, None if it used the project's own run args
+    pub profile: Option<String>,
+    /// Which tests were run, e.g. "all tests" or "module x"
+    pub scope: String,
+    /// Whether only some of the tests were run
+    pub partial: bool,
+    /// The short hash of the commit checked out when the run started, None if the project isn't a git repo
+    pub git_commit: Option<String>,
+    /// Whether there were uncommitted changes when the run started
+    pub git_dirty: bool,
+    /// Identifies the state of the project's source, two runs with the same value were run against the same code
+    pub source_state: Option<String>,
+    pub groups: Vec<SavedTestGroup>,
+    /// Test durations in seconds keyed by test_id(), only tests that reported a time are included
+    pub durations: HashMap<String, f64>,
+    pub summary: SavedSummary,
+}
+
+#[derive(Clone, Default)]
+/// The state of a project's code, read when a run starts so edits made while it runs aren't recorded as what was tested
+pub struct GitState {
+    pub commit: Option<String>,
+    pub dirty: bool,
+    pub source_state: Option<String>,
+}
+
+impl GitState {
+    /// Runs git so it shouldn't be called on the main thread
+    pub fn read(dir: &Path) -> Self {
+        Self {
+            commit: git_commit(dir),
+            dirty: git_dirty(dir),
+            source_state: source_state(dir),
+        }
+    }
+}
+
+impl HistoryEntry {
+    /// Creates an entry for a run that has just finished, the git state should be from when the run started
+    pub fn new(
+        git_state: GitState,
+        run_args: RunArgs,
+        profile: Option<String>,
+        scope: String,
+        partial: bool,
+        groups: Vec<ParsedTestGroup>,
+        durations: HashMap<String, f64>,
+    ) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |x| x.as_secs()),
+            run_args,
+            profile,
+            scope,
+            partial,
+            git_commit: git_state.commit,
+            git_dirty: git_state.dirty,
+            source_state: git_state.source_state,
+            summary: groups.clone().aggregate_summary().into(),
+            groups: groups.into_iter().map(|x| x.into()).collect(),
+            durations,
+        }
+    }
+
+    pub fn tests(&self) -> Vec<ParsedTestGroup> {
+        self.groups.clone().into_iter().map(|x| x.into()).collect()
+    }
+
+    /// Each test's status keyed by test_id()
+    pub fn statuses(&self) -> HashMap<String, Status> {
+        self.tests()
+            .into_iter()
+            .flat_map(|x| x.tests)
+            .map(|x| (test_id(&x), x.status))
+            .collect()
+    }
+
+    /// A one line description of the run, used in the history panel
+    pub fn description(&self) -> String {
+        format!(
+            "{} - {} passed, {} failed, {} ignored - {}{}",
+            format_age(self.timestamp),
+            self.summary.passed,
+            self.summary.failed,
+            self.summary.ignored,
+            self.scope,
+            match &self.git_commit {
+                Some(commit) => format!(" - {}{}", commit, if self.git_dirty { "*" } else { "" }),
+                None => String::new(),
+            }
+        )
+    }
+}
+
+/// When each test's status last changed from one run to the next, keyed by test_id().
+/// A test's first run counts as a change so tests that have only been run once are included.
+pub fn status_changes(history: &[HistoryEntry]) -> HashMap<String, u64> {
+    let mut changes: HashMap<String, u64> = HashMap::new();
+    let mut previous: HashMap<String, Status> = HashMap::new();
+
+    for entry in history {
+        for (id, status) in entry.statuses() {
+            if previous.get(&id) != Some(&status) {
+                changes.insert(id.clone(), entry.timestamp);
+            }
+            previous.insert(id, status);
+        }
+    }
+
+    changes
+}
+
+/// Runs a git command in the project folder, returning its output if it succeeded
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+pub fn git_commit(dir: &Path) -> Option<String> {
+    git(dir, &["rev-parse", "--short", "HEAD"])
+}
+
+pub fn git_dirty(dir: &Path) -> bool {
+    git(dir, &["status", "--porcelain"]).is_some_and(|x| !x.is_empty())
+}
+
+/// The current commit along with a hash of any uncommitted changes, untracked files are only included by name
+pub fn source_state(dir: &Path) -> Option<String> {
+    let commit = git_commit(dir)?;
+    let changes = git(dir, &["diff", "HEAD"])?;
+    let untracked = git(dir, &["ls-files", "--others", "--exclude-standard"])?;
+
+    let hash = stable_hash(format!("{}\0{}", changes, untracked).as_bytes());
+
+    Some(format!("{}-{:x}", commit, hash))
+}
+
+/// How long ago a timestamp was, e.g. "5 minutes ago"
+pub fn format_age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let seconds = now.saturating_sub(timestamp);
+
+    let (amount, unit) = match seconds {
+        0..60 => return String::from("just now"),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+
+    format!(
+        "{} {}{} ago",
+        amount,
+        unit,
+        if amount == 1 { "" } else { "s" }
+    )
+}
@@ -1,5 +1,18 @@
+pub(crate) mod ansi;
 pub(crate) mod assets;
+pub(crate) mod editor;
 pub(crate) mod file;
+pub(crate) mod filter;
+pub(crate) mod flaky;
+pub(crate) mod fuzzy;
+pub(crate) mod highlight;
+pub(crate) mod history;
 pub(crate) mod logger;
+pub(crate) mod panic;
+pub(crate) mod runner;
+pub(crate) mod snapshot;
+pub(crate) mod source;
+pub(crate) mod test_tree;
 mod tests;
 pub(crate) mod utils;
+pub(crate) mod watcher;
@@ -0,0 +1,448 @@
+use crate::utils::source::{PanicLocation, panic_location};
+use regex::Regex;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// The most characters compared one by one when diffing, anything bigger has its whole changed middle marked instead
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AssertionKind {
+    /// assert_eq!
+    Equal,
+    /// assert_ne!
+    NotEqual,
+}
+
+#[derive(Clone)]
+/// The two values compared by a failed assert_eq! or assert_ne!
+pub struct Assertion {
+    pub kind: AssertionKind,
+    pub left: String,
+    pub right: String,
+}
+
+/// A frame in a backtrace e.g. "3: apollo::tests::fails", full backtraces also include the address e.g. "3: 0x55d1c2a4b0f1 - apollo::tests::fails"
+static FRAME_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d+):\s+(?:0x[0-9a-fA-F]+ - )?(.+)$").unwrap());
+
+/// Functions from the standard library and the test harness, which are folded away with dependencies in backtraces
+const STD_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "test::",
+    "<std::",
+    "<core::",
+    "<alloc::",
+    "rust_begin_unwind",
+    "__rust",
+    "__libc",
+    "_start",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FrameKind {
+    /// Code from the project being tested
+    Project,
+    /// Code from a crate the project depends on
+    Dependency,
+    /// std, core and anything else from the toolchain or the system
+    Std,
+}
+
+#[derive(Clone)]
+/// A function in a backtrace, the first frame is where the panic started
+pub struct Frame {
+    pub index: usize,
+    pub function: String,
+    /// Where in the function the frame is, this is missing when there is no debug info for it
+    pub location: Option<PanicLocation>,
+}
+
+impl Frame {
+    /// Whether the frame is the project's own code, a dependency's or the standard library's
+    pub fn kind(&self, project_path: &Path) -> FrameKind {
+        match &self.location {
+            // the toolchain's source is either at /rustc/<commit>/ or in the rust-src component under rustlib
+            Some(location)
+                if location.file.starts_with("/rustc/") || location.file.contains("/rustlib/") =>
+            {
+                FrameKind::Std
+            }
+            // cargo gives rustc paths relative to the workspace for its members and absolute paths for everything else
+            Some(location) => {
+                let file = Path::new(&location.file);
+                if file.is_relative() || file.starts_with(project_path) {
+                    FrameKind::Project
+                } else if STD_PREFIXES.iter().any(|x| self.function.starts_with(x)) {
+                    FrameKind::Std
+                } else {
+                    FrameKind::Dependency
+                }
+            }
+            // frames without debug info are usually from the system, e.g. the thread starting
+            None => FrameKind::Std,
+        }
+    }
+}
+
+/// A test's panic split into its parts
+pub struct Panic {
+    /// The name of the thread that panicked, which is the test's name unless it started its own threads
+    pub thread: Option<String>,
+    /// What was passed to panic!(), or the assertion message for failed assertions
+    pub message: String,
+    pub location: Option<PanicLocation>,
+    pub assertion: Option<Assertion>,
+    /// Only printed when the test was run with RUST_BACKTRACE set
+    pub backtrace: Vec<Frame>,
+    /// Anything printed after the panic, e.g. the note about backtraces
+    pub notes: Vec<String>,
+}
+
+/// Removes the backticks and commas older versions of rust put around assertion values, e.g. `1`,
+fn assertion_value(value: &str) -> String {
+    let value = value.trim().trim_end_matches(',');
+
+    match (value.find('`'), value.rfind('`')) {
+        (Some(start), Some(end)) if value.starts_with('`') && end > start => {
+            value[start + 1..end].to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Splits the error given for a failed test into its parts, None if it doesn't contain a panic e.g. a should_panic test that didn't
+pub fn parse_panic(error: &str) -> Option<Panic> {
+    let lines: Vec<&str> = error.lines().collect();
+    let start = lines.iter().position(|x| x.contains("panicked at"))?;
+    let header = lines[start];
+
+    let thread = header
+        .strip_prefix("thread '")
+        .and_then(|x| x.split_once('\''))
+        .map(|x| x.0.to_string());
+    // older versions of rust put the location at the end of the message
+    let location = panic_location(header).or_else(|| panic_location(error));
+
+    // since rust 1.73 the message is on the lines after the location, before that it was in quotes on the same line
+    let mut message = Vec::new();
+    if let Some((_, rest)) = header.split_once("panicked at '") {
+        message.push(rest.rsplit_once("', ").map_or(rest, |x| x.0));
+    }
+
+    let mut left = None;
+    let mut right = None;
+    let mut backtrace: Vec<Frame> = Vec::new();
+    let mut in_backtrace = false;
+    let mut notes = Vec::new();
+
+    for line in &lines[start + 1..] {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("stack backtrace:") {
+            in_backtrace = true
+        } else if in_backtrace && let Some(capture) = FRAME_LINE.captures(trimmed) {
+            backtrace.push(Frame {
+                index: capture[1].parse().unwrap_or(backtrace.len()),
+                function: capture[2].to_string(),
+                location: None,
+            })
+        } else if in_backtrace && trimmed.starts_with("at ") {
+            if let Some(frame) = backtrace.last_mut() {
+                frame.location = panic_location(trimmed)
+            }
+        } else if let Some(value) = trimmed.strip_prefix("left:") {
+            left = Some(assertion_value(value))
+        } else if let Some(value) = trimmed.strip_prefix("right:") {
+            right = Some(assertion_value(value))
+        } else if trimmed.starts_with("note:") || !notes.is_empty() || in_backtrace {
+            in_backtrace = false;
+            notes.push(trimmed.to_string())
+        } else if left.is_none() {
+            message.push(line)
+        }
+    }
+
+    let message = message.join("\n").trim().to_string();
+
+    let assertion = match (left, right) {
+        (Some(left), Some(right)) => Some(Assertion {
+            kind: if message.contains("left != right") {
+                AssertionKind::NotEqual
+            } else {
+                AssertionKind::Equal
+            },
+            left,
+            right,
+        }),
+        _ => None,
+    };
+
+    Some(Panic {
+        thread,
+        message,
+        location,
+        assertion,
+        backtrace,
+        notes,
+    })
+}
+
+/// Merges neighbouring changed characters into byte ranges
+fn changed_ranges(text: &str, changed: &[bool]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+
+    for ((offset, character), changed) in text.char_indices().zip(changed) {
+        if !changed {
+            continue;
+        }
+        let end = offset + character.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == offset => last.end = end,
+            _ => ranges.push(offset..end),
+        }
+    }
+
+    ranges
+}
+
+/// The byte ranges of the characters that differ between two values, for the left value then the right value
+pub fn diff_chars(left: &str, right: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let a: Vec<char> = left.chars().collect();
+    let b: Vec<char> = right.chars().collect();
+
+    // the same start and end are skipped so only the part that changed has to be compared
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_middle = &a[prefix..a.len() - suffix];
+    let b_middle = &b[prefix..b.len() - suffix];
+
+    let mut a_changed = vec![false; a.len()];
+    let mut b_changed = vec![false; b.len()];
+
+    if a_middle.len() * b_middle.len() > MAX_DIFF_CELLS {
+        a_changed[prefix..a.len() - suffix].fill(true);
+        b_changed[prefix..b.len() - suffix].fill(true);
+    } else {
+        // longest common subsequence of the middles, anything not in it has changed
+        let width = b_middle.len() + 1;
+        let mut lengths = vec![0u32; (a_middle.len() + 1) * width];
+        for i in (0..a_middle.len()).rev() {
+            for j in (0..b_middle.len()).rev() {
+                lengths[i * width + j] = if a_middle[i] == b_middle[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a_middle.len() && j < b_middle.len() {
+            if a_middle[i] == b_middle[j] {
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                a_changed[prefix + i] = true;
+                i += 1;
+            } else {
+                b_changed[prefix + j] = true;
+                j += 1;
+            }
+        }
+        a_changed[prefix + i..a.len() - suffix].fill(true);
+        b_changed[prefix + j..b.len() - suffix].fill(true);
+    }
+
+    (
+        changed_ranges(left, &a_changed),
+        changed_ranges(right, &b_changed),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of the ranges, to make failures easier to read
+    fn texts(value: &str, ranges: &[Range<usize>]) -> Vec<String> {
+        ranges
+            .iter()
+            .map(|x| value[x.clone()].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn quoted_message() {
+        let panic =
+            parse_panic("thread 'tests::panics' panicked at 'boom', src/lib.rs:34:9\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace").unwrap();
+
+        assert_eq!(panic.thread.as_deref(), Some("tests::panics"));
+        assert_eq!(panic.message, "boom");
+        let location = panic.location.unwrap();
+        assert_eq!(
+            (location.file.as_str(), location.line, location.column),
+            ("src/lib.rs", 34, 9)
+        );
+        assert!(panic.assertion.is_none());
+        assert_eq!(panic.notes.len(), 1);
+    }
+
+    #[test]
+    fn quoted_assertion() {
+        let error = "thread 'tests::fails' panicked at 'assertion failed: `(left != right)`
+  left: `\"a\"`,
+ right: `\"a\"`', src/lib.rs:29:9
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace";
+        let panic = parse_panic(error).unwrap();
+
+        assert_eq!(panic.message, "assertion failed: `(left != right)`");
+        assert_eq!(panic.location.unwrap().line, 29);
+        let assertion = panic.assertion.unwrap();
+        assert!(assertion.kind == AssertionKind::NotEqual);
+        assert_eq!(assertion.left, "\"a\"");
+        assert_eq!(assertion.right, "\"a\"");
+    }
+
+    #[test]
+    fn message_after_location() {
+        let error = "before fail
+
+thread 'tests::fails' (2231) panicked at src/lib.rs:29:9:
+first line
+second line
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace";
+        let panic = parse_panic(error).unwrap();
+
+        assert_eq!(panic.thread.as_deref(), Some("tests::fails"));
+        assert_eq!(panic.message, "first line\nsecond line");
+        assert_eq!(panic.location.unwrap().column, 9);
+        assert!(panic.assertion.is_none());
+        assert_eq!(
+            panic.notes,
+            vec!["note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace"]
+        );
+    }
+
+    #[test]
+    fn assertion_after_location() {
+        let error = "thread 'tests::fails' (2231) panicked at src/lib.rs:29:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace";
+        let panic = parse_panic(error).unwrap();
+
+        assert_eq!(panic.message, "assertion `left == right` failed");
+        let assertion = panic.assertion.unwrap();
+        assert!(assertion.kind == AssertionKind::Equal);
+        assert_eq!(
+            (assertion.left.as_str(), assertion.right.as_str()),
+            ("1", "2")
+        );
+    }
+
+    #[test]
+    fn backtrace() {
+        let error = "thread 'tests::fails' (3738) panicked at src/lib.rs:29:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+stack backtrace:
+   0: __rustc::rust_begin_unwind
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/std/src/panicking.rs:689:5
+   1: core::panicking::panic_fmt
+             at /rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/panicking.rs:80:14
+   4: dt::tests::fails
+             at ./src/lib.rs:29:9
+   5: dt::tests::fails::{{closure}}
+             at ./src/lib.rs:27:15
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.";
+        let panic = parse_panic(error).unwrap();
+        let project = Path::new("/home/user/dt");
+
+        assert_eq!(panic.message, "assertion `left == right` failed");
+        assert!(panic.assertion.is_some());
+        assert_eq!(
+            panic.backtrace.iter().map(|x| x.index).collect::<Vec<_>>(),
+            vec![0, 1, 4, 5]
+        );
+        assert_eq!(panic.backtrace[2].function, "dt::tests::fails");
+        assert_eq!(
+            panic.backtrace[2].location.as_ref().unwrap().file,
+            "./src/lib.rs"
+        );
+        assert!(panic.backtrace[0].kind(project) == FrameKind::Std);
+        assert!(panic.backtrace[2].kind(project) == FrameKind::Project);
+        assert_eq!(panic.notes.len(), 1);
+    }
+
+    #[test]
+    fn full_backtrace_addresses() {
+        let error = "thread 'main' panicked at src/main.rs:2:5:
+boom
+stack backtrace:
+   0:     0x55d1c2a4b0f1 - std::backtrace_rs::backtrace::libunwind::trace
+   3:     0x55d1c2a4b2f2 - app::main
+                               at /home/user/app/src/main.rs:2:5";
+        let panic = parse_panic(error).unwrap();
+
+        assert_eq!(panic.backtrace[1].function, "app::main");
+        assert!(panic.backtrace[1].kind(Path::new("/home/user/app")) == FrameKind::Project);
+        assert!(panic.backtrace[0].location.is_none());
+    }
+
+    #[test]
+    fn no_panic() {
+        assert!(parse_panic("note: test did not panic as expected").is_none());
+    }
+
+    #[test]
+    fn diff_same() {
+        let (left, right) = diff_chars("hello", "hello");
+        assert!(left.is_empty() && right.is_empty());
+    }
+
+    #[test]
+    fn diff_prefix_and_suffix() {
+        let (left, right) = diff_chars("value: 1", "value: 12");
+        assert!(left.is_empty());
+        assert_eq!(texts("value: 12", &right), vec!["2"]);
+
+        let (left, right) = diff_chars("abc", "xbc");
+        assert_eq!(texts("abc", &left), vec!["a"]);
+        assert_eq!(texts("xbc", &right), vec!["x"]);
+    }
+
+    #[test]
+    fn diff_middle() {
+        let (left, right) = diff_chars("[1, 2, 3, 4]", "[1, 5, 3, 6, 4]");
+        assert_eq!(texts("[1, 2, 3, 4]", &left), vec!["2"]);
+        assert_eq!(texts("[1, 5, 3, 6, 4]", &right), vec!["5", ", 6"]);
+    }
+
+    #[test]
+    fn diff_multibyte() {
+        let (left, right) = diff_chars("café au lait", "cafè au lait");
+        assert_eq!(left, vec![3..5]);
+        assert_eq!(right, vec![3..5]);
+    }
+
+    #[test]
+    fn diff_large_values() {
+        let left = format!("<{}>", "a".repeat(1500));
+        let right = format!("<{}>", "b".repeat(1000));
+        let (left_ranges, right_ranges) = diff_chars(&left, &right);
+
+        assert_eq!(left_ranges, vec![1..1501]);
+        assert_eq!(right_ranges, vec![1..1001]);
+    }
+}
@@ -0,0 +1,901 @@
+use crate::state::{doc_test_path, split_doc_test_path, test_id};
+use crate::utils::logger::warning;
+use cargo_ptest::config::Config;
+use cargo_ptest::parse::{GeneralTestType, ParsedTest, ParsedTestGroup, Status, parse};
+use cargo_ptest::run::RunError;
+use futures::channel::mpsc::UnboundedSender;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// These args make the output of cargo test unpredictable so they are removed, this matches the filter in cargo_ptest::run::run()
+/// except for --nocapture, which is an option in the run settings
+const FILTERED_ARGS: [&str; 5] = [
+    "-v",
+    "--verbose",
+    "--color=always",
+    "--color=auto",
+    "--color=never",
+];
+
+/// Sent from the test runner while cargo test is still running
+pub enum RunEvent {
+    /// A new block of tests has started, e.g. "running 12 tests"
+    GroupStarted,
+    /// A single test has finished running, with how long it took in seconds if --report-time was used
+    TestFinished(ParsedTest, Option<f64>),
+}
+
+#[derive(Default)]
+/// The results of a finished run
+pub struct RunOutput {
+    pub groups: Vec<ParsedTestGroup>,
+    /// How long each test took in seconds keyed by test_id(), empty unless --report-time was used
+    pub durations: HashMap<String, f64>,
+    /// What each test printed keyed by test_id(), only tests that printed something are included
+    pub outputs: HashMap<String, String>,
+}
+
+impl RunOutput {
+    /// Adds the results of another cargo test run that was part of the same run
+    pub fn extend(&mut self, other: RunOutput) {
+        self.groups.extend(other.groups);
+        self.durations.extend(other.durations);
+        self.outputs.extend(other.outputs);
+    }
+}
+
+#[derive(Clone)]
+/// Which tests a run should include
+pub enum RunScope {
+    /// Runs every test, the results replace the whole test list
+    All,
+    /// Runs only the given tests, the results are merged into the existing test list
+    Tests(Vec<ParsedTest>),
+    /// Runs every test under the given module path, the results are merged into the existing test list
+    Module(String),
+    /// Runs every test in a target, e.g. the lib or an integration test file, given by its name and the cargo args that pick it.
+    /// The results are merged into the existing test list
+    Target(String, Vec<String>),
+}
+
+impl RunScope {
+    /// Whether the results of the run should be merged into the existing tests rather than replacing them
+    pub fn is_partial(&self) -> bool {
+        !matches!(self, RunScope::All)
+    }
+
+    /// A short description of the tests that were run, used when showing the results of a partial run
+    pub fn description(&self) -> String {
+        match self {
+            RunScope::All => String::from("all tests"),
+            RunScope::Tests(tests) if tests.len() == 1 => tests[0].module_path.clone(),
+            RunScope::Tests(tests) => format!("{} tests", tests.len()),
+            RunScope::Module(path) => format!("module {}", path),
+            RunScope::Target(name, _) => name.clone(),
+        }
+    }
+
+    /// The args passed to the test binaries after the --, one set for each time cargo test has to be run
+    pub fn filter_args(&self) -> Vec<Vec<String>> {
+        match self {
+            RunScope::All => vec![Vec::new()],
+            RunScope::Tests(tests) => {
+                // test_id() is the name cargo test prints, so normal tests can be matched exactly
+                let mut exact = Vec::new();
+                // rustdoc splits the filters it is given on whitespace so doc test names can't be matched exactly. They are run
+                // on their own, filtered by the item they document, so the normal tests can still use --exact. This also
+                // runs other doc tests whose names contain the item, e.g. foo::bar also runs the tests for foo::bar::baz
+                let mut documented = Vec::new();
+
+                for test in tests {
+                    let filter = match test.test_type {
+                        GeneralTestType::Normal => {
+                            exact.push(test_id(test));
+                            continue;
+                        }
+                        GeneralTestType::Doc => match split_doc_test_path(&test.module_path).0 {
+                            // crate docs don't document an item so every doc test in the file is run
+                            "" => test.file_path.clone().unwrap_or_default(),
+                            item => item.to_string(),
+                        },
+                    };
+                    if !documented.contains(&filter) {
+                        documented.push(filter)
+                    }
+                }
+
+                let mut filters = Vec::new();
+                if !exact.is_empty() {
+                    exact.push(String::from("--exact"));
+                    filters.push(exact)
+                }
+                if !documented.is_empty() {
+                    filters.push(documented)
+                }
+                filters
+            }
+            // libtest filters match anywhere in a test's name so this also runs modules with the same path inside
+            // other modules, e.g. a::tests:: also matches b::a::tests::. Their results are merged in like any other test
+            RunScope::Module(path) => vec![vec![format!("{}::", path)]],
+            RunScope::Target(_, _) => vec![Vec::new()],
+        }
+    }
+
+    /// Adds the filters for this scope onto the end of the args from RunArgs, giving the args for each time cargo test has to be run
+    pub fn apply(&self, mut args: Vec<String>) -> Vec<Vec<String>> {
+        // the target args are for cargo so they go before the --
+        if let RunScope::Target(_, target) = self {
+            let position = args.iter().position(|x| x == "--").unwrap_or(args.len());
+            args.splice(position..position, target.iter().cloned());
+        }
+
+        self.filter_args()
+            .into_iter()
+            .map(|x| add_test_args(args.clone(), x))
+            .collect()
+    }
+}
+
+/// The scope that runs every test in the target a group of tests came from, None if the target can't be told from the group's path.
+/// Targets in other packages with the same name are also run, their results are merged in like any other test
+pub fn target_scope(group: &ParsedTestGroup) -> Option<RunScope> {
+    // the path is relative to where cargo test was run, so in a workspace it starts with the package's folder
+    let path: Vec<&str> = group
+        .file_path
+        .iter()
+        .flat_map(|x| x.split(['/', '\\']))
+        .filter(|x| !x.is_empty())
+        .collect();
+    let start = path
+        .iter()
+        .position(|x| ["src", "tests", "benches", "examples"].contains(x));
+    // cargo names a target after its file, or its folder for a target made of several files, but the name in the
+    // group has its dashes changed to underscores so the name is taken from the path instead
+    let target = |flag: &str, kind: &str, file: &str| {
+        let name = file.trim_end_matches(".rs").to_string();
+        (format!("{} {}", kind, name), vec![flag.to_string(), name])
+    };
+
+    let (description, args) = match start.map(|x| &path[x..]) {
+        // doc tests don't have a path
+        None if path.is_empty() && group.crate_name == "Doc-tests" => {
+            (String::from("doc tests"), vec![String::from("--doc")])
+        }
+        Some(["src", "lib.rs"]) => (String::from("lib"), vec![String::from("--lib")]),
+        // the main binary is named after its package, which isn't in the path
+        Some(["src", "main.rs"]) => (String::from("bins"), vec![String::from("--bins")]),
+        Some(["src", "bin", file, ..]) => target("--bin", "bin", file),
+        Some(["tests", file, ..]) => target("--test", "integration", file),
+        Some(["benches", file, ..]) => target("--bench", "bench", file),
+        Some(["examples", file, ..]) => target("--example", "example", file),
+        _ => return None,
+    };
+
+    Some(RunScope::Target(description, args))
+}
+
+/// Adds args for the test binaries onto the end of the cargo args, adding the -- separator if it isn't there yet
+pub fn add_test_args(mut args: Vec<String>, test_args: Vec<String>) -> Vec<String> {
+    if test_args.is_empty() {
+        return args;
+    }
+
+    if !args.contains(&String::from("--")) {
+        args.push(String::from("--"))
+    }
+
+    args.extend(test_args);
+    args
+}
+
+#[derive(Clone, Default)]
+/// Shared between the UI and a running cargo test process so the run can be cancelled
+pub struct RunHandle {
+    /// The process id of cargo test, this is also the id of its process group on unix
+    pid: Arc<Mutex<Option<u32>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RunHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether both handles are for the same run
+    pub fn is_same(&self, other: &RunHandle) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Kills cargo and any test binaries it has started
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        let pid = match *self.pid.lock().unwrap() {
+            Some(res) => res,
+            // the process hasn't started yet, it will see the cancelled flag once it has been spawned
+            None => return,
+        };
+
+        // cargo runs the test binaries as child processes so the whole tree has to be killed, not just cargo
+        #[cfg(unix)]
+        let result = Command::new("kill")
+            .args(["-s", "KILL", "--", format!("-{}", pid).as_str()])
+            .status();
+        #[cfg(windows)]
+        let result = Command::new("taskkill")
+            .args(["/PID", pid.to_string().as_str(), "/T", "/F"])
+            .status();
+
+        if let Err(err) = result {
+            warning!("Could not kill cargo test process {}: {}", pid, err);
+        }
+    }
+}
+
+/// Gets the time from the end of a test line, e.g. "test a::b ... ok <0.012s>"
+fn parse_test_time(line: &str, test_time: &Regex) -> Option<f64> {
+    test_time.captures(line)?["time"].parse::<f64>().ok()
+}
+
+/// Parses a single line of cargo test output into a test, returns None if the line is not a test result
+fn parse_test_line(line: &str, test_line: &Regex, doc_test_line: &Regex) -> Option<ParsedTest> {
+    let status = |status: &str| match status {
+        x if x.contains("ok") => Status::Passed,
+        x if x.contains("FAILED") => Status::Failed,
+        x if x.contains("ignored") => Status::Ignored,
+        _ => Status::Passed,
+    };
+
+    if let Some(capture) = doc_test_line.captures(line) {
+        return Some(ParsedTest {
+            test_type: GeneralTestType::Doc,
+            module_path: doc_test_path(
+                capture.name("module_path").map_or("", |x| x.as_str()),
+                capture["line_num"].parse().unwrap_or(0),
+            ),
+            status: status(&capture["status"]),
+            file_path: Some(capture["file_path"].to_string()),
+            note: capture.name("note").map(|x| x.as_str().to_string()),
+            error_reason: None,
+            ignore_reason: None,
+        });
+    }
+
+    let capture = test_line.captures(line)?;
+
+    Some(ParsedTest {
+        test_type: GeneralTestType::Normal,
+        module_path: capture["module_path"].to_string(),
+        status: status(&capture["status"]),
+        file_path: None,
+        note: capture.name("note").map(|x| x.as_str().to_string()),
+        error_reason: None,
+        ignore_reason: capture
+            .name("ignore_reason")
+            .map(|x| x.as_str().to_string()),
+    })
+}
+
+/// The part of cargo test's output being read
+enum Section {
+    None,
+    /// The test result lines of a block, with how many are left
+    Tests(usize),
+    /// The output of passing tests, only printed with --show-output
+    Successes,
+    /// The names of the passing tests printed after their output
+    SuccessList,
+    /// The output of failed tests
+    Failures,
+}
+
+/// Takes what each test printed out of cargo test's output, leaving the lines cargo_ptest knows how to parse
+struct CapturedOutput {
+    section: Section,
+    /// The id of the test whose output is being read, cargo test prints tests by their test_id()
+    current: Option<String>,
+    /// The line printed when the test that has started but not finished started, only known with --nocapture on a single thread
+    running: Option<String>,
+    outputs: HashMap<String, Vec<String>>,
+    block_start: Regex,
+    output_header: Regex,
+    test_start: Regex,
+    status: Regex,
+    note: Regex,
+}
+
+impl CapturedOutput {
+    fn new() -> Self {
+        Self {
+            section: Section::None,
+            current: None,
+            running: None,
+            outputs: HashMap::new(),
+            block_start: Regex::new(r"^running (?<count>\d+) tests?$").unwrap(),
+            output_header: Regex::new(r"^---- (?<name>.+) stdout ----$").unwrap(),
+            test_start: Regex::new(r"^test (?<name>.+?) \.\.\. ?(?<rest>.*)$").unwrap(),
+            status: Regex::new(r"^(ok|FAILED|ignored)(,.*|\s+<[\d.]+s>)?$").unwrap(),
+            note: Regex::new(r" - [\w\s]+$").unwrap(),
+        }
+    }
+
+    /// The test_id() of a test from its name in a test line, which can have a note after it e.g. "foo - should panic"
+    fn id(&self, name: &str) -> String {
+        self.note.replace(name, "").to_string()
+    }
+
+    fn push(&mut self, id: Option<String>, line: &str) {
+        if let Some(id) = id {
+            self.outputs.entry(id).or_default().push(line.to_string())
+        }
+    }
+
+    /// Reads a line of cargo test's output, returning the line if it should be parsed or None if it was printed by a test.
+    /// A test line split up by --nocapture output is put back together and returned once the test finishes.
+    fn line(&mut self, line: &str) -> Option<String> {
+        let trimmed = line.trim();
+
+        match self.section {
+            Section::None => {
+                if let Some(capture) = self.block_start.captures(trimmed) {
+                    let count = capture["count"].parse().unwrap_or(0);
+                    if count > 0 {
+                        self.section = Section::Tests(count)
+                    }
+                } else if trimmed == "successes:" {
+                    self.section = Section::Successes;
+                    return None;
+                } else if trimmed == "failures:" {
+                    self.section = Section::Failures
+                }
+                Some(line.to_string())
+            }
+            Section::Tests(remaining) => {
+                // a test binary that aborts never prints the rest of its test lines, so the block also ends at the next block or result
+                if self.block_start.is_match(trimmed)
+                    || trimmed.starts_with("test result:")
+                    || trimmed.starts_with("error: test failed")
+                {
+                    self.section = Section::None;
+                    self.running = None;
+                    return self.line(line);
+                }
+
+                let finished = |section: &mut Section| {
+                    *section = if remaining > 1 {
+                        Section::Tests(remaining - 1)
+                    } else {
+                        Section::None
+                    }
+                };
+
+                if let Some(capture) = self.test_start.captures(trimmed) {
+                    let rest = capture["rest"].to_string();
+                    if self.status.is_match(&rest) {
+                        finished(&mut self.section);
+                        self.running = None;
+                        return Some(line.to_string());
+                    }
+
+                    // on a single thread the name is printed when the test starts so anything it prints comes after it
+                    let name = capture["name"].to_string();
+                    self.running = Some(name.clone());
+                    if !rest.is_empty() {
+                        let id = self.id(&name);
+                        self.push(Some(id), &rest)
+                    }
+                    return None;
+                }
+
+                if let Some(name) = self.running.clone()
+                    && self.status.is_match(trimmed)
+                {
+                    finished(&mut self.section);
+                    self.running = None;
+                    return Some(format!("test {} ... {}", name, trimmed));
+                }
+
+                // output from tests running in parallel can't be matched to a test so it is left out
+                let id = self.running.clone().map(|x| self.id(&x));
+                self.push(id, line);
+                None
+            }
+            Section::Successes => {
+                if let Some(capture) = self.output_header.captures(trimmed) {
+                    self.current = Some(capture["name"].to_string())
+                } else if trimmed == "successes:" {
+                    self.section = Section::SuccessList;
+                    self.current = None;
+                } else {
+                    self.push(self.current.clone(), line)
+                }
+                None
+            }
+            Section::SuccessList => {
+                if trimmed.is_empty() || line.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                self.section = Section::None;
+                self.line(line)
+            }
+            Section::Failures => {
+                if let Some(capture) = self.output_header.captures(trimmed) {
+                    self.current = Some(capture["name"].to_string())
+                } else if trimmed == "failures:" {
+                    // the second failures: is followed by the names of the failed tests
+                    self.section = Section::None;
+                    self.current = None;
+                } else {
+                    self.push(self.current.clone(), line)
+                }
+                Some(line.to_string())
+            }
+        }
+    }
+
+    /// Each test's output with the blank lines separating the sections removed
+    fn finish(self) -> HashMap<String, String> {
+        self.outputs
+            .into_iter()
+            .map(|(id, lines)| (id, lines.join("\n").trim_end().to_string()))
+            .filter(|x| !x.1.trim().is_empty())
+            .collect()
+    }
+}
+
+/// Runs cargo test in the given directory with the given environment variables, sending each test to events as soon as it finishes.
+/// Once the process exits the full output is parsed by cargo_ptest, which also picks up the error reasons and summaries that can't be streamed.
+/// If the run is cancelled through the handle then an error is returned, but any tests already sent to events are still valid.
+pub fn run_streaming(
+    dir: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    events: UnboundedSender<RunEvent>,
+    handle: RunHandle,
+) -> Result<RunOutput, RunError> {
+    let block_start = Regex::new(r"^running \d+ tests?$").unwrap();
+    let test_line = Regex::new(
+        r"^test (?<module_path>[\w:_]+)( - (?<note>[\w\s]+))? \.\.\. (?<status>FAILED|ignored|ok)(, (?<ignore_reason>[\w\s]+))?",
+    )
+    .unwrap();
+    let doc_test_line = Regex::new(
+        r"^test (?<file_path>[\w/\\.]+) -( (?<module_path>[\w/:]+))? \(line (?<line_num>\d+)\)( - (?<note>[\w\s]+))? \.\.\. (?<status>\w+)",
+    )
+    .unwrap();
+
+    let test_time = Regex::new(r"<(?<time>[\d.]+)s>$").unwrap();
+
+    let mut forward_args: Vec<String> = args
+        .into_iter()
+        .filter(|x| !FILTERED_ARGS.contains(&x.as_str()))
+        .collect();
+
+    // the colour arg has to go to cargo rather than the test binary so it needs to be before any --
+    let separator = forward_args
+        .iter()
+        .position(|x| x == "--")
+        .unwrap_or(forward_args.len());
+    forward_args.insert(separator, "--color=never".to_string());
+
+    let mut command = Command::new("cargo");
+    command
+        .arg("test")
+        .args(&forward_args)
+        .current_dir(dir)
+        .envs(env)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // put cargo in its own process group so cancelling can kill the test binaries along with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = match command.spawn() {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(RunError {
+                error: format!("'cargo test' failed: {}", err),
+            });
+        }
+    };
+
+    *handle.pid.lock().unwrap() = Some(child.id());
+
+    if handle.is_cancelled() {
+        handle.cancel()
+    }
+
+    // stderr has to be read on another thread, otherwise cargo can block on a full stderr pipe while we wait on stdout
+    let mut child_stderr = child.stderr.take().unwrap();
+    let stderr_thread = thread::spawn(move || {
+        let mut stderr = String::new();
+        let _ = child_stderr.read_to_string(&mut stderr);
+        stderr
+    });
+
+    let mut stdout = String::new();
+    let mut durations = HashMap::new();
+    let mut captured = CapturedOutput::new();
+    let mut doc_tests = Vec::new();
+
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let line = match line {
+            Ok(res) => res,
+            Err(err) => {
+                warning!("Could not read line from cargo test: {}", err);
+                continue;
+            }
+        };
+
+        // anything the tests printed is taken out here, cargo_ptest can't parse it
+        let Some(line) = captured.line(&line) else {
+            continue;
+        };
+
+        let trimmed = line.trim();
+
+        if block_start.is_match(trimmed) {
+            let _ = events.unbounded_send(RunEvent::GroupStarted);
+        } else if let Some(test) = parse_test_line(trimmed, &test_line, &doc_test_line) {
+            let time = parse_test_time(trimmed, &test_time);
+            if let Some(time) = time {
+                durations.insert(test_id(&test), time);
+            }
+            if test.test_type == GeneralTestType::Doc {
+                doc_tests.push(test.clone())
+            }
+            let _ = events.unbounded_send(RunEvent::TestFinished(test, time));
+        }
+
+        stdout += &line;
+        stdout += "\n";
+    }
+
+    let status = child.wait();
+    let stderr = stderr_thread.join().unwrap_or_default();
+    *handle.pid.lock().unwrap() = None;
+
+    if handle.is_cancelled() {
+        return Err(RunError {
+            error: String::from("The run was cancelled"),
+        });
+    }
+
+    let mut parsed = parse(
+        stdout,
+        stderr.clone(),
+        Config {
+            debug: false,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| err.to_run_error())?;
+
+    // a build failure exits with an error and produces no tests, so surface the compiler error instead of an empty list
+    if parsed.is_empty() && status.map_or(true, |x| !x.success()) {
+        let error = stderr
+            .lines()
+            .find(|x| x.trim_start().starts_with("error"))
+            .unwrap_or("cargo test exited with an error")
+            .to_string();
+        return Err(RunError { error });
+    }
+
+    // cargo_ptest leaves the line number out of doc test names, it is put back from the streamed tests so test_id() stays unique.
+    // The tests are in the same order in both so doc tests on the same item are matched up in order
+    for test in parsed
+        .iter_mut()
+        .flat_map(|x| x.tests.iter_mut())
+        .filter(|x| x.test_type == GeneralTestType::Doc)
+    {
+        let streamed = doc_tests.iter().position(|x| {
+            x.file_path == test.file_path
+                && split_doc_test_path(&x.module_path).0 == test.module_path
+        });
+        if let Some(index) = streamed {
+            test.module_path = doc_tests.remove(index).module_path
+        }
+    }
+
+    Ok(RunOutput {
+        groups: parsed,
+        durations,
+        outputs: captured.finish(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds output through CapturedOutput, giving the lines left for cargo_ptest and what each test printed
+    fn capture(output: &str) -> (Vec<String>, HashMap<String, String>) {
+        let mut captured = CapturedOutput::new();
+        let kept = output.lines().filter_map(|x| captured.line(x)).collect();
+        (kept, captured.finish())
+    }
+
+    fn test(test_type: GeneralTestType, module_path: &str, file_path: Option<&str>) -> ParsedTest {
+        ParsedTest {
+            test_type,
+            module_path: module_path.to_string(),
+            status: Status::Passed,
+            file_path: file_path.map(String::from),
+            note: None,
+            error_reason: None,
+            ignore_reason: None,
+        }
+    }
+
+    #[test]
+    fn filter_args() {
+        let scope = RunScope::Tests(vec![
+            test(GeneralTestType::Normal, "tests::fails", None),
+            test(GeneralTestType::Doc, "add (line 8)", Some("src/lib.rs")),
+            test(GeneralTestType::Doc, "add (line 12)", Some("src/lib.rs")),
+            test(GeneralTestType::Doc, "(line 2)", Some("src/lib.rs")),
+        ]);
+
+        assert_eq!(
+            scope.filter_args(),
+            vec![vec!["tests::fails", "--exact"], vec!["add", "src/lib.rs"],]
+        );
+        assert_eq!(RunScope::All.filter_args(), vec![Vec::<String>::new()]);
+        assert_eq!(
+            RunScope::Module(String::from("utils::tests")).filter_args(),
+            vec![vec!["utils::tests::"]]
+        );
+    }
+
+    #[test]
+    fn apply() {
+        let scope = RunScope::Module(String::from("utils"));
+        assert_eq!(
+            scope.apply(vec![String::from("--workspace")]),
+            vec![vec!["--workspace", "--", "utils::"]]
+        );
+        assert_eq!(
+            scope.apply(vec![String::from("--"), String::from("--nocapture")]),
+            vec![vec!["--", "--nocapture", "utils::"]]
+        );
+    }
+
+    #[test]
+    fn target_scope() {
+        let group = |crate_name: &str, file_path: &[&str]| ParsedTestGroup {
+            crate_name: crate_name.to_string(),
+            file_path: file_path.iter().map(|x| x.to_string()).collect(),
+            tests: Vec::new(),
+            summary: None,
+        };
+        let args = |group: ParsedTestGroup| match super::target_scope(&group) {
+            Some(RunScope::Target(_, args)) => Some(args),
+            _ => None,
+        };
+
+        assert_eq!(
+            args(group("my_crate", &["src", "lib.rs"])),
+            Some(vec!["--lib".to_string()])
+        );
+        assert_eq!(
+            args(group("my_crate", &["src", "main.rs"])),
+            Some(vec!["--bins".to_string()])
+        );
+        assert_eq!(
+            args(group("my_tool", &["crates/my-tool/src/bin/my-tool.rs"])),
+            Some(vec!["--bin".to_string(), "my-tool".to_string()])
+        );
+        assert_eq!(
+            args(group("api", &["tests", "api", "main.rs"])),
+            Some(vec!["--test".to_string(), "api".to_string()])
+        );
+        assert_eq!(
+            args(group("Doc-tests", &[])),
+            Some(vec!["--doc".to_string()])
+        );
+        assert_eq!(args(group("build_script", &["build.rs"])), None);
+
+        let scope = RunScope::Target(String::from("lib"), vec![String::from("--lib")]);
+        assert_eq!(
+            scope.apply(vec![
+                String::from("--workspace"),
+                String::from("--"),
+                String::from("--nocapture")
+            ]),
+            vec![vec!["--workspace", "--lib", "--", "--nocapture"]]
+        );
+    }
+
+    #[test]
+    fn show_output() {
+        let output = "
+running 3 tests
+test tests::fails ... FAILED
+test tests::panics - should panic ... ok
+test tests::prints ... ok
+
+successes:
+
+---- tests::panics stdout ----
+
+thread 'tests::panics' (2232) panicked at src/lib.rs:34:9:
+boom
+
+---- tests::prints stdout ----
+hello \x1b[31mred\x1b[0m
+to stderr
+
+successes:
+    tests::panics
+    tests::prints
+
+failures:
+
+---- tests::fails stdout ----
+before fail
+
+thread 'tests::fails' (2231) panicked at src/lib.rs:29:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+failures:
+    tests::fails
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s";
+        let (kept, outputs) = capture(output);
+
+        // the successes section isn't something cargo_ptest expects so only the rest is kept
+        assert!(
+            !kept
+                .iter()
+                .any(|x| x.contains("successes:") || x.contains("boom"))
+        );
+        assert!(kept.contains(&String::from("test tests::panics - should panic ... ok")));
+        assert!(kept.contains(&String::from("---- tests::fails stdout ----")));
+        assert!(kept.contains(&String::from("    tests::fails")));
+        assert!(kept.last().unwrap().starts_with("test result: FAILED."));
+
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(
+            outputs["tests::prints"],
+            "hello \x1b[31mred\x1b[0m\nto stderr"
+        );
+        assert_eq!(
+            outputs["tests::panics"].trim(),
+            "thread 'tests::panics' (2232) panicked at src/lib.rs:34:9:\nboom"
+        );
+        assert!(outputs["tests::fails"].starts_with("before fail\n\nthread 'tests::fails'"));
+        assert!(outputs["tests::fails"].ends_with("display a backtrace"));
+    }
+
+    #[test]
+    fn nocapture_single_thread() {
+        let output = "
+running 3 tests
+test tests::fails ... before fail
+FAILED
+test tests::panics - should panic ... ok
+test tests::prints ... hello \x1b[31mred\x1b[0m
+ok
+
+failures:
+
+failures:
+    tests::fails
+
+test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s";
+        let (kept, outputs) = capture(output);
+
+        // the test lines split up by what the tests printed are put back together
+        assert_eq!(
+            kept[1..5],
+            [
+                "running 3 tests",
+                "test tests::fails ... FAILED",
+                "test tests::panics - should panic ... ok",
+                "test tests::prints ... ok",
+            ]
+        );
+        assert!(
+            !kept
+                .iter()
+                .any(|x| x.contains("before fail") || x.contains("\x1b[31m"))
+        );
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs["tests::fails"], "before fail");
+        assert_eq!(outputs["tests::prints"], "hello \x1b[31mred\x1b[0m");
+    }
+
+    #[test]
+    fn nocapture_parallel() {
+        let output = "running 2 tests
+printed by one of them
+test tests::a ... ok
+test tests::b ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s";
+        let (kept, outputs) = capture(output);
+
+        assert!(outputs.is_empty());
+        assert!(!kept.iter().any(|x| x.contains("printed")));
+        assert_eq!(kept[1..3], ["test tests::a ... ok", "test tests::b ... ok"]);
+    }
+
+    #[test]
+    fn doc_test_output() {
+        let output = "running 2 tests
+test src/lib.rs - add (line 8) ... ok
+test src/lib.rs - (line 2) ... ok
+
+successes:
+
+---- src/lib.rs - add (line 8) stdout ----
+adding
+
+successes:
+    src/lib.rs - add (line 8)
+    src/lib.rs - (line 2)
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s";
+        let (_, outputs) = capture(output);
+
+        // doc tests are printed by their test_id(), which includes the line number
+        assert_eq!(outputs["src/lib.rs - add (line 8)"], "adding");
+    }
+
+    #[test]
+    fn nocapture_note_removed() {
+        let output = "running 1 test
+test tests::panics - should panic ... about to panic
+ok";
+        let (kept, outputs) = capture(output);
+
+        assert_eq!(kept[1], "test tests::panics - should panic ... ok");
+        assert_eq!(outputs["tests::panics"], "about to panic");
+    }
+
+    #[test]
+    fn aborted_binary() {
+        let output = "running 2 tests
+test tests::a ... ok
+error: test failed, to rerun pass `--lib`
+
+Caused by:
+  process didn't exit successfully: `target/debug/deps/foo-0123` (signal: 6, SIGABRT: process abort signal)
+
+running 1 test
+test tests::c ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s";
+        let (kept, outputs) = capture(output);
+
+        // the lines after the abort and the next binary's tests aren't taken as the aborted test's output
+        assert!(outputs.is_empty());
+        assert!(kept.contains(&String::from("error: test failed, to rerun pass `--lib`")));
+        assert!(kept.contains(&String::from("running 1 test")));
+        assert!(kept.contains(&String::from("test tests::c ... ok")));
+        assert!(kept.last().unwrap().starts_with("test result: ok."));
+    }
+
+    #[test]
+    fn doc_test_paths() {
+        assert_eq!(doc_test_path("add", 12), "add (line 12)");
+        assert_eq!(doc_test_path("", 2), "(line 2)");
+        assert_eq!(split_doc_test_path("add (line 12)"), ("add", Some(12)));
+        assert_eq!(split_doc_test_path("(line 2)"), ("", Some(2)));
+        assert_eq!(split_doc_test_path("add"), ("add", None));
+    }
+}
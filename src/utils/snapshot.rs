@@ -0,0 +1,167 @@
+use crate::state::test_id;
+use crate::utils::history::HistoryEntry;
+use cargo_ptest::parse::{ParsedTest, ParsedTestGroup, Status};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How much a test's duration has to change by, as a fraction of the old duration, to be shown in a diff
+const DURATION_CHANGE_RATIO: f64 = 0.5;
+/// Changes smaller than this many seconds are ignored, very fast tests vary too much between runs to be useful
+const DURATION_CHANGE_MIN: f64 = 0.1;
+
+#[derive(Clone, Serialize, Deserialize)]
+/// A named copy of a project's test results saved so it can be compared against later
+pub struct Snapshot {
+    pub name: String,
+    pub run: HistoryEntry,
+}
+
+#[derive(Clone)]
+/// One side of a diff, either a snapshot or the project's current tests
+pub struct DiffSide {
+    /// Shown in the diff's summary line
+    pub label: String,
+    pub groups: Vec<ParsedTestGroup>,
+    /// Test durations in seconds keyed by test_id()
+    pub durations: HashMap<String, f64>,
+}
+
+impl From<Snapshot> for DiffSide {
+    fn from(snapshot: Snapshot) -> Self {
+        Self {
+            label: snapshot.name,
+            groups: snapshot.run.tests(),
+            durations: snapshot.run.durations,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TestChange {
+    NewlyFailing,
+    NewlyPassing,
+    NewlyIgnored,
+    Added,
+    Removed,
+    Slower,
+    Faster,
+}
+
+impl TestChange {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestChange::NewlyFailing => "Now failing",
+            TestChange::NewlyPassing => "Now passing",
+            TestChange::NewlyIgnored => "Now ignored",
+            TestChange::Added => "Added",
+            TestChange::Removed => "Removed",
+            TestChange::Slower => "Slower",
+            TestChange::Faster => "Faster",
+        }
+    }
+}
+
+#[derive(Clone)]
+/// A test that changed between the two sides of a diff
+pub struct TestDiff {
+    /// The test as it is in the newer side, or the older side if it was removed
+    pub test: ParsedTest,
+    pub changes: Vec<TestChange>,
+    /// The test's duration in the older and newer sides, if they were recorded
+    pub durations: (Option<f64>, Option<f64>),
+}
+
+#[derive(Clone)]
+/// The differences between two sets of test results
+pub struct RunDiff {
+    pub before: String,
+    pub after: String,
+    pub tests: Vec<TestDiff>,
+    /// The index of the selected test from tests
+    pub selected: Option<usize>,
+}
+
+impl RunDiff {
+    /// Compares two sets of results, tests are matched by test_id() and listed in the order they appear in the newer side
+    pub fn new(before: DiffSide, after: DiffSide) -> Self {
+        let old_tests: Vec<ParsedTest> = before.groups.into_iter().flat_map(|x| x.tests).collect();
+        let new_tests: Vec<ParsedTest> = after.groups.into_iter().flat_map(|x| x.tests).collect();
+        let old_ids: HashMap<String, &ParsedTest> =
+            old_tests.iter().map(|x| (test_id(x), x)).collect();
+        let new_ids: HashMap<String, &ParsedTest> =
+            new_tests.iter().map(|x| (test_id(x), x)).collect();
+
+        let mut tests = Vec::new();
+
+        for test in new_tests.iter() {
+            let id = test_id(test);
+            let mut changes = Vec::new();
+            let durations = (
+                before.durations.get(&id).copied(),
+                after.durations.get(&id).copied(),
+            );
+
+            match old_ids.get(&id) {
+                Some(old) => {
+                    if old.status != test.status {
+                        changes.push(match test.status {
+                            Status::Failed => TestChange::NewlyFailing,
+                            Status::Passed => TestChange::NewlyPassing,
+                            Status::Ignored => TestChange::NewlyIgnored,
+                        })
+                    }
+                }
+                None => changes.push(TestChange::Added),
+            }
+
+            if let (Some(old), Some(new)) = durations {
+                let change = new - old;
+
+                if change.abs() >= DURATION_CHANGE_MIN
+                    && change.abs() >= old * DURATION_CHANGE_RATIO
+                {
+                    changes.push(if change > 0.0 {
+                        TestChange::Slower
+                    } else {
+                        TestChange::Faster
+                    })
+                }
+            }
+
+            if !changes.is_empty() {
+                tests.push(TestDiff {
+                    test: test.clone(),
+                    changes,
+                    durations,
+                })
+            }
+        }
+
+        for test in old_tests.iter() {
+            let id = test_id(test);
+
+            if !new_ids.contains_key(&id) {
+                tests.push(TestDiff {
+                    test: test.clone(),
+                    changes: vec![TestChange::Removed],
+                    durations: (before.durations.get(&id).copied(), None),
+                })
+            }
+        }
+
+        Self {
+            before: before.label,
+            after: after.label,
+            tests,
+            selected: None,
+        }
+    }
+
+    /// How many tests have the given change
+    pub fn count(&self, change: TestChange) -> usize {
+        self.tests
+            .iter()
+            .filter(|x| x.changes.contains(&change))
+            .count()
+    }
+}
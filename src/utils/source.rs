@@ -0,0 +1,224 @@
+use crate::state::split_doc_test_path;
+use crate::utils::highlight::{Token, highlight_rust};
+use cargo_ptest::parse::{GeneralTestType, ParsedTest};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// A source file split into lines along with the highlighting for each line
+pub struct SourceFile {
+    pub lines: Vec<String>,
+    pub tokens: Vec<Vec<Token>>,
+}
+
+impl SourceFile {
+    pub fn load(path: &Path) -> Option<Self> {
+        let data = fs::read_to_string(path).ok()?;
+        // tabs are swapped for spaces so the code lines up the same in every file
+        let lines: Vec<String> = data.lines().map(|x| x.replace('\t', "    ")).collect();
+        let tokens = highlight_rust(&lines);
+
+        Some(Self { lines, tokens })
+    }
+}
+
+#[derive(Clone)]
+/// Where a test panicked, from the file:line:col in its error
+pub struct PanicLocation {
+    pub file: String,
+    /// Starts at 1
+    pub line: usize,
+    /// Starts at 1
+    pub column: usize,
+}
+
+/// A file:line:col in a panic or backtrace, e.g. src/lib.rs:12:5
+static LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([\w./\\-]+\.rs):(\d+):(\d+)").unwrap());
+
+/// Gets the first file:line:col in a test's error, which is where it panicked
+pub fn panic_location(error: &str) -> Option<PanicLocation> {
+    let capture = LOCATION.captures(error)?;
+
+    Some(PanicLocation {
+        file: capture[1].replace('\\', "/"),
+        line: capture[2].parse().ok()?,
+        column: capture[3].parse().ok()?,
+    })
+}
+
+/// Every folder up to two levels under the project with a Cargo.toml, e.g. the members of a workspace
+fn package_folders(project_path: &Path) -> Vec<PathBuf> {
+    let mut folders = vec![project_path.to_path_buf()];
+    let mut search = vec![(project_path.to_path_buf(), 0)];
+
+    while let Some((folder, depth)) = search.pop() {
+        let Ok(entries) = fs::read_dir(&folder) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !path.is_dir() || name.starts_with('.') || name == "target" {
+                continue;
+            }
+            if path.join("Cargo.toml").exists() {
+                folders.push(path.clone())
+            }
+            if depth < 1 {
+                search.push((path, depth + 1))
+            }
+        }
+    }
+
+    folders
+}
+
+/// Finds a file from a path given by cargo, which is relative to the project or, for doc tests, to the package it is in
+pub fn resolve_file(project_path: &Path, file: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(file);
+    if path.is_absolute() {
+        return path.exists().then_some(path);
+    }
+
+    package_folders(project_path)
+        .into_iter()
+        .map(|x| x.join(file))
+        .find(|x| x.exists())
+}
+
+/// Follows the modules in a test's module path from the file its target was built from, stopping at the first module declared inline
+fn module_file(root: &Path, modules: &[&str]) -> PathBuf {
+    let mut current = root.to_path_buf();
+
+    for module in modules {
+        let folder = match current.parent() {
+            // the modules of a crate root or mod.rs are next to it, otherwise they are in a folder named after the file
+            Some(parent)
+                if current == root || current.file_name().is_some_and(|x| x == "mod.rs") =>
+            {
+                parent.to_path_buf()
+            }
+            Some(parent) => parent.join(current.file_stem().unwrap_or_default()),
+            None => break,
+        };
+
+        if folder.join(format!("{}.rs", module)).exists() {
+            current = folder.join(format!("{}.rs", module))
+        } else if folder.join(module).join("mod.rs").exists() {
+            current = folder.join(module).join("mod.rs")
+        } else {
+            break;
+        }
+    }
+
+    current
+}
+
+/// The file a test's code is in, the file is the one given by test_file()
+pub fn test_source_file(project_path: &Path, file: &str, test: &ParsedTest) -> Option<PathBuf> {
+    let root = resolve_file(project_path, file)?;
+
+    match test.test_type {
+        // doc tests are always in the file they were run from
+        GeneralTestType::Doc => Some(root),
+        GeneralTestType::Normal => {
+            let segments: Vec<&str> = test.module_path.split("::").collect();
+            Some(module_file(&root, &segments[..segments.len() - 1]))
+        }
+    }
+}
+
+/// The line a test starts on, starting at 0. This is the test function or, for doc tests, the start of the code block in the doc comment
+pub fn find_test_line(lines: &[String], test: &ParsedTest) -> Option<usize> {
+    let (path, doc_line) = match test.test_type {
+        GeneralTestType::Normal => (test.module_path.as_str(), None),
+        GeneralTestType::Doc => split_doc_test_path(&test.module_path),
+    };
+    let name = path.split("::").last().unwrap_or_default();
+
+    match test.test_type {
+        GeneralTestType::Normal => {
+            let function = Regex::new(&format!(r"\bfn\s+{}\s*[(<]", regex::escape(name))).ok()?;
+            let candidates: Vec<usize> = (0..lines.len())
+                .filter(|x| function.is_match(&lines[*x]))
+                .collect();
+
+            // a helper can share a test's name so the one with a test attribute above it is preferred
+            candidates
+                .iter()
+                .find(|x| {
+                    lines[x.saturating_sub(5)..**x]
+                        .iter()
+                        .any(|line| line.trim_start().starts_with("#[") && line.contains("test"))
+                })
+                .or(candidates.first())
+                .copied()
+        }
+        // cargo test gives the line the code block starts on, it is only trusted if the block is still there
+        GeneralTestType::Doc
+            if doc_line.is_some_and(|x| {
+                lines
+                    .get(x.saturating_sub(1))
+                    .is_some_and(|line| line.contains("```"))
+            }) =>
+        {
+            doc_line.map(|x| x - 1)
+        }
+        GeneralTestType::Doc => {
+            // crate docs have no module path
+            let item = if name.is_empty() {
+                lines
+                    .iter()
+                    .position(|x| x.trim_start().starts_with("//!"))?
+            } else {
+                let definition = Regex::new(&format!(
+                    r"\b(fn|struct|enum|trait|type|mod|const|static|union|macro_rules!)\s+{}\b|\bimpl\b.*\b{}\b",
+                    regex::escape(name),
+                    regex::escape(name)
+                ))
+                .ok()?;
+                (0..lines.len()).find(|x| {
+                    let line = lines[*x].trim_start();
+                    !line.starts_with("//") && definition.is_match(line)
+                })?
+            };
+
+            // the code block is in the doc comment above the item, or below it for crate docs
+            let is_doc = |line: &String| {
+                let line = line.trim_start();
+                line.starts_with("///") || line.starts_with("//!") || line.starts_with("#[")
+            };
+            let mut start = item;
+            while start > 0 && is_doc(&lines[start - 1]) {
+                start -= 1
+            }
+            let mut end = item;
+            while end < lines.len() && is_doc(&lines[end]) {
+                end += 1
+            }
+
+            (start..end)
+                .find(|x| lines[*x].contains("```"))
+                .or(Some(start))
+        }
+    }
+}
+
+/// The file a test is in and the line it starts on, the line is None if the test couldn't be found in the file
+pub fn test_location(
+    project_path: &Path,
+    file: &str,
+    test: &ParsedTest,
+) -> Option<(PathBuf, Option<usize>)> {
+    let path = test_source_file(project_path, file, test)?;
+    // only the lines are needed so the file isn't highlighted
+    let line = fs::read_to_string(&path).ok().and_then(|x| {
+        let lines: Vec<String> = x.lines().map(String::from).collect();
+        find_test_line(&lines, test)
+    });
+
+    Some((path, line))
+}
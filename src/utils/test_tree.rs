@@ -0,0 +1,372 @@
+use crate::state::{Project, test_id};
+use cargo_ptest::parse::{GeneralTestType, ParsedTest, ParsedTestGroup, Status};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The folders a package's targets live in, anything in a test's path before one of these is the package's folder
+const TARGET_FOLDERS: [&str; 4] = ["src", "tests", "benches", "examples"];
+
+#[derive(Clone, Copy, Default)]
+/// How many of the tests in a tree node and its children passed, failed and were ignored
+pub struct TestCounts {
+    pub passed: u32,
+    pub failed: u32,
+    pub ignored: u32,
+}
+
+impl TestCounts {
+    fn add(&mut self, status: &Status) {
+        match status {
+            Status::Passed => self.passed += 1,
+            Status::Failed => self.failed += 1,
+            Status::Ignored => self.ignored += 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+/// The order the test list is shown in, nodes in the tree are ordered by the first test inside them
+pub enum TestSort {
+    #[default]
+    /// The order the tests were run in
+    RunOrder,
+    /// Failed tests first, then passed and ignored tests
+    Status,
+    Name,
+    File,
+    /// Slowest tests first, tests without a time are last
+    Duration,
+    /// Tests whose status changed most recently first
+    RecentlyChanged,
+}
+
+impl TestSort {
+    pub const ALL: [TestSort; 6] = [
+        TestSort::RunOrder,
+        TestSort::Status,
+        TestSort::Name,
+        TestSort::File,
+        TestSort::Duration,
+        TestSort::RecentlyChanged,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TestSort::RunOrder => "Run order",
+            TestSort::Status => "Failures first",
+            TestSort::Name => "Name",
+            TestSort::File => "File",
+            TestSort::Duration => "Slowest first",
+            TestSort::RecentlyChanged => "Recently changed",
+        }
+    }
+
+    /// The sort mode after this one, wrapping back round to the first
+    pub fn next(&self) -> TestSort {
+        let position = TestSort::ALL.iter().position(|x| x == self).unwrap_or(0);
+        TestSort::ALL[(position + 1) % TestSort::ALL.len()]
+    }
+
+    /// Where a test goes in the list, tests are in ascending order of their keys
+    fn key(&self, project: &Project, index: usize, file: &str, test: &ParsedTest) -> SortKey {
+        match self {
+            TestSort::RunOrder => (index as i64, String::new()),
+            TestSort::Status => (
+                match test.status {
+                    Status::Failed => 0,
+                    Status::Passed => 1,
+                    Status::Ignored => 2,
+                },
+                test.module_path.clone(),
+            ),
+            TestSort::Name => (0, test.module_path.clone()),
+            TestSort::File => (0, format!("{} {}", file, test.module_path)),
+            // negated so the largest values come first, the key is in microseconds to keep it whole
+            TestSort::Duration => (
+                project
+                    .durations
+                    .get(&test_id(test))
+                    .map_or(0, |x| -(x * 1_000_000.0) as i64),
+                test.module_path.clone(),
+            ),
+            TestSort::RecentlyChanged => (
+                project
+                    .status_changes
+                    .get(&test_id(test))
+                    .map_or(0, |x| -(*x as i64)),
+                test.module_path.clone(),
+            ),
+        }
+    }
+}
+
+/// Tests and nodes are shown in ascending order of these, ties keep the order the tests were run in
+type SortKey = (i64, String);
+
+#[derive(Clone)]
+/// A test in the test tree
+pub struct TreeTest {
+    /// The test's index in tests_linear()
+    pub index: usize,
+    /// The name shown for the test, its module path without the modules shown by the tree
+    pub name: String,
+    pub test: ParsedTest,
+    order: SortKey,
+}
+
+#[derive(Clone)]
+/// A package, test target or module in the test tree
+pub struct TestTreeNode {
+    /// Unique within the project, used to remember whether the node is collapsed
+    pub key: String,
+    pub label: String,
+    pub children: Vec<TestTreeNode>,
+    /// The tests directly inside this node
+    pub tests: Vec<TreeTest>,
+    pub counts: TestCounts,
+    /// The lowest sort key of any test inside the node
+    order: Option<SortKey>,
+}
+
+impl TestTreeNode {
+    fn new(key: String, label: String) -> Self {
+        Self {
+            key,
+            label,
+            children: Vec::new(),
+            tests: Vec::new(),
+            counts: TestCounts::default(),
+            order: None,
+        }
+    }
+
+    /// Gets the child with the given label, adding it if it doesn't exist yet
+    fn child(&mut self, label: &str) -> &mut TestTreeNode {
+        let position = match self.children.iter().position(|x| x.label == label) {
+            Some(res) => res,
+            None => {
+                self.children.push(TestTreeNode::new(
+                    format!("{}/{}", self.key, label),
+                    label.to_string(),
+                ));
+                self.children.len() - 1
+            }
+        };
+
+        &mut self.children[position]
+    }
+
+    /// Adds a test under the given path of child labels, counting it in every node along the way
+    fn insert(&mut self, path: &[String], test: TreeTest) {
+        self.counts.add(&test.test.status);
+        if self.order.as_ref().is_none_or(|x| test.order < *x) {
+            self.order = Some(test.order.clone())
+        }
+
+        match path.split_first() {
+            Some((label, rest)) => self.child(label).insert(rest, test),
+            None => self.tests.push(test),
+        }
+    }
+
+    /// Orders the node's tests and children by their sort keys
+    fn sort(&mut self) {
+        self.tests.sort_by(|a, b| a.order.cmp(&b.order));
+        self.children.sort_by(|a, b| a.order.cmp(&b.order));
+
+        for child in self.children.iter_mut() {
+            child.sort()
+        }
+    }
+}
+
+#[derive(Clone)]
+/// A row of the test list, the tree is flattened into these so only the rows that are on screen have to be rendered
+pub enum TestRow {
+    Node {
+        key: String,
+        label: String,
+        counts: TestCounts,
+        /// How many nodes the row is inside of
+        depth: usize,
+        collapsed: bool,
+    },
+    Test {
+        test: TreeTest,
+        depth: usize,
+    },
+}
+
+fn push_rows(
+    node: TestTreeNode,
+    depth: usize,
+    collapsed: &HashSet<String>,
+    rows: &mut Vec<TestRow>,
+) {
+    let is_collapsed = collapsed.contains(&node.key);
+
+    rows.push(TestRow::Node {
+        key: node.key,
+        label: node.label,
+        counts: node.counts,
+        depth,
+        collapsed: is_collapsed,
+    });
+
+    if is_collapsed {
+        return;
+    }
+
+    for test in node.tests {
+        rows.push(TestRow::Test {
+            test,
+            depth: depth + 1,
+        })
+    }
+    for child in node.children {
+        push_rows(child, depth + 1, collapsed, rows)
+    }
+}
+
+/// Flattens the tree into the rows of the test list, leaving out everything inside collapsed nodes
+pub fn test_rows(tree: Vec<TestTreeNode>, collapsed: &HashSet<String>) -> Vec<TestRow> {
+    let mut rows = Vec::new();
+
+    for node in tree {
+        push_rows(node, 0, collapsed, &mut rows)
+    }
+
+    rows
+}
+
+/// Splits a path on either separator so paths from windows are handled too
+fn path_segments(path: &str) -> Vec<String> {
+    path.split(['/', '\\'])
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+        .collect()
+}
+
+/// The file a test was run from, joined with forward slashes on every platform
+pub fn test_file(group: &ParsedTestGroup, test: &ParsedTest) -> String {
+    // only doc tests have their own path, every other test uses the path from the group's running line
+    match (&test.test_type, &test.file_path) {
+        (GeneralTestType::Doc, Some(file_path)) => path_segments(file_path).join("/"),
+        _ => group
+            .file_path
+            .iter()
+            .flat_map(|x| path_segments(x))
+            .collect::<Vec<String>>()
+            .join("/"),
+    }
+}
+
+/// Every file tests were run from, in the order they were first seen
+pub fn test_files(groups: &Vec<ParsedTestGroup>) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+
+    for group in groups {
+        for test in group.tests.iter() {
+            let file = test_file(group, test);
+            if !file.is_empty() && !files.contains(&file) {
+                files.push(file)
+            }
+        }
+    }
+
+    files
+}
+
+/// Gets the package and test target a test belongs to from the path of the file it was run from
+fn package_and_target(
+    group: &ParsedTestGroup,
+    test: &ParsedTest,
+    file: &str,
+    default_package: &str,
+) -> (String, String) {
+    let path = path_segments(file);
+
+    let split = path
+        .iter()
+        .position(|x| TARGET_FOLDERS.contains(&x.as_str()))
+        .unwrap_or(0);
+    let package = if split == 0 {
+        default_package.to_string()
+    } else {
+        path[..split].join("/")
+    };
+    let target_path = &path[split..];
+
+    let target = if test.test_type == GeneralTestType::Doc {
+        "doc".to_string()
+    } else {
+        match target_path
+            .iter()
+            .map(|x| x.as_str())
+            .collect::<Vec<&str>>()
+            .as_slice()
+        {
+            ["src", "lib.rs"] => "lib".to_string(),
+            ["src", "main.rs"] | ["src", "bin", ..] => format!("bin {}", group.crate_name),
+            ["tests", ..] => format!("integration {}", group.crate_name),
+            ["benches", ..] => format!("bench {}", group.crate_name),
+            ["examples", ..] => format!("example {}", group.crate_name),
+            // tests streamed in before their group was known don't have a path
+            _ if group.crate_name.is_empty() => "tests".to_string(),
+            _ => group.crate_name.clone(),
+        }
+    };
+
+    (package, target)
+}
+
+/// Groups a project's tests by package, test target and module, in the project's sort order.
+/// Tests that aren't included still count towards the indexes.
+pub fn test_tree(
+    project: &Project,
+    include: impl Fn(&ParsedTestGroup, &ParsedTest) -> bool,
+) -> Vec<TestTreeNode> {
+    let mut root = TestTreeNode::new(String::new(), String::new());
+    let default_package = project.display_name();
+    let mut index = 0;
+
+    for group in project.tests.iter().flatten() {
+        for test in group.tests.iter() {
+            if include(group, test) {
+                let file = test_file(group, test);
+                let (package, target) = package_and_target(group, test, &file, &default_package);
+                let mut path = vec![package, target];
+                let mut segments: Vec<String> = test
+                    .module_path
+                    .split("::")
+                    .map(|x| x.to_string())
+                    .collect();
+
+                // doc tests for the crate itself don't have a module path so are named after their file, e.g. "src/lib.rs (line 2)"
+                let name = match segments.pop().filter(|x| !x.is_empty()) {
+                    Some(res) if res.starts_with("(line ") => match &test.file_path {
+                        Some(file_path) => format!("{} {}", file_path, res),
+                        None => res,
+                    },
+                    Some(res) => res,
+                    None => test.file_path.clone().unwrap_or(test.module_path.clone()),
+                };
+                path.extend(segments);
+
+                root.insert(
+                    &path,
+                    TreeTest {
+                        index,
+                        name,
+                        test: test.clone(),
+                        order: project.sort.key(project, index, &file, test),
+                    },
+                );
+            }
+            index += 1;
+        }
+    }
+
+    root.sort();
+    root.children
+}
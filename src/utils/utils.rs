@@ -16,3 +16,19 @@ pub fn get_starting_path() -> Box<Path> {
     let path_box = home_dir.into_boxed_path();
     path_box
 }
+
+/// A 64 bit FNV-1a hash, unlike DefaultHasher this doesn't change between rust versions so it can be used in saved files
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, x| {
+        (hash ^ *x as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Formats a test duration in seconds, e.g. "12ms" or "1.50s"
+pub fn format_duration(seconds: f64) -> String {
+    if seconds < 1.0 {
+        format!("{:.0}ms", seconds * 1000.0)
+    } else {
+        format!("{:.2}s", seconds)
+    }
+}
@@ -0,0 +1,83 @@
+use crate::utils::logger::warning;
+use futures::channel::mpsc::UnboundedSender;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+
+/// Folders that never contain source files, changes in these are always ignored
+const SKIPPED_FOLDERS: [&str; 2] = ["target", ".git"];
+
+/// Builds a matcher from the .gitignore at the root of the project, if there isn't one then nothing is matched
+fn load_gitignore(dir: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(dir);
+
+    if let Some(err) = builder.add(dir.join(".gitignore")) {
+        // a missing .gitignore is normal so this isn't worth a warning
+        if dir.join(".gitignore").exists() {
+            warning!("Could not read .gitignore for {:?}: {}", dir, err);
+        }
+    }
+
+    builder.build().unwrap_or(Gitignore::empty())
+}
+
+/// Whether a change to this path should cause the tests to be re-run
+fn is_relevant(path: &Path, root: &Path, gitignore: &Gitignore) -> bool {
+    let relative = match path.strip_prefix(root) {
+        Ok(res) => res,
+        Err(_) => return false,
+    };
+
+    if relative
+        .components()
+        .any(|x| SKIPPED_FOLDERS.contains(&x.as_os_str().to_str().unwrap_or("")))
+    {
+        return false;
+    }
+
+    !gitignore
+        .matched_path_or_any_parents(path, path.is_dir())
+        .is_ignore()
+}
+
+/// Watches a project folder, sending to changes every time a file that isn't ignored is created, modified or removed.
+/// The folder is watched until the returned watcher is dropped.
+pub fn watch_project(
+    dir: PathBuf,
+    changes: UnboundedSender<()>,
+) -> Result<RecommendedWatcher, String> {
+    let gitignore = load_gitignore(&dir);
+    let root = dir.clone();
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let event = match event {
+            Ok(res) => res,
+            Err(err) => {
+                warning!("File watcher error: {}", err);
+                return;
+            }
+        };
+
+        if !matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            return;
+        }
+
+        if event
+            .paths
+            .iter()
+            .any(|x| is_relevant(x, &root, &gitignore))
+        {
+            let _ = changes.unbounded_send(());
+        }
+    })
+    .map_err(|err| format!("Could not create file watcher: {}", err))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::Recursive)
+        .map_err(|err| format!("Could not watch {:?}: {}", dir, err))?;
+
+    Ok(watcher)
+}
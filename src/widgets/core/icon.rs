@@ -10,10 +10,14 @@ pub enum Icons {
     OpenFolder,
     Trash,
     ChevronDown,
+    ChevronRight,
     Settings,
     Check,
     Ban,
     AlertCircle,
+    History,
+    Compare,
+    Timer,
 }
 
 impl Into<SharedString> for Icons {
@@ -24,10 +28,14 @@ impl Into<SharedString> for Icons {
             Icons::OpenFolder => SharedString::from("svg/open_folder.svg"),
             Icons::Trash => SharedString::from("svg/trash.svg"),
             Icons::ChevronDown => SharedString::from("svg/chevron_down.svg"),
+            Icons::ChevronRight => SharedString::from("svg/chevron_right.svg"),
             Icons::Settings => SharedString::from("svg/settings.svg"),
             Icons::Check => SharedString::from("svg/check.svg"),
             Icons::Ban => SharedString::from("svg/ban.svg"),
             Icons::AlertCircle => SharedString::from("svg/alert_circle.svg"),
+            Icons::History => SharedString::from("svg/history.svg"),
+            Icons::Compare => SharedString::from("svg/compare.svg"),
+            Icons::Timer => SharedString::from("svg/timer.svg"),
         }
     }
 }
@@ -2,7 +2,9 @@ pub mod button;
 pub mod divider;
 pub mod icon;
 pub mod modal;
+pub mod scrollbar;
 pub mod spinner;
 pub mod tooltip;
 pub mod checkbox;
+pub mod text_input;
 mod piechart;
@@ -0,0 +1,138 @@
+use crate::style::{Colour, Size};
+use gpui::{
+    AnyElement, App, AppContext, Bounds, DragMoveEvent, EmptyView, InteractiveElement, IntoElement,
+    MouseButton, ParentElement, Pixels, Point, StatefulInteractiveElement, Styled,
+    UniformListDecoration, UniformListScrollHandle, Window, div, point, px,
+};
+use std::ops::Range;
+
+/// The shortest the thumb can be in pixels, so it can still be grabbed in very long lists
+const MIN_THUMB_HEIGHT: f32 = 20.0;
+
+/// The value dragged while the thumb is held
+struct ScrollbarDrag;
+
+/// A vertical scrollbar drawn over the right hand side of a uniform list, added with uniform_list().with_decoration()
+pub struct Scrollbar {
+    handle: UniformListScrollHandle,
+    /// Width in pixels
+    width: Size,
+    /// Thumb colour in hex e.g. 0xffffff
+    thumb_colour: Colour,
+    /// Track colour in hex e.g. 0xffffff
+    track_colour: Colour,
+}
+
+impl Scrollbar {
+    /// The handle has to be the one the list was given with track_scroll()
+    pub fn new(handle: UniformListScrollHandle) -> Self {
+        Self {
+            handle,
+            width: Size::Px(8.0),
+            thumb_colour: Colour::Rgba(0xffffff44),
+            track_colour: Colour::Rgba(0x00000000),
+        }
+    }
+    pub fn thumb_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.thumb_colour = colour.into();
+        self
+    }
+}
+
+/// Scrolls the list so the middle of the thumb is at the given height in the track
+fn scroll_to(
+    handle: &UniformListScrollHandle,
+    position: Point<Pixels>,
+    track: Bounds<Pixels>,
+    thumb_height: f32,
+    max_scroll: f32,
+) {
+    let track_height = f32::from(track.size.height) - thumb_height;
+    if track_height <= 0.0 {
+        return;
+    }
+
+    let thumb_top = f32::from(position.y - track.top()) - thumb_height / 2.0;
+    let progress = (thumb_top / track_height).clamp(0.0, 1.0);
+
+    handle
+        .0
+        .borrow()
+        .base_handle
+        .set_offset(point(px(0.0), px(-progress * max_scroll)))
+}
+
+impl UniformListDecoration for Scrollbar {
+    fn compute(
+        &self,
+        _visible_range: Range<usize>,
+        bounds: Bounds<Pixels>,
+        scroll_offset: Point<Pixels>,
+        item_height: Pixels,
+        item_count: usize,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> AnyElement {
+        let viewport = f32::from(bounds.size.height);
+        let content = f32::from(item_height) * item_count as f32;
+
+        // nothing to scroll so there's no scrollbar
+        if content <= viewport {
+            return div().into_any_element();
+        }
+
+        let max_scroll = content - viewport;
+        let thumb_height = (viewport * viewport / content).max(MIN_THUMB_HEIGHT);
+        let progress = (-f32::from(scroll_offset.y) / max_scroll).clamp(0.0, 1.0);
+        let thumb_top = (viewport - thumb_height) * progress;
+
+        let click_handle = self.handle.clone();
+        let drag_handle = self.handle.clone();
+
+        div()
+            .flex()
+            .flex_row()
+            .justify_end()
+            .size_full()
+            .child(
+                div()
+                    .id("scrollbar-track")
+                    .relative()
+                    .flex_none()
+                    .h_full()
+                    .w(self.width.get())
+                    .bg(&self.track_colour)
+                    // stops the rows under the scrollbar from being clicked, scrolling still goes to the list
+                    .block_mouse_except_scroll()
+                    .on_mouse_down(MouseButton::Left, move |e, _window, _cx| {
+                        // the track is the same height as the list
+                        scroll_to(&click_handle, e.position, bounds, thumb_height, max_scroll);
+                        _window.refresh()
+                    })
+                    .on_drag(ScrollbarDrag, |_drag, _offset, _window, _cx| {
+                        _cx.new(|_| EmptyView)
+                    })
+                    .on_drag_move(move |e: &DragMoveEvent<ScrollbarDrag>, _window, _cx| {
+                        scroll_to(
+                            &drag_handle,
+                            e.event.position,
+                            e.bounds,
+                            thumb_height,
+                            max_scroll,
+                        );
+                        _window.refresh()
+                    })
+                    .child(
+                        div()
+                            .absolute()
+                            .top(px(thumb_top))
+                            .left(px(0.0))
+                            .w_full()
+                            .h(px(thumb_height))
+                            .rounded(self.width.px() / 2.0)
+                            .bg(&self.thumb_colour),
+                    ),
+            )
+            .into_any_element()
+    }
+}
@@ -0,0 +1,248 @@
+use crate::rounding;
+use crate::style::{Colour, Size};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, ElementId, FocusHandle, InteractiveElement, IntoElement, KeyDownEvent, MouseButton,
+    ParentElement, RenderOnce, Styled, Window, div, px,
+};
+use std::rc::Rc;
+
+type TextInputHandler = Rc<dyn Fn(&String, &mut Window, &mut App) + 'static>;
+
+#[derive(IntoElement)]
+/// A single line text box, the value is owned by the caller and is replaced through on_change whenever it is edited
+pub struct TextInput {
+    id: ElementId,
+    /// The text currently in the input
+    value: String,
+    /// Text shown when the value is empty
+    placeholder: String,
+    /// Width in pixels
+    width: Option<Size>,
+    /// Height in pixels
+    height: Size,
+    /// Text size in pixels
+    text_size: Size,
+    /// Corner rounding in pixels, ordered as (top left, top right, bottom right, bottom left) e.g. clockwise starting at the top left, you can use the rounding!() macro to convert a single value to this form.
+    rounding: (Size, Size, Size, Size),
+    /// Background colour in hex e.g. 0xffffff
+    colour: Colour,
+    /// Text colour in hex e.g. 0xffffff
+    text_colour: Colour,
+    /// Placeholder text colour in hex e.g. 0xffffff
+    placeholder_colour: Colour,
+    /// Border colour
+    border_colour: Colour,
+    /// Border colour while the input is focused
+    focus_border_colour: Colour,
+    /// Border width in pixels
+    border_width: Size,
+    /// Function ran with the new value every time the text is edited
+    on_change: Option<TextInputHandler>,
+    /// Function ran with the current value when enter is pressed
+    on_submit: Option<TextInputHandler>,
+    /// Used instead of the input's own focus handle so it can be focused from elsewhere
+    focus_handle: Option<FocusHandle>,
+}
+
+impl RenderOnce for TextInput {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        // the input is rebuilt every frame so the focus handle has to be kept in element state to stay focused
+        let focus_handle = match self.focus_handle.clone() {
+            Some(res) => res,
+            None => window
+                .use_keyed_state(self.id.clone(), cx, |_, _cx| _cx.focus_handle())
+                .read(cx)
+                .clone(),
+        };
+        let focused = focus_handle.is_focused(window);
+
+        let value = self.value.clone();
+        let on_change = self.on_change.clone();
+        let on_submit = self.on_submit.clone();
+        let click_handle = focus_handle.clone();
+
+        div()
+            .id(self.id)
+            .track_focus(&focus_handle)
+            .flex()
+            .flex_row()
+            .items_center()
+            .overflow_hidden()
+            .when_else(
+                self.width.is_some(),
+                |_self| _self.w(self.width.unwrap().get()),
+                |_self| _self.w_full(),
+            )
+            .h(self.height.get())
+            .px(px(6.0))
+            .text_size(self.text_size.abs())
+            .bg(&self.colour)
+            .border(self.border_width.abs())
+            .when_else(
+                focused,
+                |_self| _self.border_color(&self.focus_border_colour),
+                |_self| _self.border_color(&self.border_colour),
+            )
+            .rounded_tl(self.rounding.0.abs())
+            .rounded_tr(self.rounding.1.abs())
+            .rounded_br(self.rounding.2.abs())
+            .rounded_bl(self.rounding.3.abs())
+            .on_mouse_down(MouseButton::Left, move |_e, _window, _cx| {
+                click_handle.focus(_window)
+            })
+            .on_key_down(move |e: &KeyDownEvent, _window, _cx| {
+                let keystroke = &e.keystroke;
+                let mut new_value = value.clone();
+
+                match keystroke.key.as_str() {
+                    "backspace" => {
+                        new_value.pop();
+                    }
+                    "enter" => {
+                        if let Some(handler) = on_submit.clone() {
+                            handler(&new_value, _window, _cx)
+                        }
+                        _cx.stop_propagation();
+                        return;
+                    }
+                    "escape" => {
+                        _window.blur();
+                        _cx.stop_propagation();
+                        return;
+                    }
+                    "v" if keystroke.modifiers.secondary() => {
+                        match _cx.read_from_clipboard().and_then(|x| x.text()) {
+                            // this is a single line input so any new lines are flattened
+                            Some(text) => new_value += &text.replace(['\n', '\r'], " "),
+                            None => return,
+                        }
+                    }
+                    _ if keystroke.modifiers.control
+                        || keystroke.modifiers.platform
+                        || keystroke.modifiers.alt =>
+                    {
+                        return;
+                    }
+                    _ => match &keystroke.key_char {
+                        Some(character) => new_value += character,
+                        None => return,
+                    },
+                }
+
+                _cx.stop_propagation();
+                if let Some(handler) = on_change.clone() {
+                    handler(&new_value, _window, _cx)
+                }
+            })
+            .when_else(
+                self.value.is_empty() && !focused,
+                |_self| {
+                    _self
+                        .text_color(&self.placeholder_colour)
+                        .child(self.placeholder.clone())
+                },
+                |_self| {
+                    _self
+                        .text_color(&self.text_colour)
+                        .child(self.value.clone())
+                        .when(focused, |__self| {
+                            __self.child(
+                                div()
+                                    .w(px(1.0))
+                                    .h(self.text_size.abs())
+                                    .bg(&self.text_colour),
+                            )
+                        })
+                },
+            )
+    }
+}
+
+impl TextInput {
+    pub fn new(id: impl Into<ElementId>) -> Self {
+        Self {
+            id: id.into(),
+            value: String::new(),
+            placeholder: String::new(),
+            width: None,
+            height: Size::Px(26.0),
+            text_size: Size::Px(13.0),
+            rounding: rounding!(Size::Px(4.0)),
+            colour: Colour::Rgba(0x00000000),
+            text_colour: Colour::Rgb(0xffffff),
+            placeholder_colour: Colour::Rgba(0xffffff66),
+            border_colour: Colour::Rgba(0xffffff22),
+            focus_border_colour: Colour::Rgb(0x2563eb),
+            border_width: Size::Px(1.0),
+            on_change: None,
+            on_submit: None,
+            focus_handle: None,
+        }
+    }
+    /// The text currently in the input
+    pub fn value<T: ToString>(mut self, value: T) -> Self {
+        self.value = value.to_string();
+        self
+    }
+    /// Text shown when the value is empty
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+    /// Width in pixels
+    pub fn w(mut self, width: Size) -> Self {
+        self.width = Some(width);
+        self
+    }
+    /// Height in pixels
+    pub fn h(mut self, height: Size) -> Self {
+        self.height = height;
+        self
+    }
+    /// Text size in pixels
+    pub fn text_size(mut self, size: Size) -> Self {
+        self.text_size = size;
+        self
+    }
+    /// Corner rounding in pixels for all corners
+    pub fn rounding_all(mut self, rounding: Size) -> Self {
+        self.rounding = rounding!(rounding);
+        self
+    }
+    /// Text colour in hex e.g. 0xffffff
+    pub fn text_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.text_colour = colour.into();
+        self
+    }
+    /// Placeholder text colour in hex e.g. 0xffffff
+    pub fn placeholder_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.placeholder_colour = colour.into();
+        self
+    }
+    /// Border colour
+    pub fn border_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.border_colour = colour.into();
+        self
+    }
+    /// Border colour while the input is focused
+    pub fn focus_border_colour<T: Into<Colour>>(mut self, colour: T) -> Self {
+        self.focus_border_colour = colour.into();
+        self
+    }
+    /// Function ran with the new value every time the text is edited
+    pub fn on_change(mut self, handler: impl Fn(&String, &mut Window, &mut App) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+    /// Function ran with the current value when enter is pressed
+    pub fn on_submit(mut self, handler: impl Fn(&String, &mut Window, &mut App) + 'static) -> Self {
+        self.on_submit = Some(Rc::new(handler));
+        self
+    }
+    /// Used instead of the input's own focus handle so it can be focused from elsewhere
+    pub fn focus_handle(mut self, handle: FocusHandle) -> Self {
+        self.focus_handle = Some(handle);
+        self
+    }
+}